@@ -3,14 +3,46 @@ use egui::Ui;
 use egui::Context;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
+use std::fmt;
+use crate::view::RootViewModel;
 
 
 pub trait ApplicationCommand: Any + Send + Sync {
     fn as_any(&self) -> &dyn Any;
+
+    /// What the command does, as shown to the user, e.g. "Add project".
+    fn description(&self) -> String;
+
+    /// The command that undoes this one, given the state before it is handled. Commands that
+    /// record their own history step, or can't be undone, have none.
+    fn inverse(&self, _app: &RootViewModel) -> Option<Box<dyn ApplicationCommand>> {
+        None
+    }
 }
 
+/// Outcome of handling a command; the error describes why the command could not be carried out.
+pub type CommandResult = Result<(), String>;
+
+/// Carries out one type of command on the application state it is given.
 pub trait ApplicationCommandHandler {
-    fn handle(&self, cmd: &dyn Any);
+    fn handle(&self, cmd: &dyn Any, app: &mut RootViewModel) -> CommandResult;
+}
+
+/// Why a command was not carried out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// No handler was registered for the command.
+    Unregistered { command: String },
+    Failed { command: String, reason: String },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Unregistered { command } => write!(f, "{} is not supported", command),
+            CommandError::Failed { command, reason } => write!(f, "{} failed: {}", command, reason),
+        }
+    }
 }
 
 
@@ -27,28 +59,62 @@ impl ApplicationCommandDispatcher {
         self.handlers.insert(TypeId::of::<C>(), Box::new(handler));
     }
 
-    pub fn dispatch(&self, commands: &mut Vec<Box<dyn ApplicationCommand>>) {
+    /// Handles all pending commands in order and returns the errors of those that failed.
+    pub fn dispatch(&mut self, commands: &mut Vec<Box<dyn ApplicationCommand>>, app: &mut RootViewModel) -> Vec<CommandError> {
 
         let all_commands = commands.drain(..).collect::<Vec<_>>();
-        for command in all_commands {
-            self.dispatch_single(command.as_any());
+        all_commands.into_iter()
+            .filter_map(|command| self.dispatch_single(command, app).err())
+            .collect()
+    }
+
+    /// Handles a command and records it in the history with its inverse, so it can be undone.
+    pub fn dispatch_single(&mut self, command: Box<dyn ApplicationCommand>, app: &mut RootViewModel) -> Result<(), CommandError> {
+        let inverse = command.inverse(app);
+        self.handle(command.as_ref(), app)?;
+
+        if let Some(inverse) = inverse {
+            app.history.record_command(command.description(), inverse, command);
         }
+        Ok(())
     }
 
-    pub fn dispatch_single(&self, command: &dyn Any) {
-        let type_id = command.type_id();
+    /// Undoes or redoes steps until `position` steps of the history are applied. The steps are
+    /// taken one at a time, so a command that fails stops the jump and keeps its step.
+    pub fn jump_in_history(&self, app: &mut RootViewModel, position: usize) -> Result<(), CommandError> {
+        let position = position.min(app.history.entries().len());
+        while app.history.position() != position {
+            let current = app.history.position();
+            let step = if position < current { current - 1 } else { current + 1 };
+            if let Some(command) = app.history.step_command(step) {
+                self.handle(command.as_ref(), app)?;
+            }
+            app.jump_in_history(step);
+        }
+        Ok(())
+    }
 
-        if let Some(handler) = self.handlers.get(&type_id) {
-            handler.handle(command);
+    fn handle(&self, command: &dyn ApplicationCommand, app: &mut RootViewModel) -> Result<(), CommandError> {
+        let command_any = command.as_any();
+
+        match self.handlers.get(&command_any.type_id()) {
+            Some(handler) => handler.handle(command_any, app).map_err(|reason| CommandError::Failed { command: command.description(), reason }),
+            None => Err(CommandError::Unregistered { command: command.description() }),
         }
     }
 }
 
 
 pub fn enqueue_ui_command(ui: &mut Ui, command: Box<dyn ApplicationCommand>) {
+    enqueue_command(ui.ctx(), command);
+}
+
+
+/// Queues a command from outside a `Ui`, e.g. after a dialog was closed.
+pub fn enqueue_command(ctx: &Context, command: Box<dyn ApplicationCommand>) {
     let key = Id::new("ui_commands");
 
-    ui.data_mut(|data| {
+    ctx.data_mut(|data| {
         let list = data.get_temp_mut_or_insert_with::<UICommands>(key, UICommands::default);
         list.0.push(command);
     });
@@ -74,3 +140,175 @@ impl Clone for UICommands {
 }
 
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::*;
+    use crate::view::*;
+
+    fn dispatcher() -> ApplicationCommandDispatcher {
+        let mut dispatcher = ApplicationCommandDispatcher::new();
+        dispatcher.register::<ToggleScrollBarVisibility, _>(ToggleScrollBarVisibilityHandler);
+        dispatcher.register::<TrashProject, _>(TrashProjectHandler);
+        dispatcher.register::<TrashColumn, _>(TrashColumnHandler);
+        dispatcher.register::<RestoreFromTrash, _>(RestoreFromTrashHandler);
+        dispatcher.register::<EmptyTrash, _>(EmptyTrashHandler);
+        dispatcher.register::<CloseProject, _>(CloseProjectHandler);
+        dispatcher.register::<SwitchToSource, _>(SwitchToSourceHandler);
+        dispatcher
+    }
+
+    fn view_model(name: &str) -> RootViewModel {
+        let path = std::env::temp_dir().join(format!("correlate-{}-{}.json", name, std::process::id()));
+        let mut config = Configuration::new(path);
+        config.projects = Some(vec![ProjectConfiguration { name: "Project".to_string(), ..Default::default() }]);
+        RootViewModel::default(config)
+    }
+
+    #[test]
+    fn test_commands_change_the_view_model() {
+        let mut dispatcher = dispatcher();
+        let mut view_model = view_model("commands");
+
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![Box::new(ToggleScrollBarVisibility), Box::new(TrashProject { project: 0 })];
+        assert!(dispatcher.dispatch(&mut commands, &mut view_model).is_empty());
+        assert!(commands.is_empty());
+        assert!(view_model.scroll_bar_always_visible);
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(0));
+
+        // The toggle is a step of the history through its inverse, next to the trashed project.
+        assert_eq!(view_model.history.entries().len(), 2);
+        assert_eq!(view_model.history.entries()[0].description, "Toggle scroll bar visibility");
+        dispatcher.jump_in_history(&mut view_model, 0).unwrap();
+        assert!(!view_model.scroll_bar_always_visible);
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(1));
+        dispatcher.jump_in_history(&mut view_model, 2).unwrap();
+        assert!(view_model.scroll_bar_always_visible);
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(0));
+
+        std::fs::remove_file(&view_model.config.path).ok();
+    }
+
+    #[test]
+    fn test_failed_commands_are_reported() {
+        let mut dispatcher = dispatcher();
+        let mut view_model = view_model("failures");
+
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![
+            Box::new(TrashColumn { column: 3 }),
+            Box::new(TrashProject { project: 1 }),
+            Box::new(AddProject),
+        ];
+        let errors = dispatcher.dispatch(&mut commands, &mut view_model);
+        assert_eq!(errors, vec![
+            CommandError::Failed { command: "Trash column".to_string(), reason: "There is no column 3 to trash".to_string() },
+            CommandError::Failed { command: "Trash project".to_string(), reason: "There is no project 1 to trash".to_string() },
+            CommandError::Unregistered { command: "Add project".to_string() },
+        ]);
+        assert_eq!(errors[2].to_string(), "Add project is not supported");
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(1));
+
+        // A command that fails to undo keeps its step.
+        view_model.history.record_command("Switch sheet".to_string(), Box::new(SwitchToSource { index: 5, sheet_idx: 0 }), Box::new(ToggleScrollBarVisibility));
+        assert!(dispatcher.jump_in_history(&mut view_model, 0).is_err());
+        assert_eq!(view_model.history.position(), 1);
+    }
+
+    #[test]
+    fn test_trashed_projects_can_be_restored() {
+        let mut dispatcher = dispatcher();
+        let mut view_model = view_model("trash");
+
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![Box::new(TrashProject { project: 0 })];
+        assert!(dispatcher.dispatch(&mut commands, &mut view_model).is_empty());
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(0));
+        assert_eq!(view_model.config.trash.entries().len(), 1);
+
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![Box::new(RestoreFromTrash { entry: 0 }), Box::new(RestoreFromTrash { entry: 0 })];
+        let errors = dispatcher.dispatch(&mut commands, &mut view_model);
+        assert_eq!(errors, vec![
+            CommandError::Failed { command: "Restore from trash".to_string(), reason: "There is no item 0 in the trash".to_string() },
+        ]);
+        assert_eq!(view_model.config.projects.as_ref().unwrap()[0].name, "Project");
+        assert!(view_model.config.trash.is_empty());
+
+        // Undoing the restore puts the project back in the trash, where it can be deleted, and
+        // keeps settings changed since.
+        view_model.config.trash_retention_days = 7;
+        view_model.jump_in_history(1);
+        assert_eq!(view_model.config.trash.entries().len(), 1);
+        assert_eq!(view_model.config.trash_retention_days, 7);
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![Box::new(EmptyTrash { entry: None })];
+        assert!(dispatcher.dispatch(&mut commands, &mut view_model).is_empty());
+        assert!(view_model.config.trash.is_empty());
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(0));
+
+        std::fs::remove_file(&view_model.config.path).ok();
+    }
+
+    #[test]
+    fn test_undoing_a_trashed_column_takes_it_out_of_the_trash() {
+        let mut dispatcher = dispatcher();
+        let mut view_model = view_model("trash-column");
+        let source = std::env::temp_dir().join(format!("correlate-trash-column-{}.csv", std::process::id()));
+        std::fs::write(&source, "Id\n1\n2\n").unwrap();
+        let file_name = source.file_name().unwrap().to_string_lossy().into_owned();
+        let sheet = DataSheetConfiguration::from_sheet(file_name, None, &[], &[], &[], false);
+        DataSourceConfiguration::new(&source, None, vec![sheet]).save().unwrap();
+        view_model.push_data_source(RootViewModel::load_data_source(source.to_str().unwrap()).unwrap());
+        view_model.switch_to_source(0, 0);
+        view_model.viewer.column_configs.push(ColumnConfiguration { is_virtual: true, ..ColumnConfiguration::test("Note", ColumnType::Text) });
+        for row in view_model.table.iter_mut() {
+            row.cells.push(CellValue::from("x"));
+        }
+        view_model.record_history();
+
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![Box::new(TrashColumn { column: 1 })];
+        assert!(dispatcher.dispatch(&mut commands, &mut view_model).is_empty());
+        view_model.record_history();
+        assert_eq!(view_model.config.trash.entries().len(), 1);
+
+        // Undoing brings the column back, so it leaves the trash until it is removed again.
+        view_model.jump_in_history(0);
+        view_model.sync_trashed_columns();
+        assert_eq!(view_model.viewer.column_configs.len(), 2);
+        assert!(view_model.config.trash.is_empty());
+        view_model.jump_in_history(1);
+        view_model.sync_trashed_columns();
+        assert_eq!(view_model.viewer.column_configs.len(), 1);
+        assert_eq!(view_model.config.trash.entries().len(), 1);
+
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![Box::new(RestoreFromTrash { entry: 0 })];
+        assert!(dispatcher.dispatch(&mut commands, &mut view_model).is_empty());
+        assert_eq!(view_model.table[1].cells[1], CellValue::from("x"));
+        assert!(view_model.config.trash.is_empty());
+
+        std::fs::remove_file(&view_model.config.path).ok();
+        std::fs::remove_file(DataSourceConfiguration::calculate_path(source.to_str().unwrap())).ok();
+        Journal::remove(source.to_str().unwrap()).ok();
+        std::fs::remove_file(&source).ok();
+    }
+
+    #[test]
+    fn test_closed_projects_are_remembered() {
+        let mut dispatcher = dispatcher();
+        let mut view_model = view_model("close");
+        let path = view_model.config.new_project_path("Project");
+        view_model.config.projects.as_mut().unwrap()[0].path = path.clone();
+
+        let mut commands: Vec<Box<dyn ApplicationCommand>> = vec![Box::new(CloseProject { project: 0, save: true })];
+        assert!(dispatcher.dispatch(&mut commands, &mut view_model).is_empty());
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(0));
+        assert!(path.exists());
+        let recent = view_model.config.recent_projects();
+        assert_eq!(recent.iter().map(|r| (r.path.as_str(), r.open)).collect::<Vec<_>>(), vec![(path.to_str().unwrap(), false)]);
+
+        // Closing is undone from the history.
+        view_model.jump_in_history(0);
+        assert_eq!(view_model.config.projects.as_ref().map(Vec::len), Some(1));
+        assert!(!view_model.config.is_project_modified(&view_model.config.projects.as_ref().unwrap()[0]));
+
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&view_model.config.path).ok();
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: correlate [OPTIONS] [FILE]
+
+Arguments:
+  [FILE]                  Project file or data source to open; a data source is added to the project
+                          when it isn't part of it yet
+
+Options:
+  --config <PATH>         Configuration file to use instead of the one in the configuration directory
+  --project <NAME>        Project to open; not with a project file as FILE
+  -h, --help              Print this help";
+
+/// The command-line arguments of the application.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Arguments {
+    pub config: Option<PathBuf>,
+    pub project: Option<String>,
+    pub file: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl Arguments {
+    /// Parses the arguments, without the name of the executable. Options take their value as
+    /// the next argument or after an `=`.
+    pub fn parse(arguments: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            let (name, inline_value) = match argument.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+                _ => (argument, None),
+            };
+            let mut value = || inline_value.clone()
+                .or_else(|| arguments.next())
+                .ok_or_else(|| format!("{} needs a value", name));
+
+            match name.as_str() {
+                "--config" => parsed.config = Some(PathBuf::from(value()?)),
+                "--project" => parsed.project = Some(value()?),
+                "-h" | "--help" => parsed.help = true,
+                option if option.starts_with('-') => return Err(format!("Unknown option {}", option)),
+                _ if parsed.file.is_some() => return Err(format!("Only one file can be opened, got {}", name)),
+                file => parsed.file = Some(PathBuf::from(file)),
+            }
+        }
+
+        // A project file is a project of its own, so there is no other project to pick.
+        if let (Some(project), Some(file)) = (&parsed.project, &parsed.file)
+            && file.extension().is_some_and(|e| e == crate::data::PROJECT_FILE_EXTENSION) {
+            return Err(format!("--project {} can't be combined with the project file {}", project, file.display()));
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Arguments, String> {
+        Arguments::parse(arguments.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(parse(&[]), Ok(Arguments::default()));
+        assert_eq!(parse(&["--config", "my.json", "--project=Sales", "orders.csv"]), Ok(Arguments {
+            config: Some(PathBuf::from("my.json")),
+            project: Some("Sales".to_string()),
+            file: Some(PathBuf::from("orders.csv")),
+            help: false,
+        }));
+        assert!(parse(&["-h"]).unwrap().help);
+
+        assert_eq!(parse(&["--config"]), Err("--config needs a value".to_string()));
+        assert_eq!(parse(&["--verbose"]), Err("Unknown option --verbose".to_string()));
+        assert_eq!(parse(&["a.csv", "b.csv"]), Err("Only one file can be opened, got b.csv".to_string()));
+        assert_eq!(parse(&["--project", "Sales", "team.correlateproj"]), Err("--project Sales can't be combined with the project file team.correlateproj".to_string()));
+    }
+}
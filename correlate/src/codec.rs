@@ -1,14 +1,21 @@
 use crate::data::*;
 use crate::egui_data_table::viewer::*;
 
-pub struct Codec {
-    pub column_configs: Vec<ColumnConfiguration>,
+pub struct Codec<'a> {
+    pub column_configs: &'a [ColumnConfiguration],
+    /// Rows that relation columns can refer to, by column; only needed for decoding.
+    pub relation_targets: Vec<Option<RelationTargets>>,
+    /// Pasted values that couldn't be converted to the type of their column.
+    pub issues: &'a mut Vec<PasteIssue>,
+    /// Number of rows decoded so far; the current row is the last one.
+    pub decoded_rows: usize,
 }
 
-impl RowCodec<Row> for Codec {
+impl RowCodec<Row> for Codec<'_> {
     type DeserializeError = &'static str;
 
     fn create_empty_decoded_row(&mut self) -> Row {
+        self.decoded_rows += 1;
         let cells = self.column_configs.iter()
             .map(|config| config.column_type.default_value())
             .collect();
@@ -27,11 +34,25 @@ impl RowCodec<Row> for Codec {
         column: usize,
         dst_row: &mut Row,
     ) -> Result<(), DecodeErrorBehavior> {
-        if let Some(cell) = dst_row.cells.get_mut(column) {
-            cell.0.replace_range(.., src_data);
-            Ok(())
-        } else {
-            Err(DecodeErrorBehavior::SkipRow)
+        let (Some(cell), Some(config)) = (dst_row.cells.get_mut(column), self.column_configs.get(column)) else {
+            return Err(DecodeErrorBehavior::SkipRow);
+        };
+
+        let targets = self.relation_targets.get(column).and_then(Option::as_ref);
+        match coerce_value(src_data, config, targets) {
+            Ok(value) => {
+                cell.0 = value;
+                Ok(())
+            }
+            Err(error) => {
+                self.issues.push(PasteIssue {
+                    row: self.decoded_rows.saturating_sub(1),
+                    column,
+                    value: src_data.to_string(),
+                    error,
+                });
+                Err(DecodeErrorBehavior::SkipCell)
+            }
         }
     }
 }
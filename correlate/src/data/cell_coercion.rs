@@ -0,0 +1,199 @@
+use std::fmt;
+use chrono::{NaiveDate, NaiveDateTime};
+use crate::data::*;
+
+/// Why a pasted value can't be stored in a column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoercionError {
+    NotANumber,
+    NotADate,
+    NotABool,
+    UnknownOption,
+    UnknownRelation,
+    NoRelatedSource,
+}
+
+impl fmt::Display for CoercionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoercionError::NotANumber => write!(f, "is not a number"),
+            CoercionError::NotADate => write!(f, "is not a date"),
+            CoercionError::NotABool => write!(f, "is not yes or no"),
+            CoercionError::UnknownOption => write!(f, "is not one of the allowed values"),
+            CoercionError::UnknownRelation => write!(f, "doesn't match a row of the related source"),
+            CoercionError::NoRelatedSource => write!(f, "can't be related, as the column has no related source"),
+        }
+    }
+}
+
+/// The rows that the cells of a relation column can refer to.
+#[derive(Debug, Clone, Default)]
+pub struct RelationTargets {
+    /// The related source, as `Source > Sheet`.
+    pub source: String,
+    /// Key and name of each row of the related sheet.
+    pub rows: Vec<(String, String)>,
+}
+
+impl RelationTargets {
+    /// Looks up the related sheet of a relation column.
+    pub fn resolve(data_sources: &[DataSource], config: &ColumnConfiguration) -> Option<Self> {
+        let related = config.related_source.as_deref()?;
+        let (source, sheet) = related.split_once(" > ")?;
+        let data_sheet = data_sources.iter()
+            .filter(|ds| ds.name.as_ref().unwrap_or(&ds.path) == source)
+            .flat_map(|ds| &ds.sheets)
+            .find(|s| s.display_name.as_ref().unwrap_or(&s.name) == sheet)?;
+
+        let mut key_columns = ColumnConfiguration::key_column_indices(&data_sheet.column_configs);
+        if key_columns.is_empty() {
+            key_columns.push(0);
+        }
+        let name_column = ColumnConfiguration::find_name_column_index(&data_sheet.column_configs);
+        let rows = data_sheet.table.iter()
+            .filter_map(|row| {
+                let key = RowKey::from_row(&key_columns, row).filter(|key| !key.is_empty())?;
+                let name = row.cells.get(name_column).map(|c| c.0.clone()).unwrap_or_else(|| key.display());
+                Some((key.to_string(), name))
+            })
+            .collect();
+        Some(Self { source: related.to_string(), rows })
+    }
+}
+
+/// Converts a pasted value into the notation that cells of the column hold: numbers in any of the
+/// usual locale formats, dates as `YYYY-MM-DD`, yes/no as `true`/`false`, options with their
+/// configured case and relations found by the name or key of the related row. Empty values
+/// clear the cell.
+pub fn coerce_value(value: &str, config: &ColumnConfiguration, targets: Option<&RelationTargets>) -> Result<String, CoercionError> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+
+    match config.column_type {
+        ColumnType::Text | ColumnType::MultiSelect => Ok(value.to_string()),
+        ColumnType::Number => coerce_number(trimmed).ok_or(CoercionError::NotANumber),
+        ColumnType::DateTime => coerce_date(trimmed).ok_or(CoercionError::NotADate),
+        ColumnType::Bool => match trimmed.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" | "on" | "x" => Ok("true".to_string()),
+            "false" | "no" | "n" | "0" | "off" => Ok("false".to_string()),
+            _ => Err(CoercionError::NotABool),
+        },
+        ColumnType::Select => match &config.allowed_values {
+            Some(allowed) if !allowed.is_empty() => allowed.iter()
+                .find(|av| av.value.trim().to_lowercase() == trimmed.to_lowercase())
+                .map(|av| av.value.clone())
+                .ok_or(CoercionError::UnknownOption),
+            _ => Ok(value.to_string()),
+        },
+        ColumnType::Relation => {
+            if trimmed.parse::<Relation>().is_ok() {
+                return Ok(trimmed.to_string());
+            }
+            let targets = targets.ok_or(CoercionError::NoRelatedSource)?;
+            let lower = trimmed.to_lowercase();
+            targets.rows.iter()
+                .find(|(_, name)| name.trim().to_lowercase() == lower)
+                .or_else(|| targets.rows.iter().find(|(key, _)| RowKey::parse(key).display().to_lowercase() == lower))
+                .map(|(key, name)| Relation::new(targets.source.clone(), key.clone(), name.clone()).to_string())
+                .ok_or(CoercionError::UnknownRelation)
+        }
+    }
+}
+
+/// Parses numbers like `1234.5`, `1,234.5`, `1.234,5`, `1 234,5` and `12,5`. A single comma
+/// followed by three digits is taken as a thousands separator.
+fn coerce_number(value: &str) -> Option<String> {
+    if value.parse::<f64>().is_ok_and(f64::is_finite) {
+        return Some(value.to_string());
+    }
+
+    let digits: String = value.chars().filter(|c| !c.is_whitespace() && *c != '\'' && *c != '\u{a0}').collect();
+    let decimal = match (digits.rfind(','), digits.rfind('.')) {
+        (Some(comma), Some(point)) => Some(if comma > point { ',' } else { '.' }),
+        (Some(comma), None) => {
+            let single = digits.matches(',').count() == 1;
+            (single && digits.len() - comma - 1 != 3).then_some(',')
+        }
+        (None, Some(_)) => (digits.matches('.').count() == 1).then_some('.'),
+        (None, None) => None,
+    };
+
+    let normalized: String = digits.chars()
+        .filter(|&c| (c != ',' && c != '.') || Some(c) == decimal)
+        .map(|c| if Some(c) == decimal { '.' } else { c })
+        .collect();
+    let number = normalized.parse::<f64>().ok().filter(|n| n.is_finite())?;
+    Some(number.to_string())
+}
+
+fn coerce_date(value: &str) -> Option<String> {
+    if let Some(date_time) = DATE_TIME_FORMATS.iter().find_map(|format| NaiveDateTime::parse_from_str(value, format).ok()) {
+        return Some(date_time.format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+    let date = DATE_FORMATS.iter().find_map(|format| NaiveDate::parse_from_str(value, format).ok())?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// A pasted value that couldn't be converted, so its cell was left unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasteIssue {
+    /// Row within the pasted data.
+    pub row: usize,
+    pub column: usize,
+    pub value: String,
+    pub error: CoercionError,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(column_type: ColumnType) -> ColumnConfiguration {
+        ColumnConfiguration {
+            allowed_values: Some(vec![AllowedValue { value: "High".to_string(), color: [255, 0, 0] }]),
+            ..ColumnConfiguration::test("Column", column_type)
+        }
+    }
+
+    #[test]
+    fn test_numbers_dates_and_bools() {
+        let number = column(ColumnType::Number);
+        assert_eq!(coerce_value("1,234.5", &number, None), Ok("1234.5".to_string()));
+        assert_eq!(coerce_value("1.234,5", &number, None), Ok("1234.5".to_string()));
+        assert_eq!(coerce_value("12,5", &number, None), Ok("12.5".to_string()));
+        assert_eq!(coerce_value("1 000", &number, None), Ok("1000".to_string()));
+        assert_eq!(coerce_value("abc", &number, None), Err(CoercionError::NotANumber));
+
+        let date = column(ColumnType::DateTime);
+        assert_eq!(coerce_value("14/02/2024", &date, None), Ok("2024-02-14".to_string()));
+        assert_eq!(coerce_value("14 Feb 2024", &date, None), Ok("2024-02-14".to_string()));
+        assert_eq!(coerce_value("2024-02-14 10:30", &date, None), Ok("2024-02-14 10:30:00".to_string()));
+        assert_eq!(coerce_value("soon", &date, None), Err(CoercionError::NotADate));
+
+        let bool = column(ColumnType::Bool);
+        assert_eq!(coerce_value("Yes", &bool, None), Ok("true".to_string()));
+        assert_eq!(coerce_value("0", &bool, None), Ok("false".to_string()));
+        assert_eq!(coerce_value("maybe", &bool, None), Err(CoercionError::NotABool));
+        assert_eq!(coerce_value(" ", &bool, None), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_options_and_relations() {
+        let select = column(ColumnType::Select);
+        assert_eq!(coerce_value("high", &select, None), Ok("High".to_string()));
+        assert_eq!(coerce_value("Low", &select, None), Err(CoercionError::UnknownOption));
+
+        let relation = column(ColumnType::Relation);
+        let targets = RelationTargets {
+            source: "People > Sheet1".to_string(),
+            rows: vec![(RowKey(vec!["7".to_string()]).to_string(), "Alice".to_string())],
+        };
+        let expected = Relation::new("People > Sheet1", targets.rows[0].0.clone(), "Alice").to_string();
+        assert_eq!(coerce_value("alice", &relation, Some(&targets)), Ok(expected.clone()));
+        assert_eq!(coerce_value("7", &relation, Some(&targets)), Ok(expected));
+        assert_eq!(coerce_value("Bob", &relation, Some(&targets)), Err(CoercionError::UnknownRelation));
+        assert_eq!(coerce_value("Bob", &relation, None), Err(CoercionError::NoRelatedSource));
+    }
+}
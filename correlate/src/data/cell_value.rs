@@ -19,7 +19,7 @@ impl From<String> for CellValue {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Row {
     pub cells: Vec<CellValue>,
 }
\ No newline at end of file
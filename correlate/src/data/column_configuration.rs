@@ -1,5 +1,5 @@
 ﻿use serde::{Deserialize, Serialize};
-use crate::data::ColumnType;
+use crate::data::{ColumnType, ColumnValidation};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AllowedValue {
@@ -7,7 +7,7 @@ pub struct AllowedValue {
     pub color: [u8; 3],
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ColumnConfiguration {
     pub name: String,
     #[serde(default)]
@@ -15,6 +15,9 @@ pub struct ColumnConfiguration {
     pub column_type: ColumnType,
     #[serde(default)]
     pub is_key: bool,
+    /// Position of this column within the (composite) key of the sheet.
+    #[serde(default)]
+    pub key_order: usize,
     #[serde(default)]
     pub is_name: bool,
     #[serde(default)]
@@ -29,6 +32,11 @@ pub struct ColumnConfiguration {
     pub allowed_values: Option<Vec<AllowedValue>>,
     #[serde(default)]
     pub related_source: Option<String>,
+    #[serde(default)]
+    pub validation: ColumnValidation,
+    /// Wrap the text of the cells to the column width, so rows grow to fit it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub wrap_text: bool,
 }
 
 fn default_true() -> bool {
@@ -36,10 +44,77 @@ fn default_true() -> bool {
 }
 
 impl ColumnConfiguration {
+    /// A visible column without any settings, for tests to adjust.
+    #[cfg(test)]
+    pub fn test(name: &str, column_type: ColumnType) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: None,
+            column_type,
+            is_key: false,
+            key_order: 0,
+            is_name: false,
+            is_virtual: false,
+            is_visible: true,
+            order: 0,
+            width: None,
+            allowed_values: None,
+            related_source: None,
+            validation: ColumnValidation::default(),
+            wrap_text: false,
+        }
+    }
+
     pub fn find_name_column_index(configs: &[ColumnConfiguration]) -> usize {
         configs.iter().position(|c| c.is_name)
             .or_else(|| configs.iter().position(|c| c.name.to_lowercase().contains("name")))
             .or_else(|| configs.iter().position(|c| c.column_type == ColumnType::Text))
             .unwrap_or(0)
     }
+
+    /// Indices of the key columns, in key order. Only physical columns can be part of the key,
+    /// as virtual cell values are themselves stored by key.
+    pub fn key_column_indices(configs: &[ColumnConfiguration]) -> Vec<usize> {
+        let mut indices: Vec<usize> = configs.iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_key && !c.is_virtual)
+            .map(|(i, _)| i)
+            .collect();
+        indices.sort_by_key(|&i| (configs[i].key_order, i));
+        indices
+    }
+
+    /// Adds a column to, or removes it from, the key. Newly added key columns are appended
+    /// at the end of the key order.
+    pub fn set_key(configs: &mut [ColumnConfiguration], column: usize, is_key: bool) {
+        let mut key_columns = Self::key_column_indices(configs);
+        key_columns.retain(|&i| i != column);
+        if is_key {
+            key_columns.push(column);
+        }
+        if let Some(config) = configs.get_mut(column) {
+            config.is_key = is_key;
+        }
+        Self::renumber_key_order(configs, &key_columns);
+    }
+
+    /// Moves a key column one position earlier (`-1`) or later (`1`) in the key order.
+    pub fn move_key(configs: &mut [ColumnConfiguration], column: usize, offset: isize) {
+        let mut key_columns = Self::key_column_indices(configs);
+        let Some(pos) = key_columns.iter().position(|&i| i == column) else {
+            return;
+        };
+        let new_pos = (pos as isize + offset).clamp(0, key_columns.len() as isize - 1) as usize;
+        key_columns.swap(pos, new_pos);
+        Self::renumber_key_order(configs, &key_columns);
+    }
+
+    fn renumber_key_order(configs: &mut [ColumnConfiguration], key_columns: &[usize]) {
+        for config in configs.iter_mut() {
+            config.key_order = 0;
+        }
+        for (order, &idx) in key_columns.iter().enumerate() {
+            configs[idx].key_order = order;
+        }
+    }
 }
\ No newline at end of file
@@ -1,7 +1,6 @@
 ﻿use serde::{Deserialize, Serialize};
 use egui::{Response, Ui};
 use crate::data::*;
-use crate::view::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ColumnType {
@@ -44,10 +43,10 @@ impl ColumnType {
         ui: &mut Ui,
         cell_value: &mut CellValue,
         column_config: &mut ColumnConfiguration,
-        view_model: &mut RootViewModel
+        context: &mut EditorContext
 
     ) -> Option<Response> {
-        self.editor().show(ui, cell_value, column_config, view_model)
+        self.editor().show(ui, cell_value, column_config, context)
     }
 
     pub fn default_value(&self) -> CellValue {
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::data::*;
+
+/// Validation rules of a single column. All rules are optional; empty cells are only
+/// reported when the column is `required`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ColumnValidation {
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(default)]
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub allowed_values_only: bool,
+    #[serde(default)]
+    pub date_from: Option<String>,
+    #[serde(default)]
+    pub date_to: Option<String>,
+    /// Reject edits and pastes that violate a rule, instead of only marking the cell.
+    #[serde(default)]
+    pub reject_invalid: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    Required,
+    NotUnique,
+    PatternMismatch(String),
+    InvalidPattern(String),
+    NotANumber,
+    BelowMinimum(f64),
+    AboveMaximum(f64),
+    NotAllowed,
+    NotADate,
+    BeforeDate(String),
+    AfterDate(String),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Required => write!(f, "A value is required"),
+            ValidationError::NotUnique => write!(f, "The value is not unique"),
+            ValidationError::PatternMismatch(pattern) => write!(f, "The value does not match '{}'", pattern),
+            ValidationError::InvalidPattern(pattern) => write!(f, "The pattern '{}' is not a valid regular expression", pattern),
+            ValidationError::NotANumber => write!(f, "The value is not a number"),
+            ValidationError::BelowMinimum(min) => write!(f, "The value is below the minimum of {}", min),
+            ValidationError::AboveMaximum(max) => write!(f, "The value is above the maximum of {}", max),
+            ValidationError::NotAllowed => write!(f, "The value is not one of the allowed values"),
+            ValidationError::NotADate => write!(f, "The value is not a date"),
+            ValidationError::BeforeDate(from) => write!(f, "The date is before {}", from),
+            ValidationError::AfterDate(to) => write!(f, "The date is after {}", to),
+        }
+    }
+}
+
+impl ColumnValidation {
+    pub fn is_empty(&self) -> bool {
+        *self == Self { reject_invalid: self.reject_invalid, ..Default::default() }
+    }
+
+    /// Checks all rules that only depend on the value of the cell itself.
+    pub fn check(&self, value: &str, config: &ColumnConfiguration) -> Result<(), ValidationError> {
+        self.check_with_pattern(value, config, &self.compile_pattern())
+    }
+
+    /// The pattern has to match the whole value.
+    fn compile_pattern(&self) -> Option<Result<regex::Regex, ValidationError>> {
+        let pattern = self.pattern.as_deref().filter(|p| !p.is_empty())?;
+        Some(regex::Regex::new(&format!("^(?:{})$", pattern))
+            .map_err(|_| ValidationError::InvalidPattern(pattern.to_string())))
+    }
+
+    fn check_with_pattern(
+        &self,
+        value: &str,
+        config: &ColumnConfiguration,
+        pattern: &Option<Result<regex::Regex, ValidationError>>,
+    ) -> Result<(), ValidationError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return if self.required { Err(ValidationError::Required) } else { Ok(()) };
+        }
+
+        if let Some(regex) = pattern {
+            let regex = regex.as_ref().map_err(|e| e.clone())?;
+            if !regex.is_match(value) {
+                return Err(ValidationError::PatternMismatch(self.pattern.clone().unwrap_or_default()));
+            }
+        }
+
+        if self.min.is_some() || self.max.is_some() {
+            let number: f64 = value.parse().map_err(|_| ValidationError::NotANumber)?;
+            if let Some(min) = self.min.filter(|&min| number < min) {
+                return Err(ValidationError::BelowMinimum(min));
+            }
+            if let Some(max) = self.max.filter(|&max| number > max) {
+                return Err(ValidationError::AboveMaximum(max));
+            }
+        }
+
+        if self.allowed_values_only {
+            let allowed = config.allowed_values.as_deref().unwrap_or_default();
+            let values: Vec<&str> = match config.column_type {
+                ColumnType::MultiSelect => value.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()).collect(),
+                _ => vec![value],
+            };
+            if !values.iter().all(|v| allowed.iter().any(|av| av.value == *v)) {
+                return Err(ValidationError::NotAllowed);
+            }
+        }
+
+        let date_from = self.date_from.as_deref().filter(|d| !d.is_empty());
+        let date_to = self.date_to.as_deref().filter(|d| !d.is_empty());
+        if date_from.is_some() || date_to.is_some() {
+            let date = parse_date(value).ok_or(ValidationError::NotADate)?;
+            if let Some(from) = date_from.filter(|from| parse_date(from).is_some_and(|from| date < from)) {
+                return Err(ValidationError::BeforeDate(from.to_string()));
+            }
+            if let Some(to) = date_to.filter(|to| parse_date(to).is_some_and(|to| date > to)) {
+                return Err(ValidationError::AfterDate(to.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The common date notations found in spreadsheets.
+pub const DATE_FORMATS: [&str; 10] = [
+    "%Y-%m-%d", "%Y/%m/%d", "%d-%m-%Y", "%d/%m/%Y", "%d.%m.%Y", "%Y.%m.%d",
+    "%d %b %Y", "%d %B %Y", "%b %d, %Y", "%B %d, %Y",
+];
+/// The common date-time notations found in spreadsheets.
+pub const DATE_TIME_FORMATS: [&str; 8] = [
+    "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M",
+    "%d-%m-%Y %H:%M:%S", "%d-%m-%Y %H:%M", "%d/%m/%Y %H:%M:%S", "%d/%m/%Y %H:%M",
+];
+
+/// Parses the date part of the common date(time) notations found in spreadsheets.
+pub fn parse_date(value: &str) -> Option<chrono::NaiveDate> {
+    let value = value.trim();
+    let date = value.split(['T', ' ']).next().unwrap_or_default();
+    DATE_FORMATS.iter().find_map(|format| chrono::NaiveDate::parse_from_str(value, format).ok())
+        .or_else(|| DATE_TIME_FORMATS.iter()
+            .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+            .map(|date_time| date_time.date()))
+        .or_else(|| DATE_FORMATS.iter().find_map(|format| chrono::NaiveDate::parse_from_str(date, format).ok()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellProblem {
+    pub row: usize,
+    pub column: usize,
+    pub error: ValidationError,
+}
+
+/// All validation problems of a sheet.
+#[derive(Debug, Clone, Default)]
+pub struct Problems {
+    /// Modifications of the table the problems were found at.
+    validated: Option<u64>,
+    rules: Vec<ColumnValidation>,
+    cells: HashMap<(usize, usize), usize>,
+    pub problems: Vec<CellProblem>,
+}
+
+impl Problems {
+    /// Validates the rows of a table after the given number of `modifications`.
+    pub fn validate(column_configs: &[ColumnConfiguration], rows: &[Row], modifications: u64) -> Self {
+        let mut problems = Vec::new();
+        for (column, config) in column_configs.iter().enumerate() {
+            let rules = &config.validation;
+            if rules.is_empty() {
+                continue;
+            }
+
+            let pattern = rules.compile_pattern();
+            let mut rows_by_value: HashMap<&str, Vec<usize>> = HashMap::new();
+            for (row_idx, row) in rows.iter().enumerate() {
+                let value = row.cells.get(column).map(|c| c.0.as_str()).unwrap_or_default();
+                if let Err(error) = rules.check_with_pattern(value, config, &pattern) {
+                    problems.push(CellProblem { row: row_idx, column, error });
+                } else if rules.unique && !value.trim().is_empty() {
+                    rows_by_value.entry(value.trim()).or_default().push(row_idx);
+                }
+            }
+
+            for row_indices in rows_by_value.into_values().filter(|r| r.len() > 1) {
+                problems.extend(row_indices.into_iter().map(|row| CellProblem { row, column, error: ValidationError::NotUnique }));
+            }
+        }
+        problems.sort_by_key(|p| (p.row, p.column));
+
+        Self {
+            validated: Some(modifications),
+            rules: column_configs.iter().map(|c| c.validation.clone()).collect(),
+            cells: problems.iter().enumerate().map(|(i, p)| ((p.row, p.column), i)).collect(),
+            problems,
+        }
+    }
+
+    /// Whether the problems need to be recomputed for the given columns, or because the table
+    /// was modified since.
+    pub fn is_stale(&self, column_configs: &[ColumnConfiguration], modifications: u64) -> bool {
+        self.validated != Some(modifications) || !self.rules.iter().eq(column_configs.iter().map(|c| &c.validation))
+    }
+
+    pub fn cell_problem(&self, row: usize, column: usize) -> Option<&CellProblem> {
+        self.cells.get(&(row, column)).map(|&i| &self.problems[i])
+    }
+
+    pub fn len(&self) -> usize {
+        self.problems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(column_type: ColumnType, validation: ColumnValidation) -> ColumnConfiguration {
+        ColumnConfiguration {
+            is_virtual: true,
+            allowed_values: Some(vec![
+                AllowedValue { value: "Red".to_string(), color: [255, 0, 0] },
+                AllowedValue { value: "Blue".to_string(), color: [0, 0, 255] },
+            ]),
+            validation,
+            ..ColumnConfiguration::test("Column", column_type)
+        }
+    }
+
+    #[test]
+    fn test_cell_rules() {
+        let number = column(ColumnType::Number, ColumnValidation { required: true, min: Some(0.0), max: Some(10.0), ..Default::default() });
+        assert_eq!(number.validation.check("", &number), Err(ValidationError::Required));
+        assert_eq!(number.validation.check("abc", &number), Err(ValidationError::NotANumber));
+        assert_eq!(number.validation.check("-1", &number), Err(ValidationError::BelowMinimum(0.0)));
+        assert_eq!(number.validation.check("11", &number), Err(ValidationError::AboveMaximum(10.0)));
+        assert_eq!(number.validation.check("5", &number), Ok(()));
+
+        let code = column(ColumnType::Text, ColumnValidation { pattern: Some("[A-Z]{3}".to_string()), ..Default::default() });
+        assert_eq!(code.validation.check("ABC", &code), Ok(()));
+        assert!(matches!(code.validation.check("ABCD", &code), Err(ValidationError::PatternMismatch(_))));
+        assert_eq!(code.validation.check("", &code), Ok(()));
+
+        let colors = column(ColumnType::MultiSelect, ColumnValidation { allowed_values_only: true, ..Default::default() });
+        assert_eq!(colors.validation.check("Red, Blue", &colors), Ok(()));
+        assert_eq!(colors.validation.check("Red, Green", &colors), Err(ValidationError::NotAllowed));
+
+        let date = column(ColumnType::DateTime, ColumnValidation {
+            date_from: Some("2024-01-01".to_string()),
+            date_to: Some("2024-12-31".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(date.validation.check("2024-06-01 12:00:00", &date), Ok(()));
+        assert_eq!(date.validation.check("31/12/2023", &date), Err(ValidationError::BeforeDate("2024-01-01".to_string())));
+        assert_eq!(date.validation.check("2025-01-01", &date), Err(ValidationError::AfterDate("2024-12-31".to_string())));
+        assert_eq!(date.validation.check("soon", &date), Err(ValidationError::NotADate));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 2, 14);
+        assert_eq!(parse_date("14 Feb 2024"), date);
+        assert_eq!(parse_date(" Feb 14, 2024 "), date);
+        assert_eq!(parse_date("14.02.2024"), date);
+        assert_eq!(parse_date("2024-02-14T10:30"), date);
+        assert_eq!(parse_date("2024-02-14 10:30:00.5"), date);
+        assert_eq!(parse_date("Feb"), None);
+    }
+
+    #[test]
+    fn test_unique_rule() {
+        let configs = vec![column(ColumnType::Text, ColumnValidation { unique: true, ..Default::default() })];
+        let rows: Vec<Row> = ["a", "b", "a", ""].iter()
+            .map(|v| Row { cells: vec![CellValue::from(*v)] })
+            .collect();
+
+        let problems = Problems::validate(&configs, &rows, 0);
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems.cell_problem(0, 0).map(|p| &p.error), Some(&ValidationError::NotUnique));
+        assert!(problems.cell_problem(1, 0).is_none());
+        assert!(problems.cell_problem(3, 0).is_none());
+        assert!(!problems.is_stale(&configs, 0));
+        assert!(problems.is_stale(&configs, 1));
+    }
+}
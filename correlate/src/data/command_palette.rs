@@ -0,0 +1,159 @@
+use crate::egui_data_table::*;
+
+/// How many recently run entries are kept, and shown on top of the palette.
+const RECENT_CAPACITY: usize = 8;
+
+/// Actions of the table that can be run outside of editing a cell.
+pub const TABLE_ACTIONS: [UiAction; 17] = [
+    UiAction::Undo,
+    UiAction::Redo,
+    UiAction::CopySelection,
+    UiAction::CutSelection,
+    UiAction::PasteInPlace,
+    UiAction::PasteInsert,
+    UiAction::DuplicateRow,
+    UiAction::DeleteSelection,
+    UiAction::DeleteRow,
+    UiAction::SelectionDuplicateValues,
+    UiAction::SelectAll,
+    UiAction::Find,
+    UiAction::Replace,
+    UiAction::NavPageUp,
+    UiAction::NavPageDown,
+    UiAction::NavTop,
+    UiAction::NavBottom,
+];
+
+pub fn table_action_label(action: UiAction) -> &'static str {
+    match action {
+        UiAction::SelectionStartEditing => "Edit cell",
+        UiAction::CancelEdition => "Cancel edit",
+        UiAction::CommitEdition => "Commit edit",
+        UiAction::CommitEditionAndMove(MoveDirection::Up) => "Commit edit and move up",
+        UiAction::CommitEditionAndMove(MoveDirection::Down) => "Commit edit and move down",
+        UiAction::CommitEditionAndMove(MoveDirection::Left) => "Commit edit and move left",
+        UiAction::CommitEditionAndMove(MoveDirection::Right) => "Commit edit and move right",
+        UiAction::Undo => "Undo cell edit",
+        UiAction::Redo => "Redo cell edit",
+        UiAction::MoveSelection(MoveDirection::Up) => "Move selection up",
+        UiAction::MoveSelection(MoveDirection::Down) => "Move selection down",
+        UiAction::MoveSelection(MoveDirection::Left) => "Move selection left",
+        UiAction::MoveSelection(MoveDirection::Right) => "Move selection right",
+        UiAction::CopySelection => "Copy",
+        UiAction::CutSelection => "Cut",
+        UiAction::PasteInPlace => "Paste",
+        UiAction::PasteInsert => "Paste as new rows",
+        UiAction::DuplicateRow => "Duplicate row",
+        UiAction::DeleteSelection => "Clear selected cells",
+        UiAction::DeleteRow => "Delete row",
+        UiAction::NavPageDown => "Page down",
+        UiAction::NavPageUp => "Page up",
+        UiAction::NavTop => "Go to first row",
+        UiAction::NavBottom => "Go to last row",
+        UiAction::SelectionDuplicateValues => "Fill selection with the current cell",
+        UiAction::SelectAll => "Select all",
+        UiAction::Find => "Find",
+        UiAction::Replace => "Find and replace",
+    }
+}
+
+/// Scores how well a query matches a label: all characters of the query have to appear in the
+/// label in order, ignoring case. Characters at the start of a word and consecutive characters
+/// score higher, so "sbv" matches "Toggle scroll bar visibility" better than "Subversion".
+pub fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let label = label.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for query_char in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (position..label.len()).find(|&i| chars_match(query_char, label[i]))?;
+
+        let word_start = found == 0 || !label[found - 1].is_alphanumeric();
+        score += match previous {
+            _ if word_start => 10,
+            Some(previous) if previous + 1 == found => 8,
+            // Letters skipped within a word make the match weaker.
+            _ => 1 - (found - position).min(4) as i32,
+        };
+
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+fn chars_match(query: char, label: char) -> bool {
+    query.to_lowercase().eq(label.to_lowercase())
+}
+
+/// The entries that were run most recently, identified by their label.
+#[derive(Debug, Clone, Default)]
+pub struct RecentEntries(Vec<String>);
+
+impl RecentEntries {
+    pub fn push(&mut self, label: &str) {
+        self.0.retain(|l| l != label);
+        self.0.insert(0, label.to_string());
+        self.0.truncate(RECENT_CAPACITY);
+    }
+
+    /// Position in the recent entries, the most recent first.
+    pub fn rank(&self, label: &str) -> Option<usize> {
+        self.0.iter().position(|l| l == label)
+    }
+
+    /// Orders the labels that match the query: recent entries first when there is no query,
+    /// otherwise by how well they match, with recent entries first among equal matches.
+    pub fn filter<'a>(&self, query: &str, labels: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+        let mut matches = labels.into_iter()
+            .enumerate()
+            .filter_map(|(i, label)| {
+                let score = fuzzy_score(query, label)?;
+                Some((i, score, self.rank(label).unwrap_or(usize::MAX)))
+            })
+            .collect::<Vec<_>>();
+
+        if query.trim().is_empty() {
+            matches.sort_by_key(|&(i, _, recent)| (recent, i));
+        } else {
+            matches.sort_by_key(|&(i, score, recent)| (std::cmp::Reverse(score), recent, i));
+        }
+        matches.into_iter().map(|(i, _, _)| i).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("", "Copy").is_some());
+        assert!(fuzzy_score("cpy", "Copy").is_some());
+        assert!(fuzzy_score("ypc", "Copy").is_none());
+        assert!(fuzzy_score("hide na", "Hide column Name").is_some());
+
+        let word_starts = fuzzy_score("sbv", "Toggle scroll bar visibility").unwrap();
+        let scattered = fuzzy_score("sbv", "Subversion").unwrap();
+        assert!(word_starts > scattered);
+        assert!(fuzzy_score("col", "Hide column").unwrap() > fuzzy_score("col", "Clear modification flag").unwrap());
+    }
+
+    #[test]
+    fn test_recent_entries_come_first() {
+        let labels = ["Copy", "Cut", "Paste", "Add project"];
+        let mut recent = RecentEntries::default();
+        assert_eq!(recent.filter("", labels), vec![0, 1, 2, 3]);
+
+        recent.push("Paste");
+        recent.push("Cut");
+        recent.push("Paste");
+        assert_eq!(recent.filter("", labels), vec![2, 1, 0, 3]);
+
+        // Better matches go first, and equal matches are ordered by recency.
+        assert_eq!(recent.filter("c", labels), vec![1, 0, 3]);
+        assert_eq!(recent.filter("cut", labels), vec![1]);
+    }
+}
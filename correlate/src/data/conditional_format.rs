@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::data::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FormatCondition {
+    GreaterThan(f64),
+    LessThan(f64),
+    Contains(String),
+    Equals(String),
+    DateInPast,
+}
+
+impl FormatCondition {
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            FormatCondition::GreaterThan(x) => value.trim().parse::<f64>().is_ok_and(|v| v > *x),
+            FormatCondition::LessThan(x) => value.trim().parse::<f64>().is_ok_and(|v| v < *x),
+            FormatCondition::Contains(text) => !text.is_empty() && value.to_lowercase().contains(&text.to_lowercase()),
+            FormatCondition::Equals(text) => value == text,
+            FormatCondition::DateInPast => parse_date(value).is_some_and(|d| d < today()),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FormatCondition::GreaterThan(_) => "Greater than",
+            FormatCondition::LessThan(_) => "Less than",
+            FormatCondition::Contains(_) => "Contains",
+            FormatCondition::Equals(_) => "Equals",
+            FormatCondition::DateInPast => "Date in the past",
+        }
+    }
+
+    /// The conditions that make sense for a column of the given type.
+    pub fn available_for(column_type: ColumnType) -> Vec<FormatCondition> {
+        match column_type {
+            ColumnType::Number => vec![FormatCondition::GreaterThan(0.0), FormatCondition::LessThan(0.0), FormatCondition::Equals(String::new())],
+            ColumnType::DateTime => vec![FormatCondition::DateInPast, FormatCondition::Equals(String::new())],
+            ColumnType::Select | ColumnType::Bool => vec![FormatCondition::Equals(String::new())],
+            _ => vec![FormatCondition::Contains(String::new()), FormatCondition::Equals(String::new())],
+        }
+    }
+}
+
+fn today() -> chrono::NaiveDate {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or_default();
+    chrono::NaiveDate::default() + chrono::Days::new(days)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum FormatTarget {
+    #[default]
+    Cell,
+    Row,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CellStyle {
+    #[serde(default)]
+    pub background: Option<[u8; 3]>,
+    #[serde(default)]
+    pub text_color: Option<[u8; 3]>,
+    #[serde(default)]
+    pub bold: bool,
+}
+
+impl CellStyle {
+    /// Applies the set properties of `other` on top of this style.
+    pub fn merge(&mut self, other: &CellStyle) {
+        if other.background.is_some() {
+            self.background = other.background;
+        }
+        if other.text_color.is_some() {
+            self.text_color = other.text_color;
+        }
+        self.bold |= other.bold;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FormatKind {
+    Condition {
+        condition: FormatCondition,
+        #[serde(default)]
+        target: FormatTarget,
+        style: CellStyle,
+    },
+    /// Background interpolated between two colors over the range of a numeric column.
+    ColorScale {
+        min_color: [u8; 3],
+        max_color: [u8; 3],
+    },
+    /// Bar proportional to the value within the range of a numeric column.
+    DataBar {
+        color: [u8; 3],
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConditionalFormat {
+    pub column_name: String,
+    pub kind: FormatKind,
+}
+
+/// How a single cell should be drawn, as resolved from the conditional formats of a sheet.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolvedFormat {
+    pub style: CellStyle,
+    /// Fraction of the cell covered by a data bar, and its color.
+    pub data_bar: Option<(f32, [u8; 3])>,
+}
+
+/// The conditional formats of a sheet, together with the numeric ranges of the columns that
+/// use a color scale or data bar.
+#[derive(Debug, Clone, Default)]
+pub struct ConditionalFormatting {
+    pub formats: Vec<ConditionalFormat>,
+    ranges: HashMap<String, (f64, f64)>,
+    /// The formats and the modifications of the table the ranges were computed for.
+    validated: Option<(Vec<ConditionalFormat>, u64)>,
+}
+
+impl ConditionalFormatting {
+    pub fn new(formats: Vec<ConditionalFormat>) -> Self {
+        Self { formats, ..Default::default() }
+    }
+
+    /// Whether the numeric ranges need to be recomputed, because the formats changed or the
+    /// table was modified since.
+    pub fn is_stale(&self, modifications: u64) -> bool {
+        self.validated.as_ref().is_none_or(|(formats, validated)| *formats != self.formats || *validated != modifications)
+    }
+
+    /// Computes the ranges from the rows of a table after the given number of `modifications`.
+    pub fn update_ranges(&mut self, column_configs: &[ColumnConfiguration], rows: &[Row], modifications: u64) {
+        self.ranges.clear();
+        for format in &self.formats {
+            if matches!(format.kind, FormatKind::Condition { .. }) {
+                continue;
+            }
+            let Some(column) = column_configs.iter().position(|c| c.name == format.column_name) else {
+                continue;
+            };
+            let range = rows.iter()
+                .filter_map(|r| r.cells.get(column).and_then(|c| c.0.trim().parse::<f64>().ok()))
+                .fold(None, |range: Option<(f64, f64)>, v| match range {
+                    Some((min, max)) => Some((min.min(v), max.max(v))),
+                    None => Some((v, v)),
+                });
+            if let Some(range) = range {
+                self.ranges.insert(format.column_name.clone(), range);
+            }
+        }
+        self.validated = Some((self.formats.clone(), modifications));
+    }
+
+    /// Keeps the formats attached to a column when it is renamed.
+    pub fn rename_column(&mut self, old_name: &str, new_name: &str) {
+        for format in self.formats.iter_mut().filter(|f| f.column_name == old_name) {
+            format.column_name = new_name.to_string();
+        }
+    }
+
+    pub fn for_column<'a>(&'a self, column_name: &'a str) -> impl Iterator<Item = (usize, &'a ConditionalFormat)> + 'a {
+        self.formats.iter().enumerate().filter(move |(_, f)| f.column_name == column_name)
+    }
+
+    /// Resolves the format of a cell. Row formats are applied first, so cell formats win.
+    pub fn resolve(&self, column_configs: &[ColumnConfiguration], row: &Row, column: usize) -> ResolvedFormat {
+        let mut resolved = ResolvedFormat::default();
+        if self.formats.is_empty() {
+            return resolved;
+        }
+        let Some(column_name) = column_configs.get(column).map(|c| c.name.as_str()) else {
+            return resolved;
+        };
+        let value_of = |name: &str| {
+            column_configs.iter().position(|c| c.name == name)
+                .and_then(|i| row.cells.get(i))
+                .map(|c| c.0.as_str())
+        };
+
+        for target in [FormatTarget::Row, FormatTarget::Cell] {
+            for format in &self.formats {
+                let FormatKind::Condition { condition, target: t, style } = &format.kind else {
+                    continue;
+                };
+                let applies = *t == target && (target == FormatTarget::Row || format.column_name == column_name);
+                if applies && value_of(&format.column_name).is_some_and(|v| condition.matches(v)) {
+                    resolved.style.merge(style);
+                }
+            }
+        }
+
+        let value = row.cells.get(column).and_then(|c| c.0.trim().parse::<f64>().ok());
+        let range = self.ranges.get(column_name);
+        if let (Some(value), Some(&(min, max))) = (value, range) {
+            let t = if max > min { ((value - min) / (max - min)) as f32 } else { 1.0 };
+            for (_, format) in self.for_column(column_name) {
+                match &format.kind {
+                    FormatKind::ColorScale { min_color, max_color } if resolved.style.background.is_none() => {
+                        resolved.style.background = Some(lerp_color(*min_color, *max_color, t));
+                    }
+                    FormatKind::DataBar { color } => resolved.data_bar = Some((t, *color)),
+                    _ => {}
+                }
+            }
+        }
+        resolved
+    }
+}
+
+fn lerp_color(from: [u8; 3], to: [u8; 3], t: f32) -> [u8; 3] {
+    let t = t.clamp(0.0, 1.0);
+    std::array::from_fn(|i| (from[i] as f32 + (to[i] as f32 - from[i] as f32) * t).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configs() -> Vec<ColumnConfiguration> {
+        vec![
+            ColumnConfiguration::test("Name", ColumnType::Text),
+            ColumnConfiguration::test("Score", ColumnType::Number),
+        ]
+    }
+
+    fn row(name: &str, score: &str) -> Row {
+        Row { cells: vec![CellValue::from(name), CellValue::from(score)] }
+    }
+
+    #[test]
+    fn test_conditions() {
+        assert!(FormatCondition::GreaterThan(5.0).matches("6"));
+        assert!(!FormatCondition::GreaterThan(5.0).matches("five"));
+        assert!(FormatCondition::Contains("ali".to_string()).matches("Alice"));
+        assert!(!FormatCondition::Contains(String::new()).matches("Alice"));
+        assert!(FormatCondition::DateInPast.matches("2000-01-01"));
+        assert!(!FormatCondition::DateInPast.matches("2999-01-01"));
+    }
+
+    #[test]
+    fn test_row_and_cell_styles() {
+        let red = CellStyle { background: Some([255, 0, 0]), ..Default::default() };
+        let bold = CellStyle { bold: true, background: Some([0, 255, 0]), ..Default::default() };
+        let formatting = ConditionalFormatting::new(vec![
+            ConditionalFormat {
+                column_name: "Score".to_string(),
+                kind: FormatKind::Condition { condition: FormatCondition::GreaterThan(5.0), target: FormatTarget::Row, style: red },
+            },
+            ConditionalFormat {
+                column_name: "Name".to_string(),
+                kind: FormatKind::Condition { condition: FormatCondition::Equals("Bob".to_string()), target: FormatTarget::Cell, style: bold },
+            },
+        ]);
+        let configs = configs();
+
+        let alice = formatting.resolve(&configs, &row("Alice", "8"), 0);
+        assert_eq!(alice.style.background, Some([255, 0, 0]));
+        assert!(!alice.style.bold);
+
+        let bob = formatting.resolve(&configs, &row("Bob", "8"), 0);
+        assert_eq!(bob.style.background, Some([0, 255, 0]));
+        assert!(bob.style.bold);
+
+        let bob_score = formatting.resolve(&configs, &row("Bob", "1"), 1);
+        assert_eq!(bob_score, ResolvedFormat::default());
+    }
+
+    #[test]
+    fn test_color_scale_and_data_bar() {
+        let mut formatting = ConditionalFormatting::new(vec![
+            ConditionalFormat { column_name: "Score".to_string(), kind: FormatKind::ColorScale { min_color: [0, 0, 0], max_color: [200, 100, 0] } },
+            ConditionalFormat { column_name: "Score".to_string(), kind: FormatKind::DataBar { color: [0, 0, 255] } },
+        ]);
+        let configs = configs();
+        let rows = vec![row("a", "0"), row("b", "5"), row("c", "10")];
+        assert!(formatting.is_stale(0));
+        formatting.update_ranges(&configs, &rows, 0);
+        assert!(!formatting.is_stale(0));
+        assert!(formatting.is_stale(1));
+
+        let middle = formatting.resolve(&configs, &rows[1], 1);
+        assert_eq!(middle.style.background, Some([100, 50, 0]));
+        assert_eq!(middle.data_bar, Some((0.5, [0, 0, 255])));
+    }
+}
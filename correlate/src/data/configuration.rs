@@ -1,16 +1,54 @@
 ﻿use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::data::*;
 
+/// How many closed projects are remembered.
+const MAX_RECENT_PROJECTS: usize = 10;
+
+/// A project file that was opened before.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecentProject {
+    pub path: String,
+    /// Whether the project is opened when the application starts.
+    pub open: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Configuration {
     #[serde(skip)]
     pub path: std::path::PathBuf,
 
     pub selected_index: Option<usize>,
-    pub projects: Option<Vec<ProjectConfiguration>>
+    /// The open projects, each stored in its own project file. Earlier versions kept them in
+    /// the configuration, so those are still read and moved to project files.
+    #[serde(default, skip_serializing)]
+    pub projects: Option<Vec<ProjectConfiguration>>,
+    /// Project files, the open ones first.
+    #[serde(default)]
+    pub recent_projects: Vec<RecentProject>,
+    /// Why projects could not be opened.
+    #[serde(skip)]
+    pub project_errors: Vec<String>,
+    /// The open projects as they were last read from or written to their project files.
+    #[serde(skip)]
+    pub saved_projects: Vec<ProjectConfiguration>,
+
+    /// Keyboard shortcuts that differ from the defaults.
+    #[serde(default, skip_serializing_if = "KeyBindings::is_empty")]
+    pub shortcuts: KeyBindings,
+
+    /// Projects, data sources and columns that were removed and can be restored.
+    #[serde(default, skip_serializing_if = "Trash::is_empty")]
+    pub trash: Trash,
+    /// How many days trashed items are kept before they are deleted.
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+}
+
+fn default_trash_retention_days() -> u32 {
+    DEFAULT_TRASH_RETENTION_DAYS
 }
 
 impl Configuration {
@@ -20,12 +58,58 @@ impl Configuration {
         Self {
             path: source_path.as_ref().to_path_buf(),
             selected_index: Some(0),
-            projects: Some(vec![])
+            projects: Some(vec![]),
+            recent_projects: Vec::new(),
+            project_errors: Vec::new(),
+            saved_projects: Vec::new(),
+            shortcuts: KeyBindings::default(),
+            trash: Trash::default(),
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
         }
     }
 }
 
 impl Configuration {
+    /// Where the configuration is stored when no other file is given: in the configuration
+    /// directory of the platform, or the working directory when there is none.
+    pub fn default_path() -> std::path::PathBuf {
+        Self::config_dir()
+            .map(|dir| dir.join("correlate").join("config.json"))
+            .unwrap_or_else(|| "config.json".into())
+    }
+
+    fn config_dir() -> Option<std::path::PathBuf> {
+        use std::env::var_os;
+        let home = || var_os("HOME").map(std::path::PathBuf::from);
+        let dir = if cfg!(target_os = "windows") {
+            var_os("APPDATA").map(std::path::PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            home().map(|home| home.join("Library").join("Application Support"))
+        } else if cfg!(target_arch = "wasm32") {
+            None
+        } else {
+            var_os("XDG_CONFIG_HOME")
+                .map(std::path::PathBuf::from)
+                .or_else(|| home().map(|home| home.join(".config")))
+        };
+        dir.filter(|dir| dir.is_absolute())
+    }
+
+    /// Loads the configuration from [`Self::default_path`]. Earlier versions kept it in the
+    /// working directory, so that one is copied over when there is none yet.
+    pub fn load_default() -> Result<Self, String> {
+        let path = Self::default_path();
+        let legacy = Path::new("config.json");
+        if !path.exists() && legacy.exists() && path != legacy {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::copy(legacy, &path).map_err(|e| e.to_string())?;
+            log::info!("Copied {:?} to {:?}", legacy, path);
+        }
+        Self::load(path)
+    }
+
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
         let path_ref = path.as_ref();
         if !path_ref.exists() {
@@ -37,15 +121,177 @@ impl Configuration {
         let content = fs::read_to_string(path_ref).map_err(|e| e.to_string())?;
         let mut config: Configuration = serde_json::from_str(&content).map_err(|e| e.to_string())?;
         config.path = path_ref.to_path_buf();
+
+        let base = config.base_dir();
+        let legacy = config.projects.replace(Vec::new()).unwrap_or_default();
+        let migrated = !legacy.is_empty();
+        for mut project in legacy {
+            for source in &mut project.data_sources {
+                *source = resolve_path(&base, source);
+            }
+            project.path = config.new_project_path(&project.name);
+            project.save()?;
+            config.saved_projects.push(project.clone());
+            config.projects.get_or_insert_with(Vec::new).push(project);
+        }
+
+        for recent in &mut config.recent_projects {
+            recent.path = resolve_path(&base, &recent.path);
+        }
+        for recent in config.recent_projects.iter().filter(|recent| recent.open) {
+            match ProjectConfiguration::load(&recent.path) {
+                Ok(project) => {
+                    config.saved_projects.push(project.clone());
+                    config.projects.get_or_insert_with(Vec::new).push(project);
+                }
+                Err(e) => {
+                    log::error!("{}", e);
+                    config.project_errors.push(e);
+                }
+            }
+        }
+
+        if migrated {
+            config.save()?;
+            log::info!("Moved the projects of {:?} to project files", config.path);
+        }
         Ok(config)
     }
 
+    /// The folder that the paths of the projects are stored relative to, so a folder with
+    /// the configuration and its projects can be moved as a whole.
+    fn base_dir(&self) -> PathBuf {
+        parent_dir(&self.path)
+    }
+
+    /// A file for a new project, next to the configuration, that no other project uses.
+    pub fn new_project_path(&self, name: &str) -> PathBuf {
+        let file_name = name.chars()
+            .map(|c| if c.is_alphanumeric() || " -_".contains(c) { c } else { '_' })
+            .collect::<String>();
+        let dir = self.base_dir().join("projects");
+        (1..)
+            .map(|n| match n {
+                1 => dir.join(format!("{}.{}", file_name, PROJECT_FILE_EXTENSION)),
+                n => dir.join(format!("{} {}.{}", file_name, n, PROJECT_FILE_EXTENSION)),
+            })
+            .find(|path| !path.exists() && !self.projects.iter().flatten().any(|p| &p.path == path))
+            .unwrap_or_default()
+    }
+
+    /// The open projects followed by the ones that were opened before, most recent first.
+    pub fn recent_projects(&self) -> Vec<RecentProject> {
+        let open = self.projects.iter().flatten()
+            .filter(|project| !project.path.as_os_str().is_empty())
+            .map(|project| project.path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        let closed = self.recent_projects.iter()
+            .filter(|recent| !open.contains(&recent.path))
+            .map(|recent| RecentProject { path: recent.path.clone(), open: false })
+            .take(MAX_RECENT_PROJECTS);
+        open.iter()
+            .map(|path| RecentProject { path: path.clone(), open: true })
+            .chain(closed)
+            .collect()
+    }
+
+    /// Whether a project differs from its project file. Which sheets are shown doesn't count.
+    pub fn is_project_modified(&self, project: &ProjectConfiguration) -> bool {
+        !self.saved_projects.iter().any(|saved| saved.path == project.path
+            && saved.name == project.name
+            && saved.data_sources == project.data_sources
+            && saved.relations == project.relations)
+    }
+
+    /// Writes an open project to its project file.
+    pub fn save_project(&mut self, index: usize) -> Result<(), String> {
+        let project = self.projects.iter().flatten().nth(index).cloned()
+            .ok_or_else(|| format!("There is no project {} to save", index))?;
+        project.save()?;
+        self.saved_projects.retain(|saved| saved.path != project.path);
+        self.saved_projects.push(project);
+        self.save()
+    }
+
+    /// Remembers a project that was closed, so it can be opened again from the recent projects.
+    pub fn remember_project(&mut self, path: &Path) {
+        let path = path.to_string_lossy().into_owned();
+        self.recent_projects.retain(|recent| recent.path != path);
+        self.recent_projects.insert(0, RecentProject { path, open: false });
+    }
+
     pub fn save(&self) -> Result<(), String> {
         if let Some(parent) = self.path.parent() {
             fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
-        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        let mut stored = self.clone();
+        let base = self.base_dir();
+        stored.recent_projects = self.recent_projects();
+        for recent in &mut stored.recent_projects {
+            recent.path = relative_path(&base, &recent.path);
+        }
+        let content = serde_json::to_string_pretty(&stored).map_err(|e| e.to_string())?;
         fs::write(self.path.as_path(), content).map_err(|e| e.to_string())?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projects_are_moved_to_project_files() {
+        let dir = std::env::temp_dir().join(format!("correlate-config-{}", std::process::id()));
+        let source = dir.join("data").join("people.csv").to_string_lossy().into_owned();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config.json"), r#"{
+            "selected_index": 0,
+            "projects": [{ "name": "People/Teams", "data_sources": ["data/people.csv"] }]
+        }"#).unwrap();
+
+        let config = Configuration::load(dir.join("config.json")).unwrap();
+        let project = &config.projects.as_ref().unwrap()[0];
+        assert_eq!(project.data_sources, vec![source.clone()]);
+        assert_eq!(project.path, dir.join("projects").join("People_Teams.correlateproj"));
+
+        let content = fs::read_to_string(&config.path).unwrap();
+        assert!(!content.contains(r#""projects""#));
+        assert!(content.contains(r#""projects/People_Teams.correlateproj""#));
+        assert!(fs::read_to_string(&project.path).unwrap().contains(r#""../data/people.csv""#));
+
+        let mut loaded = Configuration::load(&config.path).unwrap();
+        assert_eq!(loaded.projects, config.projects);
+
+        // A closed project is remembered, but no longer opened.
+        let closed = loaded.projects.take().unwrap().remove(0);
+        loaded.remember_project(&closed.path);
+        loaded.save().unwrap();
+        let reloaded = Configuration::load(&config.path).unwrap();
+        assert_eq!(reloaded.projects, Some(Vec::new()));
+        assert_eq!(reloaded.recent_projects, vec![RecentProject { path: closed.path.to_string_lossy().into_owned(), open: false }]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_projects_are_only_written_when_saved() {
+        let dir = std::env::temp_dir().join(format!("correlate-save-{}", std::process::id()));
+        let mut config = Configuration::new(dir.join("config.json"));
+        let project = ProjectConfiguration { path: config.new_project_path("Sales"), name: "Sales".to_string(), ..Default::default() };
+        config.projects = Some(vec![project.clone()]);
+        assert!(config.is_project_modified(&project));
+
+        config.save().unwrap();
+        assert!(!project.path.exists());
+
+        config.save_project(0).unwrap();
+        assert!(project.path.exists());
+        assert!(!config.is_project_modified(&project));
+
+        let renamed = ProjectConfiguration { name: "Revenue".to_string(), ..project };
+        assert!(config.is_project_modified(&renamed));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use crate::data::*;
 use crate::egui_data_table::*;
 
@@ -14,7 +15,12 @@ pub struct DataSheet {
     pub display_name: Option<String>,
     pub icon: &'static str,
     pub column_configs: Vec<ColumnConfiguration>,
+    pub conditional_formats: Vec<ConditionalFormat>,
     pub table: DataTable<Row>,
+    /// Whether the rows were arranged by hand, so their order is saved.
+    pub manual_row_order: bool,
+    pub frozen: FrozenPanes,
+    pub row_density: RowDensity,
 }
 impl DataSheet {
     pub fn new_from_raw_data(
@@ -39,6 +45,7 @@ impl DataSheet {
                     display_name: None,
                     column_type,
                     is_key: false,
+                    key_order: 0,
                     is_name: false,
                     is_virtual: false,
                     is_visible: true,
@@ -46,6 +53,8 @@ impl DataSheet {
                     width: None,
                     allowed_values: None,
                     related_source: None,
+                    validation: ColumnValidation::default(),
+                    wrap_text: false,
                 });
             }
         } else {
@@ -53,11 +62,21 @@ impl DataSheet {
         }
 
         let mut rows = Vec::new();
+        let mut row_keys = Vec::new();
         let cell_values = config_sheet.cell_values.clone();
 
-        for row_data in raw_rows {
+        // Sheets without a key column fall back to a key derived from the row content.
+        let content_keys = if ColumnConfiguration::key_column_indices(&column_configs).is_empty() {
+            RowKey::from_content(raw_rows.iter().map(|r| r.iter().map(|s| s.as_str())))
+        } else {
+            Vec::new()
+        };
+
+        for (row_idx, row_data) in raw_rows.iter().enumerate() {
             // 1. First pass: get the physical key value if it exists
-            let row_key = DataSheet::get_row_key(&column_configs, row_data);
+            let row_key = DataSheet::get_row_key(&column_configs, row_data)
+                .or_else(|| content_keys.get(row_idx).cloned())
+                .map(|k| k.to_string());
 
             // 2. Second pass: build the row
             let mut cells = Vec::new();
@@ -81,6 +100,11 @@ impl DataSheet {
                 cells.push(column_type);
             }
             rows.push(Row { cells });
+            row_keys.push(row_key);
+        }
+
+        if let Some(order) = &config_sheet.row_order {
+            rows = apply_row_order(rows, &row_keys, order);
         }
 
         let configuration = DataSheetConfiguration {
@@ -89,6 +113,10 @@ impl DataSheet {
             column_configs: column_configs.clone(),
             sort_config: None,
             cell_values: Vec::new(),
+            conditional_formats: config_sheet.conditional_formats.clone(),
+            row_order: None,
+            frozen: FrozenPanes::default(),
+            row_density: RowDensity::default(),
         };
 
         Self {
@@ -98,21 +126,71 @@ impl DataSheet {
             display_name,
             icon,
             column_configs,
+            conditional_formats: config_sheet.conditional_formats.clone(),
             table: rows.into_iter().collect(),
+            manual_row_order: config_sheet.row_order.is_some(),
+            frozen: config_sheet.frozen.clone(),
+            row_density: config_sheet.row_density,
         }
     }
 
-    fn get_row_key(column_configs: &Vec<ColumnConfiguration>, row_data: &Vec<String>) -> Option<String> {
-        let mut row_key = None;
+    fn get_row_key(column_configs: &[ColumnConfiguration], row_data: &[String]) -> Option<RowKey> {
+        let key_columns = ColumnConfiguration::key_column_indices(column_configs);
+        if key_columns.is_empty() {
+            return None;
+        }
+
+        // Raw data only contains the physical columns, so map each config to its physical index.
+        let mut physical_indices = Vec::with_capacity(column_configs.len());
         let mut phys_idx = 0;
         for config in column_configs {
-            if !config.is_virtual {
-                if config.is_key {
-                    row_key = row_data.get(phys_idx).cloned();
-                }
+            if config.is_virtual {
+                physical_indices.push(None);
+            } else {
+                physical_indices.push(Some(phys_idx));
                 phys_idx += 1;
             }
         }
-        row_key
+
+        let parts = key_columns.iter()
+            .map(|&i| physical_indices[i].and_then(|p| row_data.get(p)).cloned().unwrap_or_default())
+            .collect();
+        Some(RowKey(parts))
+    }
+}
+
+/// Arranges rows in a saved manual order. Rows that are not in the order, e.g. rows that were
+/// added to the source since, stay after the row that precedes them in the source.
+fn apply_row_order(rows: Vec<Row>, row_keys: &[Option<String>], order: &[String]) -> Vec<Row> {
+    let positions: HashMap<&str, usize> = order.iter().enumerate()
+        .map(|(position, key)| (key.as_str(), position))
+        .collect();
+
+    let mut previous = None;
+    let mut sort_keys = Vec::with_capacity(rows.len());
+    for (index, key) in row_keys.iter().enumerate() {
+        let known = key.as_deref().and_then(|key| positions.get(key)).copied();
+        sort_keys.push((known.or(previous), known.is_none(), index));
+        previous = known.or(previous);
+    }
+
+    let mut rows: Vec<_> = sort_keys.into_iter().zip(rows).collect();
+    rows.sort_by_key(|(sort_key, _)| *sort_key);
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_row_order() {
+        let rows = ["a", "b", "new", "c"].map(|v| Row { cells: vec![CellValue::from(v)] }).to_vec();
+        let keys = ["a", "b", "new", "c"].map(|v| Some(v.to_string()));
+        let order = ["c", "b", "a", "gone"].map(String::from);
+
+        let rows = apply_row_order(rows, &keys, &order);
+        let values: Vec<_> = rows.iter().map(|row| row.cells[0].0.as_str()).collect();
+        assert_eq!(values, ["c", "b", "new", "a"]);
     }
 }
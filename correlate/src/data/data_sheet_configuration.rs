@@ -1,7 +1,7 @@
 ﻿use serde::{Deserialize, Serialize};
 use crate::data::*;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DataSheetConfiguration {
     pub name: String,
     #[serde(default, rename = "display_name")]
@@ -10,8 +10,100 @@ pub struct DataSheetConfiguration {
     pub sort_config: Option<SortConfiguration>,
     #[serde(default)]
     pub cell_values: Vec<CellValueConfiguration>,
+    #[serde(default)]
+    pub conditional_formats: Vec<ConditionalFormat>,
+    /// Keys of the rows in the order they were arranged in by hand, for sheets with key columns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_order: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "FrozenPanes::is_empty")]
+    pub frozen: FrozenPanes,
+    #[serde(default, skip_serializing_if = "RowDensity::is_normal")]
+    pub row_density: RowDensity,
 }
 
 impl DataSheetConfiguration {
+    /// Builds the configuration that is saved for a sheet, including the values of its
+    /// virtual columns keyed by row, and the order of the rows once they were arranged by hand.
+    pub fn from_sheet(
+        name: String,
+        display_name: Option<String>,
+        column_configs: &[ColumnConfiguration],
+        conditional_formats: &[ConditionalFormat],
+        rows: &[Row],
+        manual_row_order: bool,
+    ) -> Self {
+        let mut column_configs = column_configs.to_vec();
+        for (i, config) in column_configs.iter_mut().enumerate() {
+            config.order = i;
+        }
+
+        let key_columns = ColumnConfiguration::key_column_indices(&column_configs);
+        let virtual_cols: Vec<usize> = column_configs.iter().enumerate()
+            .filter(|(_, c)| c.is_virtual)
+            .map(|(i, _)| i)
+            .collect();
+
+        let mut cell_values = Vec::new();
+        let mut seen_keys = std::collections::HashSet::new();
+
+        let keys = if virtual_cols.is_empty() { Vec::new() } else { Self::row_keys(&column_configs, rows) };
+        for (row, key) in rows.iter().zip(keys) {
+            if key.is_empty() {
+                continue;
+            }
+            if !seen_keys.insert(key.clone()) {
+                log::warn!("Duplicate key '{}' in sheet '{}'; its virtual cell values are ambiguous", key.display(), name);
+            }
+
+            let key = key.to_string();
+            for &v_idx in &virtual_cols {
+                let value = row.cells[v_idx].0.clone();
+                if !value.is_empty() {
+                    cell_values.push(CellValueConfiguration {
+                        key: key.clone(),
+                        column_name: column_configs[v_idx].name.clone(),
+                        value,
+                    });
+                }
+            }
+        }
+
+        // Rows can only be found back by key, so the order of sheets without key columns is lost.
+        let row_order = (manual_row_order && !key_columns.is_empty()).then(|| {
+            rows.iter()
+                .filter_map(|row| RowKey::from_row(&key_columns, row))
+                .map(|key| key.to_string())
+                .collect()
+        });
+
+        Self {
+            name,
+            display_name,
+            column_configs,
+            sort_config: None,
+            cell_values,
+            conditional_formats: conditional_formats.to_vec(),
+            row_order,
+            frozen: FrozenPanes::default(),
+            row_density: RowDensity::default(),
+        }
+    }
+    /// The key each row is stored by: the values of its key columns, or for sheets without key
+    /// columns, a key derived from the content of its physical columns.
+    pub fn row_keys(column_configs: &[ColumnConfiguration], rows: &[Row]) -> Vec<RowKey> {
+        let key_columns = ColumnConfiguration::key_column_indices(column_configs);
+        if !key_columns.is_empty() {
+            return rows.iter()
+                .filter_map(|row| RowKey::from_row(&key_columns, row))
+                .collect();
+        }
 
+        let physical_cols: Vec<usize> = column_configs.iter().enumerate()
+            .filter(|(_, c)| !c.is_virtual)
+            .map(|(i, _)| i)
+            .collect();
+        RowKey::from_content(rows.iter().map(|row| {
+            physical_cols.iter().map(|&i| row.cells.get(i).map(|c| c.0.as_str()).unwrap_or(""))
+        }))
+    }
 }
\ No newline at end of file
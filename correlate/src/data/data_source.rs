@@ -8,6 +8,8 @@ pub struct DataSource {
     pub config: DataSourceConfiguration,
     pub sheets: Vec<DataSheet>,
     pub selected_sheet_index: usize,
+    /// State of the source file when it was loaded.
+    pub fingerprint: Option<SourceFingerprint>,
 }
 
 impl DataSource {
@@ -31,6 +33,7 @@ impl DataSource {
         selected_sheet_index: usize,
     ) -> Self {
         Self {
+            fingerprint: SourceFingerprint::of(&path),
             path,
             name,
             config,
@@ -42,9 +45,11 @@ impl DataSource {
     pub fn save(
         &mut self,
         column_configs: Vec<ColumnConfiguration>,
+        conditional_formats: Vec<ConditionalFormat>,
         table: DataTable<Row>,
     ) -> Result<(), String> {
         self.sheets[self.selected_sheet_index].column_configs = column_configs;
+        self.sheets[self.selected_sheet_index].conditional_formats = conditional_formats;
         self.sheets[self.selected_sheet_index].table = table;
 
         let mut sheet_configs = Vec::new();
@@ -53,40 +58,17 @@ impl DataSource {
                 config.order = i;
             }
 
-            let key_col_idx = sheet.column_configs.iter().position(|c| c.is_key);
-            let virtual_cols: Vec<usize> = sheet.column_configs.iter().enumerate()
-                .filter(|(_, c)| c.is_virtual)
-                .map(|(i, _)| i)
-                .collect();
-
-            let mut cell_values = Vec::new();
-            if let Some(key_idx) = key_col_idx {
-                let rows: &Vec<Row> = &sheet.table;
-                for row in rows {
-                    let key = row.cells[key_idx].0.clone();
-                    if key.is_empty() {
-                        continue;
-                    }
-
-                    for &v_idx in &virtual_cols {
-                        let value = row.cells[v_idx].0.clone();
-                        if !value.is_empty() {
-                            cell_values.push(CellValueConfiguration {
-                                key: key.clone(),
-                                column_name: sheet.column_configs[v_idx].name.clone(),
-                                value,
-                            });
-                        }
-                    }
-                }
-            }
-
             sheet_configs.push(DataSheetConfiguration {
-                name: sheet.name.clone(),
-                display_name: sheet.display_name.clone(),
-                column_configs: sheet.column_configs.clone(),
-                sort_config: None,
-                cell_values,
+                frozen: sheet.frozen.clone(),
+                row_density: sheet.row_density,
+                ..DataSheetConfiguration::from_sheet(
+                    sheet.name.clone(),
+                    sheet.display_name.clone(),
+                    &sheet.column_configs,
+                    &sheet.conditional_formats,
+                    &sheet.table,
+                    sheet.manual_row_order,
+                )
             });
         }
 
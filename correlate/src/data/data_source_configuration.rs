@@ -3,17 +3,42 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 use crate::data::*;
 
+/// Version of the `.correlate` format that is written. Files without a version are version 1.
+pub const DATA_SOURCE_CONFIGURATION_VERSION: u32 = 2;
+
+/// Upgrades the content of a `.correlate` file by one version.
+type Migration = fn(&mut serde_json::Value) -> Result<(), String>;
+
+/// The migration from the version at its index plus one to the next version.
+const MIGRATIONS: [Migration; DATA_SOURCE_CONFIGURATION_VERSION as usize - 1] = [
+    migrate_v1_to_v2,
+];
+
+/// Version 2 adds the version itself; the content is unchanged.
+fn migrate_v1_to_v2(_: &mut serde_json::Value) -> Result<(), String> {
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DataSourceConfiguration {
     #[serde(skip)]
     pub path: std::path::PathBuf,
     #[serde(default)]
+    pub version: u32,
+    /// Why the `.correlate` file could not be read, when the configuration was started over.
+    #[serde(skip)]
+    pub load_error: Option<String>,
+    /// Set when the `.correlate` file was written by a newer version of Correlate. The file is
+    /// left as it is, so it is never saved over.
+    #[serde(skip)]
+    pub read_only: bool,
+    #[serde(default)]
     pub name: Option<String>,
     pub sheets: Vec<DataSheetConfiguration>,
 }
 
 impl DataSourceConfiguration {
-    fn calculate_path<P: AsRef<Path>>(source_path: P) -> std::path::PathBuf {
+    pub fn calculate_path<P: AsRef<Path>>(source_path: P) -> std::path::PathBuf {
         let mut p = source_path.as_ref().to_path_buf();
         let ext = p.extension().and_then(|e| e.to_str()).unwrap_or("");
         let new_ext = if ext.is_empty() {
@@ -32,36 +57,141 @@ impl DataSourceConfiguration {
     ) -> Self {
         Self {
             path: Self::calculate_path(source_path),
+            version: DATA_SOURCE_CONFIGURATION_VERSION,
+            load_error: None,
+            read_only: false,
             name,
             sheets,
         }
     }
 
+    /// Loads the `.correlate` file of a data source, migrated to the current version. A file
+    /// that can't be read is moved aside as a backup, so starting over doesn't overwrite it. A
+    /// file of a newer version stays in place, and the settings start over without being saved.
     pub fn load<P: AsRef<Path>>(source_path: P) -> Self {
         let path_buf = Self::calculate_path(&source_path);
-        match fs::read_to_string(&path_buf) {
-            Ok(content) => {
-                match serde_json::from_str::<Self>(&content) {
-                    Ok(mut config) => {
-                        config.path = path_buf;
-                        config
-                    }
-                    Err(e) => {
-                        log::error!("Failed to parse config at {:?}: {}", path_buf, e);
-                        Self::new(source_path, None, Vec::new())
-                    }
-                }
+        let Ok(content) = fs::read_to_string(&path_buf) else {
+            // File likely does not exist, return a new default config
+            return Self::new(source_path, None, Vec::new());
+        };
+
+        match Self::parse(&content) {
+            Ok(mut config) => {
+                config.path = path_buf;
+                config
+            }
+            Err(ParseError::Newer(e)) => {
+                log::error!("Failed to load config at {:?}: {}", path_buf, e);
+                let mut config = Self::new(source_path, None, Vec::new());
+                config.read_only = true;
+                config.load_error = Some(format!("{}. The file is left as it is and the settings are not saved.", e));
+                config
             }
-            Err(_) => {
-                // File likely does not exist, return a new default config
-                Self::new(source_path, None, Vec::new())
+            Err(ParseError::Invalid(e)) => {
+                log::error!("Failed to parse config at {:?}: {}", path_buf, e);
+                let mut config = Self::new(source_path, None, Vec::new());
+                config.load_error = Some(match Self::back_up(&path_buf) {
+                    Ok(backup) => format!("{}. The file was moved to {} and the settings start over.", e, backup.display()),
+                    Err(backup_error) => format!("{}. The file could not be backed up: {}", e, backup_error),
+                });
+                config
             }
         }
     }
 
+    /// Reads a `.correlate` file of any version up to the current one.
+    fn parse(content: &str) -> Result<Self, ParseError> {
+        let invalid = |e: String| ParseError::Invalid(e);
+        let mut value = serde_json::from_str::<serde_json::Value>(content).map_err(|e| invalid(e.to_string()))?;
+        let version = match value.get("version") {
+            None => 1,
+            Some(version) => version.as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|&v| v >= 1)
+                .ok_or_else(|| invalid(format!("Invalid version {}", version)))?,
+        };
+        if version > DATA_SOURCE_CONFIGURATION_VERSION {
+            return Err(ParseError::Newer(format!("Version {} was written by a newer version of Correlate", version)));
+        }
+
+        for migration in &MIGRATIONS[version as usize - 1..] {
+            migration(&mut value).map_err(invalid)?;
+        }
+        if let Some(object) = value.as_object_mut() {
+            object.insert("version".to_string(), DATA_SOURCE_CONFIGURATION_VERSION.into());
+        }
+        serde_json::from_value(value).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Moves a file aside as `<name>.bak`, numbered when earlier backups exist.
+    fn back_up(path: &Path) -> Result<std::path::PathBuf, String> {
+        let backup = (1..)
+            .map(|n| {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(if n == 1 { ".bak".to_string() } else { format!(".{}.bak", n) });
+                std::path::PathBuf::from(backup)
+            })
+            .find(|backup| !backup.exists())
+            .unwrap_or_default();
+        fs::rename(path, &backup).map_err(|e| e.to_string())?;
+        Ok(backup)
+    }
+
+    /// Writes the `.correlate` file, unless it belongs to a newer version of Correlate.
     pub fn save(&self) -> Result<(), String> {
+        if self.read_only {
+            return Ok(());
+        }
         let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
         fs::write(&self.path, content).map_err(|e| e.to_string())?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Why a `.correlate` file could not be read.
+#[derive(Debug)]
+enum ParseError {
+    /// The file was written by a newer version of Correlate.
+    Newer(String),
+    Invalid(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configurations_are_migrated() {
+        let legacy = DataSourceConfiguration::parse(r#"{ "name": "People", "sheets": [] }"#).unwrap();
+        assert_eq!(legacy.version, DATA_SOURCE_CONFIGURATION_VERSION);
+        assert_eq!(legacy.name.as_deref(), Some("People"));
+
+        assert!(matches!(DataSourceConfiguration::parse(r#"{ "version": 99, "sheets": [] }"#), Err(ParseError::Newer(_))));
+        assert!(matches!(DataSourceConfiguration::parse(r#"{ "version": 0, "sheets": [] }"#), Err(ParseError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_unreadable_configurations_are_backed_up() {
+        let dir = std::env::temp_dir().join(format!("correlate-sidecar-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("people.csv");
+        let path = DataSourceConfiguration::calculate_path(&source);
+        fs::write(&path, r#"{ "sheets": "broken" }"#).unwrap();
+
+        let config = DataSourceConfiguration::load(&source);
+        assert!(config.load_error.is_some());
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(dir.join("people.csv.correlate.bak")).unwrap(), r#"{ "sheets": "broken" }"#);
+
+        // A file of a newer version stays in place and isn't saved over.
+        let newer = r#"{ "version": 99, "sheets": [] }"#;
+        fs::write(&path, newer).unwrap();
+        let config = DataSourceConfiguration::load(&source);
+        assert!(config.read_only && config.load_error.is_some());
+        config.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), newer);
+        assert!(!dir.join("people.csv.correlate.2.bak").exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -10,6 +10,20 @@ impl Default for CsvSheet {
     }
 }
 
+impl CsvSheet {
+    /// Writes the visible columns of a sheet to a CSV file, with their names as the header.
+    pub fn export<P: AsRef<std::path::Path>>(path: P, column_configs: &[ColumnConfiguration], rows: &[Row]) -> Result<(), String> {
+        let columns: Vec<usize> = (0..column_configs.len()).filter(|&c| column_configs[c].is_visible).collect();
+        let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+        writer.write_record(columns.iter().map(|&c| &column_configs[c].name)).map_err(|e| e.to_string())?;
+        for row in rows {
+            let values = columns.iter().map(|&c| row.cells.get(c).map(|cell| cell.0.as_str()).unwrap_or_default());
+            writer.write_record(values).map_err(|e| e.to_string())?;
+        }
+        writer.flush().map_err(|e| e.to_string())
+    }
+}
+
 impl SheetLoader for CsvSheet {
     fn load(&self, path: String) -> Result<(Vec<DataSheet>, DataSourceConfiguration), String> {
         let file_name = std::path::Path::new(&path)
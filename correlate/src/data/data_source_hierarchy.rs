@@ -19,11 +19,18 @@ impl DataSource {
 
         let ds_display_name = self.name.as_ref().unwrap_or(&default_file_name).clone();
 
-        let icon = self
-            .sheets
-            .first()
-            .map(|s| s.icon)
-            .unwrap_or(egui_material_icons::icons::ICON_TABLE_CHART);
+        let icon = if self.config.load_error.is_some() {
+            egui_material_icons::icons::ICON_WARNING
+        } else {
+            self.sheets
+                .first()
+                .map(|s| s.icon)
+                .unwrap_or(egui_material_icons::icons::ICON_TABLE_CHART)
+        };
+        let hover_text = match &self.config.load_error {
+            Some(error) => format!("{}\nThe settings could not be loaded: {}", self.path, error),
+            None => self.path.clone(),
+        };
 
         let renaming_target_id = egui::Id::new("renaming_target");
 
@@ -105,17 +112,18 @@ impl DataSource {
                     );
                 });
             } else {
-                header_res.header_response.context_menu(|ui| {
+                let header_response = header_res.header_response.on_hover_text(&hover_text);
+                header_response.context_menu(|ui| {
                     Rename::ui_item_context_menu(ui, Rename::DataSource(ds_idx));
                 });
 
-                if header_res.header_response.clicked() {
+                if header_response.clicked() {
                     if view_model.selected_index != Some(ds_idx) {
                         *newly_selected_index = Some(ds_idx);
                         *newly_selected_sheet_index = Some(self.selected_sheet_index);
                     }
                 }
-                if header_res.header_response.double_clicked() {
+                if header_response.double_clicked() {
                     ui.data_mut(|d| d.insert_temp(renaming_target_id, Rename::DataSource(ds_idx)));
                 }
             }
@@ -140,7 +148,7 @@ impl DataSource {
                     selected,
                     icon,
                     &ds_display_name,
-                    Some(&self.path),
+                    Some(&hover_text),
                     || {
                         if view_model.selected_index != Some(ds_idx) {
                             *newly_selected_index = Some(ds_idx);
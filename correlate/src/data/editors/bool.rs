@@ -1,6 +1,5 @@
 ﻿use egui::{Response, Ui};
 use crate::data::*;
-use crate::view::*;
 
 pub struct BoolEditor;
 impl ColumnTypeEditor for BoolEditor {
@@ -9,7 +8,7 @@ impl ColumnTypeEditor for BoolEditor {
         ui: &mut Ui,
         cell_value: &mut CellValue,
         _column_config: &mut ColumnConfiguration,
-        _view_model: &mut RootViewModel
+        _context: &mut EditorContext
     ) -> Option<Response> {
         let mut b: bool = cell_value.0.parse().unwrap_or(false);
         let res = ui.checkbox(&mut b, "");
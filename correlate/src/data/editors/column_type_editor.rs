@@ -1,6 +1,13 @@
 ﻿use egui::{Response, Ui};
 use crate::data::*;
-use crate::view::*;
+
+/// What an editor can use besides the cell it edits.
+pub struct EditorContext<'a> {
+    /// All loaded data sources, to look up the rows of a related sheet.
+    pub data_sources: &'a [DataSource],
+    /// Set by an editor to have the configuration of the data source saved.
+    pub save_requested: &'a mut bool,
+}
 
 pub trait ColumnTypeEditor {
     fn show(
@@ -8,7 +15,7 @@ pub trait ColumnTypeEditor {
         ui: &mut Ui,
         cell_value: &mut CellValue,
         column_config: &mut ColumnConfiguration,
-        view_model: &mut RootViewModel
+        context: &mut EditorContext
     ) -> Option<Response>;
 }
 
@@ -1,6 +1,5 @@
 ﻿use egui::{Response, Ui};
 use crate::data::*;
-use crate::view::*;
 
 pub struct MultiSelectEditor;
 impl ColumnTypeEditor for MultiSelectEditor {
@@ -9,7 +8,7 @@ impl ColumnTypeEditor for MultiSelectEditor {
         ui: &mut Ui,
         cell_value: &mut CellValue,
         _column_config: &mut ColumnConfiguration,
-        _view_model: &mut RootViewModel
+        _context: &mut EditorContext
     ) -> Option<Response> {
         Some(ui.text_edit_singleline(&mut cell_value.0))
     }
@@ -1,6 +1,5 @@
 ﻿use egui::{Response, Ui};
 use crate::data::*;
-use crate::view::*;
 
 pub struct NumberEditor;
 impl ColumnTypeEditor for NumberEditor {
@@ -9,7 +8,7 @@ impl ColumnTypeEditor for NumberEditor {
         ui: &mut Ui,
         cell_value: &mut CellValue,
         _column_config: &mut ColumnConfiguration,
-        _view_model: &mut RootViewModel
+        _context: &mut EditorContext
     ) -> Option<Response> {
         let mut n: f64 = cell_value.0.parse().unwrap_or(0.0);
         let res = ui.add(egui::DragValue::new(&mut n).speed(0.1));
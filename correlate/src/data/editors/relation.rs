@@ -1,6 +1,5 @@
 ﻿use egui::{Popup, Response, Ui};
 use crate::data::*;
-use crate::view::*;
 
 pub struct RelationEditor;
 
@@ -10,7 +9,7 @@ impl ColumnTypeEditor for RelationEditor {
         ui: &mut Ui,
         cell_value: &mut CellValue,
         column_config: &mut ColumnConfiguration,
-        view_model: &mut RootViewModel
+        context: &mut EditorContext
     ) -> Option<Response> {
         // Determine the related sheet from ColumnConfig.related_source (format: "Source > Sheet")
         let related = column_config.related_source.clone().unwrap_or_default();
@@ -22,7 +21,7 @@ impl ColumnTypeEditor for RelationEditor {
         let mut rel_sheet: Option<&DataSheet> = None;
         let mut resolved_src_name = String::new();
         if !src_part.is_empty() && !sheet_part.is_empty() {
-            for ds in &view_model.data_sources {
+            for ds in context.data_sources {
                 let display_source = ds.name.as_ref().unwrap_or(&ds.path);
                 if display_source == src_part {
                     for sheet in &ds.sheets {
@@ -45,7 +44,11 @@ impl ColumnTypeEditor for RelationEditor {
         let rel_sheet = rel_sheet.unwrap();
 
         // Find key and name columns in the related sheet
-        let key_col_idx = rel_sheet.column_configs.iter().position(|c| c.is_key).unwrap_or(0);
+        let mut key_columns = ColumnConfiguration::key_column_indices(&rel_sheet.column_configs);
+        if key_columns.is_empty() {
+            key_columns.push(0);
+        }
+        let row_key = |row: &Row| RowKey::from_row(&key_columns, row).map(|k| k.to_string()).unwrap_or_default();
         let name_col_idx = ColumnConfiguration::find_name_column_index(&rel_sheet.column_configs);
 
         // Prepare the current display text
@@ -56,24 +59,24 @@ impl ColumnTypeEditor for RelationEditor {
 
         let mut current_display = String::new();
         if !current_key.is_empty() {
-            if let Some(row) = rel_sheet.table.iter().find(|r| r.cells.get(key_col_idx).map(|c| &c.0) == Some(&current_key)) {
+            if let Some(row) = rel_sheet.table.iter().find(|r| row_key(r) == current_key) {
                 if let Some(name_cell) = row.cells.get(name_col_idx) {
                     current_display = name_cell.0.clone();
                 }
             }
         }
         let placeholder = if current_display.is_empty() {
-            if current_key.is_empty() { "Select...".to_string() } else { current_key.clone() }
+            if current_key.is_empty() { "Select...".to_string() } else { RowKey::parse(&current_key).display() }
         } else {
             current_display
         };
 
-        // Prepare options for the popup (to avoid borrowing view_model inside the closure)
+        // Prepare options for the popup (to avoid borrowing the context inside the closure)
         let query_initial = current_key.to_lowercase();
         let mut options = Vec::new();
         for row in rel_sheet.table.iter() {
-            let key = row.cells.get(key_col_idx).map(|c| c.0.clone()).unwrap_or_default();
-            if key.is_empty() { continue; }
+            let key = row_key(row);
+            if RowKey::parse(&key).is_empty() { continue; }
             let display = row.cells.get(name_col_idx).map(|c| c.0.clone()).unwrap_or_else(|| key.clone());
             if !query_initial.is_empty() && !display.to_lowercase().contains(&query_initial) {
                 // We'll filter again inside the closure because query_buffer can change,
@@ -116,7 +119,7 @@ impl ColumnTypeEditor for RelationEditor {
                 ui.ctx().request_repaint();
             }
             if text_edit_res.lost_focus() || ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                view_model.save_datasource_configuration();
+                *context.save_requested = true;
                 Popup::close_id(ui.ctx(), popup_id);
             }
 
@@ -133,7 +136,7 @@ impl ColumnTypeEditor for RelationEditor {
                     cell_value.0 = relation.to_string();
                     response.mark_changed();
                     ui.ctx().request_repaint();
-                    view_model.save_datasource_configuration();
+                    *context.save_requested = true;
                     Popup::close_id(ui.ctx(), popup_id);
                 }
             }
@@ -144,7 +147,7 @@ impl ColumnTypeEditor for RelationEditor {
             if !cell_value.0.is_empty() {
                     response.mark_changed();
                     ui.ctx().request_repaint(); // Ensure it updates and eventually saves
-                view_model.save_datasource_configuration();
+                *context.save_requested = true;
                 Popup::close_id(ui.ctx(), popup_id);
             }
         }
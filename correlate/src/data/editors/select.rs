@@ -1,6 +1,5 @@
 ﻿use egui::{Color32, Popup, Response, Ui};
 use crate::data::*;
-use crate::view::*;
 
 pub struct SelectEditor;
 impl ColumnTypeEditor for SelectEditor {
@@ -9,7 +8,7 @@ impl ColumnTypeEditor for SelectEditor {
         ui: &mut Ui,
         cell_value: &mut CellValue,
         column_config: &mut ColumnConfiguration,
-        _view_model: &mut RootViewModel
+        _context: &mut EditorContext
     ) -> Option<Response> {
         let text = if cell_value.0.is_empty() { "Select...".to_string() } else { cell_value.0.clone() };
         let mut color = Color32::TRANSPARENT;
@@ -1,18 +1,25 @@
-﻿use egui::{Response, Ui};
+﻿use egui::{Key, KeyboardShortcut, Modifiers, Response, Ui};
 use crate::data::*;
-use crate::view::*;
 
 pub struct TextEditor;
+
+impl TextEditor {
+    /// Starts a new line, as Enter commits the edit.
+    pub const NEW_LINE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::SHIFT, Key::Enter);
+}
+
 impl ColumnTypeEditor for TextEditor {
     fn show(
         &self,
         ui: &mut Ui,
         cell_value: &mut CellValue,
         _column_config: &mut ColumnConfiguration,
-        _view_model: &mut RootViewModel
+        _context: &mut EditorContext
     ) -> Option<Response> {
+        let rows = cell_value.0.lines().count().max(1);
         Some(egui::TextEdit::multiline(&mut cell_value.0)
-            .desired_rows(1)
+            .desired_rows(rows)
+            .return_key(Self::NEW_LINE)
             .code_editor()
             .show(ui)
             .response)
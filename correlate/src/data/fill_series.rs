@@ -0,0 +1,137 @@
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use crate::data::{DATE_FORMATS, DATE_TIME_FORMATS};
+
+/// Continues the series started by the `seed` values with `count` more values, the way the fill
+/// handle of a spreadsheet does:
+/// - numbers advance by the step between the seed values; a single number is repeated,
+/// - dates advance by the step between the seed values, or by a day,
+/// - values of `options`, e.g. those of a select column, cycle through the options,
+/// - text ending in a number counts on from that number,
+/// - anything else repeats the seed.
+pub fn continue_series(seed: &[&str], options: &[String], count: usize) -> Vec<String> {
+    if seed.is_empty() {
+        return Vec::new();
+    }
+
+    numbers(seed, count)
+        .or_else(|| dates(seed, count))
+        .or_else(|| cycle_options(seed, options, count))
+        .or_else(|| numbered_text(seed, count))
+        .unwrap_or_else(|| (0..count).map(|i| seed[i % seed.len()].to_string()).collect())
+}
+
+/// The step between evenly spaced values, from the first to the last one.
+fn step(first: f64, last: f64, len: usize) -> f64 {
+    if len < 2 {
+        return 0.0;
+    }
+    (last - first) / (len - 1) as f64
+}
+
+fn numbers(seed: &[&str], count: usize) -> Option<Vec<String>> {
+    let values = seed.iter()
+        .map(|value| value.trim().parse::<f64>().ok().filter(|v| v.is_finite()))
+        .collect::<Option<Vec<_>>>()?;
+    let decimals = seed.iter()
+        .map(|value| value.trim().split_once('.').map_or(0, |(_, fraction)| fraction.len()))
+        .max()
+        .unwrap_or(0);
+
+    let last = values[values.len() - 1];
+    let step = step(values[0], last, values.len());
+    Some((1..=count).map(|i| format!("{:.*}", decimals, last + step * i as f64)).collect())
+}
+
+fn dates(seed: &[&str], count: usize) -> Option<Vec<String>> {
+    let value = seed[0].trim();
+    if let Some(format) = DATE_FORMATS.iter().find(|format| NaiveDate::parse_from_str(value, format).is_ok()) {
+        let values = seed.iter()
+            .map(|value| NaiveDate::parse_from_str(value.trim(), format).ok())
+            .collect::<Option<Vec<_>>>()?;
+        let last = values[values.len() - 1];
+        let days = match values.len() {
+            1 => 1,
+            len => (last - values[0]).num_days() / (len - 1) as i64,
+        };
+        return (1..=count as i64)
+            .map(|i| last.checked_add_signed(TimeDelta::try_days(days * i)?).map(|date| date.format(format).to_string()))
+            .collect();
+    }
+
+    let format = DATE_TIME_FORMATS.iter().find(|format| NaiveDateTime::parse_from_str(value, format).is_ok())?;
+    let values = seed.iter()
+        .map(|value| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let last = values[values.len() - 1];
+    let seconds = match values.len() {
+        1 => 24 * 60 * 60,
+        len => (last - values[0]).num_seconds() / (len - 1) as i64,
+    };
+    (1..=count as i64)
+        .map(|i| last.checked_add_signed(TimeDelta::try_seconds(seconds * i)?).map(|date| date.format(format).to_string()))
+        .collect()
+}
+
+fn cycle_options(seed: &[&str], options: &[String], count: usize) -> Option<Vec<String>> {
+    let indices = seed.iter()
+        .map(|value| options.iter().position(|option| option == value))
+        .collect::<Option<Vec<_>>>()?;
+    let last = indices[indices.len() - 1] as i64;
+    let step = match indices.len() {
+        1 => 1,
+        len => (last - indices[0] as i64) / (len - 1) as i64,
+    };
+    Some((1..=count as i64)
+        .map(|i| options[(last + step * i).rem_euclid(options.len() as i64) as usize].clone())
+        .collect())
+}
+
+/// Text that ends in a number, e.g. `Item 007`, counts on from that number with the same
+/// prefix and padding.
+fn numbered_text(seed: &[&str], count: usize) -> Option<Vec<String>> {
+    fn split(value: &str) -> Option<(&str, usize, i64)> {
+        let prefix = value.trim_end_matches(|c: char| c.is_ascii_digit());
+        let digits = &value[prefix.len()..];
+        if prefix.is_empty() || digits.is_empty() {
+            return None;
+        }
+        Some((prefix, digits.len(), digits.parse().ok()?))
+    }
+
+    let parts = seed.iter().map(|value| split(value)).collect::<Option<Vec<_>>>()?;
+    let (prefix, width, last) = parts[parts.len() - 1];
+    if parts.iter().any(|(p, ..)| *p != prefix) {
+        return None;
+    }
+    let step = match parts.len() {
+        1 => 1,
+        len => (last - parts[0].2) / (len - 1) as i64,
+    };
+    Some((1..=count as i64)
+        .map(|i| format!("{}{:0width$}", prefix, (last + step * i).max(0), width = width))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_and_dates() {
+        assert_eq!(continue_series(&["1", "3"], &[], 3), vec!["5", "7", "9"]);
+        assert_eq!(continue_series(&["0.5", "0.75"], &[], 2), vec!["1.00", "1.25"]);
+        assert_eq!(continue_series(&["4"], &[], 2), vec!["4", "4"]);
+        assert_eq!(continue_series(&["2024-01-30"], &[], 2), vec!["2024-01-31", "2024-02-01"]);
+        assert_eq!(continue_series(&["01/03/2024", "08/03/2024"], &[], 1), vec!["15/03/2024"]);
+        assert_eq!(continue_series(&["2024-01-01 10:00", "2024-01-01 10:30"], &[], 1), vec!["2024-01-01 11:00"]);
+    }
+
+    #[test]
+    fn test_options_and_text() {
+        let options = ["Low", "Medium", "High"].map(String::from);
+        assert_eq!(continue_series(&["Medium"], &options, 3), vec!["High", "Low", "Medium"]);
+        assert_eq!(continue_series(&["Item 1"], &[], 2), vec!["Item 2", "Item 3"]);
+        assert_eq!(continue_series(&["A008", "A010"], &[], 1), vec!["A012"]);
+        assert_eq!(continue_series(&["red", "green"], &[], 3), vec!["red", "green", "red"]);
+    }
+}
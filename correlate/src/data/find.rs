@@ -0,0 +1,148 @@
+use regex::{NoExpand, Regex, RegexBuilder};
+use crate::data::*;
+
+/// Where to search for matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FindScope {
+    /// The columns that were selected when the search was started.
+    SelectedColumns,
+    #[default]
+    Sheet,
+    Project,
+}
+
+impl FindScope {
+    pub const ALL: [FindScope; 3] = [FindScope::SelectedColumns, FindScope::Sheet, FindScope::Project];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FindScope::SelectedColumns => "Selected columns",
+            FindScope::Sheet => "Sheet",
+            FindScope::Project => "Project",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FindOptions {
+    pub query: String,
+    pub replacement: String,
+    pub match_case: bool,
+    pub whole_word: bool,
+    /// Interpret the query as a regular expression. The replacement can then refer to capture
+    /// groups, e.g. `$1`.
+    pub regex: bool,
+    pub scope: FindScope,
+}
+
+impl FindOptions {
+    /// Compiles the query into a matcher, or returns `None` when there is nothing to search for.
+    pub fn matcher(&self) -> Result<Option<Matcher>, String> {
+        if self.query.is_empty() {
+            return Ok(None);
+        }
+
+        let mut pattern = if self.regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!self.match_case)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Some(Matcher { regex, expand: self.regex }))
+    }
+}
+
+pub struct Matcher {
+    regex: Regex,
+    /// Whether capture groups in the replacement are expanded.
+    expand: bool,
+}
+
+impl Matcher {
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+
+    pub fn replace(&self, value: &str, replacement: &str) -> String {
+        if self.expand {
+            self.regex.replace_all(value, replacement).into_owned()
+        } else {
+            self.regex.replace_all(value, NoExpand(replacement)).into_owned()
+        }
+    }
+
+    /// Finds the matching cells of the given columns, row by row.
+    pub fn find_in_rows<'a>(&self, rows: impl IntoIterator<Item = &'a Row>, columns: &[usize]) -> Vec<(usize, usize)> {
+        rows.into_iter().enumerate()
+            .flat_map(|(row_idx, row)| {
+                columns.iter()
+                    .filter(|&&column| row.cells.get(column).is_some_and(|cell| self.is_match(&cell.0)))
+                    .map(move |&column| (row_idx, column))
+            })
+            .collect()
+    }
+}
+
+/// A matching cell of a sheet in the project. Matches are ordered by sheet, then row by row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FindMatch {
+    /// Index of the data source.
+    pub source: usize,
+    pub sheet: usize,
+    pub row: usize,
+    pub column: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(query: &str) -> FindOptions {
+        FindOptions { query: query.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_plain_text_options() {
+        let matcher = options("a.b").matcher().unwrap().unwrap();
+        assert!(matcher.is_match("xA.By"));
+        assert!(!matcher.is_match("axb"));
+
+        let matcher = FindOptions { match_case: true, ..options("a.b") }.matcher().unwrap().unwrap();
+        assert!(!matcher.is_match("A.B"));
+
+        let matcher = FindOptions { whole_word: true, ..options("cat") }.matcher().unwrap().unwrap();
+        assert!(matcher.is_match("the cat sat"));
+        assert!(!matcher.is_match("concatenate"));
+
+        assert!(options("").matcher().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_replace() {
+        let matcher = options("$1").matcher().unwrap().unwrap();
+        assert_eq!(matcher.replace("costs $1", "$2"), "costs $2");
+
+        let matcher = FindOptions { regex: true, ..options(r"(\d+)-(\d+)") }.matcher().unwrap().unwrap();
+        assert_eq!(matcher.replace("1-2 and 3-4", "$2-$1"), "2-1 and 4-3");
+
+        assert!(FindOptions { regex: true, ..options("(") }.matcher().is_err());
+    }
+
+    #[test]
+    fn test_find_in_rows() {
+        let rows = [
+            Row { cells: vec![CellValue::from("apple"), CellValue::from("pear")] },
+            Row { cells: vec![CellValue::from("plum"), CellValue::from("apple pie")] },
+        ];
+        let matcher = options("apple").matcher().unwrap().unwrap();
+        assert_eq!(matcher.find_in_rows(&rows, &[0, 1]), vec![(0, 0), (1, 1)]);
+        assert_eq!(matcher.find_in_rows(&rows, &[1]), vec![(1, 1)]);
+    }
+}
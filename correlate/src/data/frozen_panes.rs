@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use crate::data::*;
+
+/// Columns and rows of a sheet that stay in place when the table is scrolled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FrozenPanes {
+    /// Number of visible columns, counted from the left, that are frozen.
+    #[serde(default)]
+    pub columns: usize,
+    /// Keys of the rows that are pinned to the top.
+    #[serde(default)]
+    pub rows: Vec<String>,
+}
+
+impl FrozenPanes {
+    pub fn is_empty(&self) -> bool {
+        self.columns == 0 && self.rows.is_empty()
+    }
+
+    /// Whether a row is pinned. Rows are pinned by key, so only sheets with key columns can
+    /// pin rows.
+    pub fn is_pinned(&self, key_columns: &[usize], row: &Row) -> bool {
+        !self.rows.is_empty()
+            && RowKey::from_row(key_columns, row).is_some_and(|key| self.rows.contains(&key.to_string()))
+    }
+
+    pub fn set_pinned(&mut self, key: String, pinned: bool) {
+        self.rows.retain(|k| *k != key);
+        if pinned {
+            self.rows.push(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_rows() {
+        let row = |key: &str| Row { cells: vec![CellValue::from(key), CellValue::from("value")] };
+        let mut panes = FrozenPanes::default();
+        assert!(panes.is_empty());
+
+        panes.set_pinned(RowKey(vec!["b".to_string()]).to_string(), true);
+        assert!(panes.is_pinned(&[0], &row("b")));
+        assert!(!panes.is_pinned(&[0], &row("a")));
+        assert!(!panes.is_pinned(&[], &row("b")));
+
+        panes.set_pinned(RowKey(vec!["b".to_string()]).to_string(), false);
+        assert!(panes.is_empty());
+    }
+}
@@ -0,0 +1,479 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use crate::application_command::ApplicationCommand;
+use crate::data::*;
+
+/// Maximum number of steps kept in the history; the oldest steps are dropped first.
+const MAX_HISTORY_STEPS: usize = 200;
+
+/// Approximate number of bytes the states of the history may take, as every step keeps whole
+/// sheets. The oldest steps are dropped first, but the latest step is always kept.
+const MAX_HISTORY_SIZE: usize = 256 * 1024 * 1024;
+
+/// Identifies a sheet independently of the position of its data source in the workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SheetId {
+    pub source: String,
+    pub sheet: usize,
+}
+
+/// Content of a single sheet at one point in the history.
+#[derive(Debug, Clone)]
+pub struct SheetState {
+    pub rows: Vec<Row>,
+    pub column_configs: Vec<ColumnConfiguration>,
+    pub formats: Vec<ConditionalFormat>,
+}
+
+impl SheetState {
+    fn size(&self) -> usize {
+        rows_size(&self.rows)
+    }
+
+    fn differs(&self, other: &SheetState) -> bool {
+        self.columns_differ(&other.column_configs, &other.formats) || self.rows != other.rows
+    }
+
+    /// Whether the columns or formats differ. Column widths follow the layout and are ignored.
+    fn columns_differ(&self, column_configs: &[ColumnConfiguration], formats: &[ConditionalFormat]) -> bool {
+        self.formats != formats
+            || self.column_configs.len() != column_configs.len()
+            || self.column_configs.iter().zip(column_configs).any(|(a, b)| !same_settings(a, b))
+    }
+
+    /// Describes the change from this state to `after`, e.g. "Edit Price in row 3".
+    pub fn describe_change(&self, after: &SheetState) -> String {
+        let (before_columns, after_columns) = (&self.column_configs, &after.column_configs);
+        let added = after_columns.iter().find(|a| !before_columns.iter().any(|b| b.name == a.name));
+        let removed = before_columns.iter().find(|b| !after_columns.iter().any(|a| a.name == b.name));
+
+        if after_columns.len() > before_columns.len() {
+            return format!("Add column {}", added.map(display_name).unwrap_or_default());
+        }
+        if after_columns.len() < before_columns.len() {
+            return format!("Remove column {}", removed.map(display_name).unwrap_or_default());
+        }
+        if let (Some(before), Some(after)) = (removed, added) {
+            return format!("Rename column {} to {}", display_name(before), display_name(after));
+        }
+
+        for (before, after) in before_columns.iter().zip(after_columns) {
+            if before.name != after.name {
+                return format!("Move column {}", display_name(before));
+            }
+            if before.display_name != after.display_name {
+                return format!("Rename column {} to {}", display_name(before), display_name(after));
+            }
+            if before.column_type != after.column_type {
+                return format!("Change type of {} to {:?}", display_name(after), after.column_type);
+            }
+            if before.is_visible != after.is_visible {
+                let verb = if after.is_visible { "Show" } else { "Hide" };
+                return format!("{} column {}", verb, display_name(after));
+            }
+        }
+        if ColumnConfiguration::key_column_indices(before_columns) != ColumnConfiguration::key_column_indices(after_columns) {
+            return "Change key columns".to_string();
+        }
+        if let Some((_, after)) = before_columns.iter().zip(after_columns).find(|(b, a)| b.validation != a.validation) {
+            return format!("Change validation of {}", display_name(after));
+        }
+        if let Some((_, after)) = before_columns.iter().zip(after_columns).find(|(b, a)| !same_settings(b, a)) {
+            return format!("Change settings of column {}", display_name(after));
+        }
+        if self.formats != after.formats {
+            return "Change conditional formatting".to_string();
+        }
+
+        let (before_rows, after_rows) = (self.rows.len(), after.rows.len());
+        if after_rows != before_rows {
+            let count = after_rows.abs_diff(before_rows);
+            let verb = if after_rows > before_rows { "Insert" } else { "Remove" };
+            return format!("{} {} {}", verb, count, if count == 1 { "row" } else { "rows" });
+        }
+
+        if self.rows != after.rows && is_permutation(&self.rows, &after.rows) {
+            return "Move rows".to_string();
+        }
+
+        let changed: Vec<(usize, usize)> = self.rows.iter().zip(&after.rows).enumerate()
+            .flat_map(|(row, (b, a))| {
+                let len = b.cells.len().max(a.cells.len());
+                (0..len).filter(move |&c| b.cells.get(c) != a.cells.get(c)).map(move |c| (row, c))
+            })
+            .collect();
+        match changed.as_slice() {
+            [(row, column)] => {
+                let column = after_columns.get(*column).map(display_name).unwrap_or_default();
+                format!("Edit {} in row {}", column, row + 1)
+            }
+            cells => format!("Edit {} cells", cells.len()),
+        }
+    }
+}
+
+/// Approximate number of bytes taken by the rows.
+fn rows_size(rows: &[Row]) -> usize {
+    rows.iter()
+        .map(|row| size_of::<Row>() + row.cells.iter().map(|cell| size_of::<CellValue>() + cell.0.len()).sum::<usize>())
+        .sum()
+}
+
+fn display_name(config: &ColumnConfiguration) -> String {
+    config.display_name.clone().unwrap_or_else(|| config.name.clone())
+}
+
+/// Whether the rows hold the same values in a different order.
+fn is_permutation(a: &[Row], b: &[Row]) -> bool {
+    fn sorted(rows: &[Row]) -> Vec<Vec<&str>> {
+        let mut values: Vec<Vec<&str>> = rows.iter()
+            .map(|row| row.cells.iter().map(|cell| cell.0.as_str()).collect())
+            .collect();
+        values.sort_unstable();
+        values
+    }
+    sorted(a) == sorted(b)
+}
+
+fn same_settings(a: &ColumnConfiguration, b: &ColumnConfiguration) -> bool {
+    ColumnConfiguration { width: None, ..a.clone() } == ColumnConfiguration { width: None, ..b.clone() }
+}
+
+/// Projects and data sources, recorded around renames and trash operations.
+#[derive(Clone)]
+pub struct WorkspaceState {
+    pub projects: Option<Vec<ProjectConfiguration>>,
+    pub data_sources: Vec<DataSource>,
+    pub selected_index: Option<usize>,
+    /// Entries the step moved into or out of the trash that are in the trash in this state.
+    pub trashed: Vec<TrashEntry>,
+    /// Entries the step moved into or out of the trash that aren't in the trash in this state.
+    pub untrashed: Vec<TrashEntry>,
+}
+
+#[derive(Clone)]
+pub enum HistoryState {
+    Sheet(SheetId, Arc<SheetState>),
+    /// Saved configuration of a sheet, for steps restored from a journal.
+    Saved(SheetId, Arc<DataSheetConfiguration>),
+    Workspace(Arc<WorkspaceState>),
+    /// Command to handle, for steps of commands that can be undone.
+    Command(Arc<dyn ApplicationCommand>),
+}
+
+impl HistoryState {
+    fn sheet(&self) -> Option<&SheetId> {
+        match self {
+            HistoryState::Sheet(sheet, _) | HistoryState::Saved(sheet, _) => Some(sheet),
+            HistoryState::Workspace(_) | HistoryState::Command(_) => None,
+        }
+    }
+
+    fn saved(&self, persist: &impl Fn(&SheetId, &SheetState) -> DataSheetConfiguration) -> Option<DataSheetConfiguration> {
+        match self {
+            HistoryState::Sheet(sheet, state) => Some(persist(sheet, state)),
+            HistoryState::Saved(_, config) => Some(config.as_ref().clone()),
+            HistoryState::Workspace(_) | HistoryState::Command(_) => None,
+        }
+    }
+
+    /// Approximate number of bytes taken by the state.
+    fn size(&self) -> usize {
+        match self {
+            HistoryState::Sheet(_, state) => state.size(),
+            HistoryState::Saved(_, config) => config.cell_values.iter().map(|value| value.value.len()).sum(),
+            HistoryState::Workspace(state) => state.data_sources.iter()
+                .flat_map(|ds| &ds.sheets)
+                .map(|sheet| rows_size(&sheet.table))
+                .sum(),
+            HistoryState::Command(_) => 0,
+        }
+    }
+}
+
+pub struct HistoryEntry {
+    pub description: String,
+    /// Where the change happened, e.g. the data source and sheet.
+    pub location: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    before: HistoryState,
+    after: HistoryState,
+    /// The saved form of the step; `None` if it does not change what is saved.
+    journal: OnceLock<Option<JournalEntry>>,
+    /// Approximate number of bytes taken by the states. The state before a step is usually
+    /// shared with the one after the previous step, so this is an upper bound.
+    size: usize,
+}
+
+impl HistoryEntry {
+    fn new(description: String, location: String, before: HistoryState, after: HistoryState) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let size = before.size() + after.size();
+        Self { description, location, timestamp, before, after, journal: OnceLock::new(), size }
+    }
+
+    pub fn from_journal(source: &str, location: String, entry: JournalEntry) -> Self {
+        let sheet = SheetId { source: source.to_string(), sheet: entry.sheet };
+        let before = HistoryState::Saved(sheet.clone(), Arc::new(entry.before.clone()));
+        let after = HistoryState::Saved(sheet, Arc::new(entry.after.clone()));
+        Self {
+            description: entry.description.clone(),
+            location,
+            timestamp: entry.timestamp,
+            size: before.size() + after.size(),
+            before,
+            after,
+            journal: OnceLock::from(Some(entry)),
+        }
+    }
+
+    fn journal_entry(&self, persist: &impl Fn(&SheetId, &SheetState) -> DataSheetConfiguration) -> Option<&JournalEntry> {
+        self.journal.get_or_init(|| {
+            let sheet = self.after.sheet()?.sheet;
+            let before = self.before.saved(persist)?;
+            let after = self.after.saved(persist)?;
+            (before != after).then(|| JournalEntry {
+                timestamp: self.timestamp,
+                description: self.description.clone(),
+                sheet,
+                before,
+                after,
+            })
+        }).as_ref()
+    }
+}
+
+/// Application-wide history of changes, covering all sheets of the workspace.
+pub struct History {
+    entries: Vec<HistoryEntry>,
+    /// Number of entries that are currently applied.
+    position: usize,
+    /// Last recorded state of every sheet that has been shown.
+    sheets: HashMap<SheetId, Arc<SheetState>>,
+    /// Approximate number of bytes taken by the states of the entries.
+    size: usize,
+    /// Approximate number of bytes the states of the entries may take.
+    max_size: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::with_max_size(MAX_HISTORY_SIZE)
+    }
+}
+
+impl History {
+    /// An empty history whose states may take about `max_size` bytes.
+    pub fn with_max_size(max_size: usize) -> Self {
+        Self { entries: Vec::new(), position: 0, sheets: HashMap::new(), size: 0, max_size }
+    }
+
+    /// A history that starts with steps restored from the journals, oldest first.
+    pub fn with_entries(entries: Vec<HistoryEntry>) -> Self {
+        let size = entries.iter().map(|entry| entry.size).sum();
+        Self { position: entries.len(), entries, size, ..Default::default() }
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    /// Whether the sheet may have changed since its last recorded state, without comparing
+    /// the rows. Sheets that are not tracked yet always need to be recorded.
+    pub fn columns_changed(&self, sheet: &SheetId, column_configs: &[ColumnConfiguration], formats: &[ConditionalFormat]) -> bool {
+        self.sheets.get(sheet).is_none_or(|s| s.columns_differ(column_configs, formats))
+    }
+
+    /// Records the state of a sheet as a new step if it differs from its last recorded state,
+    /// and returns whether it did. The first state of a sheet only serves as the starting point.
+    pub fn record_sheet(&mut self, sheet: SheetId, location: String, state: SheetState) -> bool {
+        let Some(before) = self.sheets.get(&sheet).cloned() else {
+            self.sheets.insert(sheet, Arc::new(state));
+            return false;
+        };
+        if !before.differs(&state) {
+            return false;
+        }
+
+        let after = Arc::new(state);
+        let description = before.describe_change(&after);
+        self.sheets.insert(sheet.clone(), after.clone());
+        self.push(HistoryEntry::new(
+            description,
+            location,
+            HistoryState::Sheet(sheet.clone(), before),
+            HistoryState::Sheet(sheet, after),
+        ));
+        true
+    }
+
+    pub fn record_workspace(&mut self, description: String, before: WorkspaceState, after: WorkspaceState) {
+        // The sheets are tracked again from the sources once they are shown.
+        self.sheets.clear();
+        self.push(HistoryEntry::new(
+            description,
+            "Workspace".to_string(),
+            HistoryState::Workspace(Arc::new(before)),
+            HistoryState::Workspace(Arc::new(after)),
+        ));
+    }
+
+    /// Records a command as a step, undone by handling `undo` and redone by handling `redo`.
+    pub fn record_command(&mut self, description: String, undo: Box<dyn ApplicationCommand>, redo: Box<dyn ApplicationCommand>) {
+        self.push(HistoryEntry::new(
+            description,
+            "Application".to_string(),
+            HistoryState::Command(Arc::from(undo)),
+            HistoryState::Command(Arc::from(redo)),
+        ));
+    }
+
+    /// The command to handle for the step from the current position to `position`, if that step
+    /// is a command. `position` must be next to the current one.
+    pub fn step_command(&self, position: usize) -> Option<Arc<dyn ApplicationCommand>> {
+        let state = match position.cmp(&self.position) {
+            std::cmp::Ordering::Less => &self.entries[position].before,
+            std::cmp::Ordering::Greater => &self.entries.get(self.position)?.after,
+            std::cmp::Ordering::Equal => return None,
+        };
+        match state {
+            HistoryState::Command(command) => Some(command.clone()),
+            _ => None,
+        }
+    }
+
+    /// The saved form of the applied steps of a data source, to be written to its journal.
+    /// Steps that do not change what is saved, like edits of physical cells, are left out.
+    pub fn journal(&self, source: &str, persist: impl Fn(&SheetId, &SheetState) -> DataSheetConfiguration) -> Vec<JournalEntry> {
+        self.entries[..self.position].iter()
+            .filter(|entry| entry.after.sheet().is_some_and(|sheet| sheet.source == source))
+            .filter_map(|entry| entry.journal_entry(&persist).cloned())
+            .collect()
+    }
+
+    fn push(&mut self, entry: HistoryEntry) {
+        // Recording a new step discards the steps that were undone.
+        for undone in self.entries.drain(self.position..) {
+            self.size -= undone.size;
+        }
+        self.size += entry.size;
+        self.entries.push(entry);
+        while self.entries.len() > MAX_HISTORY_STEPS || self.size > self.max_size && self.entries.len() > 1 {
+            self.size -= self.entries.remove(0).size;
+        }
+        self.position = self.entries.len();
+    }
+
+    /// Moves to the point where `position` steps are applied, and returns the states to restore
+    /// in order.
+    pub fn jump_to(&mut self, position: usize) -> Vec<HistoryState> {
+        let position = position.min(self.entries.len());
+        let mut states = Vec::new();
+        while self.position > position {
+            self.position -= 1;
+            states.push(self.entries[self.position].before.clone());
+        }
+        while self.position < position {
+            states.push(self.entries[self.position].after.clone());
+            self.position += 1;
+        }
+
+        for state in &states {
+            match state {
+                HistoryState::Sheet(sheet, state) => {
+                    self.sheets.insert(sheet.clone(), state.clone());
+                }
+                HistoryState::Saved(sheet, _) => {
+                    self.sheets.remove(sheet);
+                }
+                HistoryState::Workspace(_) => self.sheets.clear(),
+                HistoryState::Command(_) => {}
+            }
+        }
+        states
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(values: &[&str]) -> SheetState {
+        SheetState {
+            rows: values.iter().map(|v| Row { cells: vec![CellValue::from(*v)] }).collect(),
+            column_configs: vec![ColumnConfiguration {
+                is_name: true,
+                ..ColumnConfiguration::test("Name", ColumnType::Text)
+            }],
+            formats: Vec::new(),
+        }
+    }
+
+    fn sheet() -> SheetId {
+        SheetId { source: "data.csv".to_string(), sheet: 0 }
+    }
+
+    #[test]
+    fn test_records_and_describes_changes() {
+        let mut history = History::default();
+        history.record_sheet(sheet(), String::new(), state(&["a", "b"]));
+        assert!(history.entries().is_empty());
+
+        history.record_sheet(sheet(), String::new(), state(&["a", "b"]));
+        history.record_sheet(sheet(), String::new(), state(&["a", "c"]));
+        history.record_sheet(sheet(), String::new(), state(&["a", "c", "d"]));
+        let descriptions: Vec<&str> = history.entries().iter().map(|e| e.description.as_str()).collect();
+        assert_eq!(descriptions, ["Edit Name in row 2", "Insert 1 row"]);
+
+        let mut wider = state(&["a", "c", "d"]);
+        wider.column_configs[0].width = Some(120.0);
+        assert!(!history.columns_changed(&sheet(), &wider.column_configs, &wider.formats));
+    }
+
+    #[test]
+    fn test_oldest_steps_are_dropped_beyond_the_size_limit() {
+        let mut history = History::with_max_size(4 * 1024);
+        let large = "x".repeat(1024);
+        for values in [&["a"][..], &[large.as_str()], &["b"], &[large.as_str()], &["c"]] {
+            history.record_sheet(sheet(), String::new(), state(values));
+        }
+        assert_eq!(history.entries().len(), 3);
+        assert!(history.size <= history.max_size);
+        assert_eq!(history.position(), 3);
+    }
+
+    #[test]
+    fn test_jump_restores_states_and_discards_redo() {
+        let mut history = History::default();
+        for values in [&["a"][..], &["b"], &["c"]] {
+            history.record_sheet(sheet(), String::new(), state(values));
+        }
+        assert_eq!(history.position(), 2);
+
+        let states = history.jump_to(0);
+        assert_eq!(states.len(), 2);
+        let HistoryState::Sheet(_, restored) = states.last().unwrap() else { panic!() };
+        assert_eq!(restored.rows[0].cells[0].0, "a");
+        assert!(history.can_redo());
+
+        history.record_sheet(sheet(), String::new(), state(&["x"]));
+        assert_eq!(history.entries().len(), 1);
+        assert!(!history.can_redo());
+    }
+}
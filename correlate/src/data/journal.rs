@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::data::*;
+
+/// Maximum number of steps kept in the journal of a data source.
+pub const MAX_JOURNAL_ENTRIES: usize = 50;
+
+/// Size and modification time of a source file, used to detect changes made outside of Correlate.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceFingerprint {
+    pub size: u64,
+    pub modified: u64,
+}
+
+impl SourceFingerprint {
+    pub fn of<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?
+            .duration_since(std::time::UNIX_EPOCH).ok()?
+            .as_secs();
+        Some(Self { size: metadata.len(), modified })
+    }
+}
+
+/// A saved step of the history: the configuration of a sheet before and after the change.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub description: String,
+    pub sheet: usize,
+    pub before: DataSheetConfiguration,
+    pub after: DataSheetConfiguration,
+}
+
+/// Bounded journal of the saved changes of a data source, stored next to its `.correlate` file
+/// so they can still be undone after a restart.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Journal {
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// The source file the journal was recorded against.
+    pub source: SourceFingerprint,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    fn calculate_path<P: AsRef<Path>>(source_path: P) -> PathBuf {
+        let mut path = DataSourceConfiguration::calculate_path(source_path).into_os_string();
+        path.push(".journal");
+        path.into()
+    }
+
+    /// Loads the journal of a data source. Steps recorded after the saved `configuration` were
+    /// never saved, so they are dropped. The journal is discarded when the source file changed
+    /// since it was recorded, or when the saved configuration matches none of its steps.
+    pub fn load<P: AsRef<Path>>(source_path: P, fingerprint: Option<SourceFingerprint>, configuration: &DataSourceConfiguration) -> Vec<JournalEntry> {
+        let path = Self::calculate_path(&source_path);
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Vec::new();
+        };
+
+        let journal = match serde_json::from_str::<Self>(&content) {
+            Ok(journal) => journal,
+            Err(e) => {
+                log::error!("Failed to parse journal at {:?}: {}", path, e);
+                return Vec::new();
+            }
+        };
+
+        let saved = (Some(journal.source) == fingerprint)
+            .then(|| journal.saved_entries(configuration))
+            .flatten();
+        let Some(entries) = saved else {
+            log::warn!("Discarding journal at {:?}, as its data source changed outside of Correlate", path);
+            if let Err(e) = fs::remove_file(&path) {
+                log::error!("Failed to remove journal at {:?}: {}", path, e);
+            }
+            return Vec::new();
+        };
+        entries
+    }
+
+    /// The entries up to the saved configuration of each sheet, or `None` when a sheet was saved
+    /// in a state that isn't in the journal.
+    fn saved_entries(self, configuration: &DataSourceConfiguration) -> Option<Vec<JournalEntry>> {
+        let mut keep = vec![true; self.entries.len()];
+        let sheets: std::collections::BTreeSet<usize> = self.entries.iter().map(|entry| entry.sheet).collect();
+        for sheet in sheets {
+            let saved = configuration.sheets.get(sheet)?;
+            let indices: Vec<usize> = (0..self.entries.len()).filter(|&i| self.entries[i].sheet == sheet).collect();
+            let unsaved = match indices.iter().rposition(|&i| same_content(&self.entries[i].after, saved)) {
+                Some(last) => &indices[last + 1..],
+                // None of the steps of the sheet were saved.
+                None if same_content(&self.entries[indices[0]].before, saved) => &indices[..],
+                None => return None,
+            };
+            for &i in unsaved {
+                keep[i] = false;
+            }
+        }
+        Some(self.entries.into_iter().zip(keep).filter_map(|(entry, keep)| keep.then_some(entry)).collect())
+    }
+
+    /// Saves the journal of a data source, keeping only the latest entries. An empty journal
+    /// removes the file.
+    pub fn save<P: AsRef<Path>>(source_path: P, fingerprint: Option<SourceFingerprint>, mut entries: Vec<JournalEntry>) -> Result<(), String> {
+        let path = Self::calculate_path(&source_path);
+        let Some(source) = fingerprint.filter(|_| !entries.is_empty()) else {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| e.to_string())?;
+            }
+            return Ok(());
+        };
+
+        entries.drain(..entries.len().saturating_sub(MAX_JOURNAL_ENTRIES));
+        let journal = Self { path, source, entries };
+        let content = serde_json::to_string_pretty(&journal).map_err(|e| e.to_string())?;
+        fs::write(&journal.path, content).map_err(|e| e.to_string())
+    }
+
+    /// Removes the journal of a data source, if it has one.
+    pub fn remove<P: AsRef<Path>>(source_path: P) -> Result<(), String> {
+        let path = Self::calculate_path(&source_path);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the saved content of two sheet configurations. Column widths, frozen panes and the
+/// row density follow the layout and the order of the cell values follows the rows, so these
+/// are ignored.
+fn same_content(a: &DataSheetConfiguration, b: &DataSheetConfiguration) -> bool {
+    let normalize = |config: &DataSheetConfiguration| {
+        let mut config = config.clone();
+        config.frozen = FrozenPanes::default();
+        config.row_density = RowDensity::default();
+        for column in &mut config.column_configs {
+            column.width = None;
+        }
+        config.cell_values.sort_by(|a, b| (&a.key, &a.column_name).cmp(&(&b.key, &b.column_name)));
+        config
+    };
+    normalize(a) == normalize(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sheet(value: &str) -> DataSheetConfiguration {
+        DataSheetConfiguration {
+            name: "Sheet".to_string(),
+            display_name: None,
+            column_configs: Vec::new(),
+            sort_config: None,
+            cell_values: vec![CellValueConfiguration { key: "1".to_string(), column_name: "Note".to_string(), value: value.to_string() }],
+            conditional_formats: Vec::new(),
+            row_order: None,
+            frozen: FrozenPanes::default(),
+            row_density: RowDensity::default(),
+        }
+    }
+
+    #[test]
+    fn test_journal_is_discarded_when_the_source_changed() {
+        let dir = std::env::temp_dir().join(format!("correlate-journal-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("data.csv");
+        fs::write(&source, "Id\n1\n").unwrap();
+
+        let fingerprint = SourceFingerprint::of(&source);
+        let entry = JournalEntry { timestamp: 0, description: "Edit".to_string(), sheet: 0, before: sheet("a"), after: sheet("b") };
+        Journal::save(&source, fingerprint, vec![entry.clone()]).unwrap();
+
+        let configuration = DataSourceConfiguration { sheets: vec![sheet("b")], ..DataSourceConfiguration::new(PathBuf::new(), None, Vec::new()) };
+        assert_eq!(Journal::load(&source, fingerprint, &configuration), vec![entry.clone()]);
+
+        // The saved configuration no longer ends in the journal.
+        let edited = DataSourceConfiguration { sheets: vec![sheet("c")], ..configuration.clone() };
+        assert!(Journal::load(&source, fingerprint, &edited).is_empty());
+        assert!(!Journal::calculate_path(&source).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unsaved_steps_are_dropped() {
+        let dir = std::env::temp_dir().join(format!("correlate-journal-unsaved-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("data.csv");
+        fs::write(&source, "Id\n1\n").unwrap();
+
+        let fingerprint = SourceFingerprint::of(&source);
+        let step = |index, before: &str, after: &str| JournalEntry { timestamp: 0, description: "Edit".to_string(), sheet: index, before: sheet(before), after: sheet(after) };
+        let entries = vec![step(0, "a", "b"), step(1, "x", "y"), step(0, "b", "c")];
+        Journal::save(&source, fingerprint, entries.clone()).unwrap();
+
+        // The first sheet was last saved after its first step, the second one never.
+        let configuration = DataSourceConfiguration { sheets: vec![sheet("b"), sheet("x")], ..DataSourceConfiguration::new(PathBuf::new(), None, Vec::new()) };
+        assert_eq!(Journal::load(&source, fingerprint, &configuration), vec![entries[0].clone()]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
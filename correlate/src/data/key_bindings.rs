@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+use egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use crate::application_command::ApplicationCommand;
+use crate::data::*;
+use crate::egui_data_table::*;
+use crate::view::*;
+
+/// Actions of the table that are bound while a cell is edited.
+const EDITING_ACTIONS: [UiAction; 6] = [
+    UiAction::CancelEdition,
+    UiAction::CommitEdition,
+    UiAction::CommitEditionAndMove(MoveDirection::Up),
+    UiAction::CommitEditionAndMove(MoveDirection::Down),
+    UiAction::CommitEditionAndMove(MoveDirection::Left),
+    UiAction::CommitEditionAndMove(MoveDirection::Right),
+];
+
+/// Where a shortcut applies. Shortcuts of the same scope, or of the application and the table,
+/// can't share a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutScope {
+    /// While a cell is edited.
+    Editing,
+    /// While the table has focus.
+    Table,
+    /// Anywhere in the application.
+    Application,
+}
+
+impl ShortcutScope {
+    fn overlaps(self, other: ShortcutScope) -> bool {
+        self == other || self == ShortcutScope::Application || other == ShortcutScope::Application
+    }
+}
+
+/// Something a keyboard shortcut can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutTarget {
+    Table(UiAction),
+    CommandPalette,
+    AddProject,
+    ToggleScrollBarVisibility,
+    ClearModificationFlag,
+    ToggleFlag(Flag),
+}
+
+impl ShortcutTarget {
+    pub fn all() -> Vec<ShortcutTarget> {
+        let mut targets = vec![
+            ShortcutTarget::CommandPalette,
+            ShortcutTarget::AddProject,
+            ShortcutTarget::ToggleScrollBarVisibility,
+            ShortcutTarget::ClearModificationFlag,
+        ];
+        targets.extend(Flag::ALL.map(ShortcutTarget::ToggleFlag));
+        targets.push(ShortcutTarget::Table(UiAction::SelectionStartEditing));
+        targets.extend([MoveDirection::Up, MoveDirection::Down, MoveDirection::Left, MoveDirection::Right]
+            .map(|direction| ShortcutTarget::Table(UiAction::MoveSelection(direction))));
+        targets.extend(TABLE_ACTIONS.map(ShortcutTarget::Table));
+        targets.extend(EDITING_ACTIONS.map(ShortcutTarget::Table));
+        targets
+    }
+
+    /// Identifies the target in the configuration file. The ids are stored, so they must not
+    /// change when an action or flag is renamed.
+    pub fn id(&self) -> String {
+        match self {
+            ShortcutTarget::Table(action) => format!("table.{}", table_action_id(*action)),
+            ShortcutTarget::CommandPalette => "CommandPalette".to_string(),
+            ShortcutTarget::AddProject => "AddProject".to_string(),
+            ShortcutTarget::ToggleScrollBarVisibility => "ToggleScrollBarVisibility".to_string(),
+            ShortcutTarget::ClearModificationFlag => "ClearModificationFlag".to_string(),
+            ShortcutTarget::ToggleFlag(flag) => format!("flag.{}", flag_id(*flag)),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            ShortcutTarget::Table(action) => table_action_label(*action).to_string(),
+            ShortcutTarget::CommandPalette => "Command palette".to_string(),
+            ShortcutTarget::AddProject => "Add project".to_string(),
+            ShortcutTarget::ToggleScrollBarVisibility => "Toggle scroll bar visibility".to_string(),
+            ShortcutTarget::ClearModificationFlag => "Clear modification flag".to_string(),
+            ShortcutTarget::ToggleFlag(flag) => format!("Toggle {}", flag.label().to_lowercase()),
+        }
+    }
+
+    pub fn scope(&self) -> ShortcutScope {
+        match self {
+            ShortcutTarget::Table(action) if EDITING_ACTIONS.contains(action) => ShortcutScope::Editing,
+            ShortcutTarget::Table(_) => ShortcutScope::Table,
+            _ => ShortcutScope::Application,
+        }
+    }
+
+    pub fn default_shortcuts(&self) -> Vec<KeyboardShortcut> {
+        match self {
+            ShortcutTarget::Table(action) => {
+                let cursor = if self.scope() == ShortcutScope::Editing { UiCursorState::Editing } else { UiCursorState::Idle };
+                default_hotkeys(&UiActionContext { cursor })
+                    .into_iter()
+                    .filter(|(_, a)| a == action)
+                    .map(|(shortcut, _)| shortcut)
+                    .collect()
+            }
+            ShortcutTarget::CommandPalette => vec![KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::P)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// The command that runs when the shortcut of an application target is pressed. The
+    /// command palette and the table handle their shortcuts themselves.
+    pub fn command(&self) -> Option<Box<dyn ApplicationCommand>> {
+        match self {
+            ShortcutTarget::AddProject => Some(Box::new(AddProject)),
+            ShortcutTarget::ToggleScrollBarVisibility => Some(Box::new(ToggleScrollBarVisibility)),
+            ShortcutTarget::ClearModificationFlag => Some(Box::new(ClearUserModificationFlag)),
+            ShortcutTarget::ToggleFlag(flag) => Some(Box::new(ToggleFlag { flag: *flag })),
+            ShortcutTarget::Table(_) | ShortcutTarget::CommandPalette => None,
+        }
+    }
+}
+
+/// Keyboard shortcuts that were changed from their defaults, stored in the configuration by the
+/// id of their target, e.g. `"table.CopySelection": ["Ctrl+C"]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyBindings(BTreeMap<String, Vec<String>>);
+
+impl KeyBindings {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn is_customized(&self, target: ShortcutTarget) -> bool {
+        self.0.contains_key(&target.id())
+    }
+
+    pub fn shortcuts(&self, target: ShortcutTarget) -> Vec<KeyboardShortcut> {
+        match self.0.get(&target.id()) {
+            Some(shortcuts) => shortcuts.iter().filter_map(|s| parse_shortcut(s)).collect(),
+            None => target.default_shortcuts(),
+        }
+    }
+
+    pub fn set(&mut self, target: ShortcutTarget, shortcuts: Vec<KeyboardShortcut>) {
+        if shortcuts == target.default_shortcuts() {
+            self.0.remove(&target.id());
+        } else {
+            self.0.insert(target.id(), shortcuts.iter().map(format_shortcut).collect());
+        }
+    }
+
+    pub fn reset(&mut self, target: ShortcutTarget) {
+        self.0.remove(&target.id());
+    }
+
+    pub fn reset_all(&mut self) {
+        self.0.clear();
+    }
+
+    /// Finds the other target that the shortcut is bound to within an overlapping scope.
+    pub fn conflict(&self, target: ShortcutTarget, shortcut: KeyboardShortcut) -> Option<ShortcutTarget> {
+        ShortcutTarget::all().into_iter()
+            .filter(|other| *other != target && other.scope().overlaps(target.scope()))
+            .find(|other| self.shortcuts(*other).contains(&shortcut))
+    }
+
+    /// The hotkeys of the table. Shortcuts with more modifiers go first, as a shortcut also
+    /// matches when extra modifiers are held.
+    pub fn table_hotkeys(&self, editing: bool) -> Vec<(KeyboardShortcut, UiAction)> {
+        let scope = if editing { ShortcutScope::Editing } else { ShortcutScope::Table };
+        let mut hotkeys = ShortcutTarget::all().into_iter()
+            .filter(|target| target.scope() == scope)
+            .filter_map(|target| match target {
+                ShortcutTarget::Table(action) => Some((self.shortcuts(target), action)),
+                _ => None,
+            })
+            .flat_map(|(shortcuts, action)| shortcuts.into_iter().map(move |shortcut| (shortcut, action)))
+            .collect::<Vec<_>>();
+        hotkeys.sort_by_key(|(shortcut, _)| std::cmp::Reverse(modifier_count(shortcut.modifiers)));
+        hotkeys
+    }
+
+    /// The first target with a command whose shortcut was pressed, consuming the shortcut.
+    pub fn consume_pressed(&self, input: &mut egui::InputState) -> Option<ShortcutTarget> {
+        ShortcutTarget::all().into_iter()
+            .filter(|target| target.command().is_some())
+            .find(|target| self.shortcuts(*target).iter().any(|shortcut| input.consume_shortcut(shortcut)))
+    }
+}
+
+fn table_action_id(action: UiAction) -> &'static str {
+    match action {
+        UiAction::SelectionStartEditing => "SelectionStartEditing",
+        UiAction::CancelEdition => "CancelEdition",
+        UiAction::CommitEdition => "CommitEdition",
+        UiAction::CommitEditionAndMove(MoveDirection::Up) => "CommitEditionAndMove(Up)",
+        UiAction::CommitEditionAndMove(MoveDirection::Down) => "CommitEditionAndMove(Down)",
+        UiAction::CommitEditionAndMove(MoveDirection::Left) => "CommitEditionAndMove(Left)",
+        UiAction::CommitEditionAndMove(MoveDirection::Right) => "CommitEditionAndMove(Right)",
+        UiAction::Undo => "Undo",
+        UiAction::Redo => "Redo",
+        UiAction::MoveSelection(MoveDirection::Up) => "MoveSelection(Up)",
+        UiAction::MoveSelection(MoveDirection::Down) => "MoveSelection(Down)",
+        UiAction::MoveSelection(MoveDirection::Left) => "MoveSelection(Left)",
+        UiAction::MoveSelection(MoveDirection::Right) => "MoveSelection(Right)",
+        UiAction::CopySelection => "CopySelection",
+        UiAction::CutSelection => "CutSelection",
+        UiAction::PasteInPlace => "PasteInPlace",
+        UiAction::PasteInsert => "PasteInsert",
+        UiAction::DuplicateRow => "DuplicateRow",
+        UiAction::DeleteSelection => "DeleteSelection",
+        UiAction::DeleteRow => "DeleteRow",
+        UiAction::NavPageDown => "NavPageDown",
+        UiAction::NavPageUp => "NavPageUp",
+        UiAction::NavTop => "NavTop",
+        UiAction::NavBottom => "NavBottom",
+        UiAction::SelectionDuplicateValues => "SelectionDuplicateValues",
+        UiAction::SelectAll => "SelectAll",
+        UiAction::Find => "Find",
+        UiAction::Replace => "Replace",
+    }
+}
+
+fn flag_id(flag: Flag) -> &'static str {
+    match flag {
+        Flag::RowProtection => "RowProtection",
+        Flag::SingleClickEdit => "SingleClickEdit",
+        Flag::AutoShrinkX => "AutoShrinkX",
+        Flag::AutoShrinkY => "AutoShrinkY",
+        Flag::ShowProblems => "ShowProblems",
+        Flag::ShowHistory => "ShowHistory",
+        Flag::ShowShortcuts => "ShowShortcuts",
+        Flag::ShowTrash => "ShowTrash",
+    }
+}
+
+fn modifier_count(modifiers: Modifiers) -> usize {
+    [modifiers.ctrl, modifiers.alt, modifiers.shift, modifiers.mac_cmd].iter().filter(|m| **m).count()
+}
+
+/// Writes a shortcut as e.g. `Ctrl+Shift+P`, independent of the platform.
+pub fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    let modifiers = &shortcut.modifiers;
+    let mut parts = Vec::new();
+    for (held, name) in [(modifiers.ctrl, "Ctrl"), (modifiers.alt, "Alt"), (modifiers.shift, "Shift"), (modifiers.mac_cmd, "Cmd")] {
+        if held {
+            parts.push(name);
+        }
+    }
+    parts.push(shortcut.logical_key.name());
+    parts.join("+")
+}
+
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+    let key = Key::from_name(parts.pop()?)?;
+
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers |= match part.to_lowercase().as_str() {
+            "ctrl" => Modifiers::CTRL,
+            "alt" => Modifiers::ALT,
+            "shift" => Modifiers::SHIFT,
+            "cmd" => Modifiers::MAC_CMD,
+            _ => return None,
+        };
+    }
+    Some(KeyboardShortcut::new(modifiers, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcut_notation() {
+        let shortcut = KeyboardShortcut::new(Modifiers::CTRL | Modifiers::SHIFT, Key::P);
+        assert_eq!(format_shortcut(&shortcut), "Ctrl+Shift+P");
+        assert_eq!(parse_shortcut("ctrl + shift + P"), Some(shortcut));
+        assert_eq!(parse_shortcut("Delete"), Some(KeyboardShortcut::new(Modifiers::NONE, Key::Delete)));
+        assert_eq!(parse_shortcut("Hyper+P"), None);
+
+        let ids = ShortcutTarget::all().iter().map(ShortcutTarget::id).collect::<Vec<_>>();
+        assert!(ids.iter().enumerate().all(|(i, id)| !ids[i + 1..].contains(id)));
+    }
+
+    #[test]
+    fn test_rebinding_and_conflicts() {
+        let copy = ShortcutTarget::Table(UiAction::CopySelection);
+        let ctrl_c = KeyboardShortcut::new(Modifiers::CTRL, Key::C);
+        let ctrl_k = KeyboardShortcut::new(Modifiers::CTRL, Key::K);
+        let mut bindings = KeyBindings::default();
+        assert_eq!(bindings.shortcuts(copy), vec![ctrl_c]);
+
+        bindings.set(copy, vec![ctrl_k]);
+        assert_eq!(serde_json::to_string(&bindings).unwrap(), r#"{"table.CopySelection":["Ctrl+K"]}"#);
+        assert!(bindings.table_hotkeys(false).contains(&(ctrl_k, UiAction::CopySelection)));
+        assert!(!bindings.table_hotkeys(false).contains(&(ctrl_c, UiAction::CopySelection)));
+
+        // Enter commits an edit and starts one, which never happen at the same time.
+        let enter = KeyboardShortcut::new(Modifiers::NONE, Key::Enter);
+        assert_eq!(bindings.conflict(ShortcutTarget::Table(UiAction::SelectionStartEditing), enter), None);
+        assert_eq!(bindings.conflict(ShortcutTarget::AddProject, ctrl_k), Some(copy));
+        assert_eq!(bindings.conflict(ShortcutTarget::AddProject, ctrl_c), None);
+
+        bindings.set(copy, vec![ctrl_c]);
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        let ids: std::collections::HashSet<String> = ShortcutTarget::all().iter().map(ShortcutTarget::id).collect();
+        assert_eq!(ids.len(), ShortcutTarget::all().len());
+        assert_eq!(ShortcutTarget::Table(UiAction::MoveSelection(MoveDirection::Up)).id(), "table.MoveSelection(Up)");
+        assert_eq!(ShortcutTarget::ToggleFlag(Flag::ShowTrash).id(), "flag.ShowTrash");
+    }
+}
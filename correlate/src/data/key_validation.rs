@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use crate::data::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyIssue {
+    /// None of the key columns of the row carries a value.
+    Empty,
+    /// Another row has the same key.
+    Duplicate,
+}
+
+impl KeyIssue {
+    pub fn description(&self) -> &'static str {
+        match self {
+            KeyIssue::Empty => "This row has an empty key. Its virtual cell values will not be saved.",
+            KeyIssue::Duplicate => "This row shares its key with another row. Its virtual cell values are ambiguous.",
+        }
+    }
+}
+
+/// Result of validating the key columns of a sheet.
+#[derive(Debug, Clone, Default)]
+pub struct KeyIssues {
+    /// Modifications of the table the issues were computed at.
+    validated: Option<u64>,
+    key_columns: Vec<usize>,
+    rows: HashMap<usize, KeyIssue>,
+    pub empty_rows: usize,
+    pub duplicate_rows: usize,
+    pub duplicate_keys: usize,
+}
+
+impl KeyIssues {
+    /// Validates the rows of a table after the given number of `modifications`.
+    pub fn validate(key_columns: &[usize], rows: &[Row], modifications: u64) -> Self {
+        let mut issues = Self {
+            validated: Some(modifications),
+            key_columns: key_columns.to_vec(),
+            ..Default::default()
+        };
+
+        let mut rows_by_key: HashMap<RowKey, Vec<usize>> = HashMap::new();
+        for (row_idx, row) in rows.iter().enumerate() {
+            let Some(key) = RowKey::from_row(key_columns, row) else {
+                // A sheet without key columns has nothing to validate.
+                return issues;
+            };
+            if key.is_empty() {
+                issues.rows.insert(row_idx, KeyIssue::Empty);
+                issues.empty_rows += 1;
+            } else {
+                rows_by_key.entry(key).or_default().push(row_idx);
+            }
+        }
+
+        for row_indices in rows_by_key.values().filter(|r| r.len() > 1) {
+            issues.duplicate_keys += 1;
+            issues.duplicate_rows += row_indices.len();
+            for &row_idx in row_indices {
+                issues.rows.insert(row_idx, KeyIssue::Duplicate);
+            }
+        }
+        issues
+    }
+
+    /// Whether the issues need to be recomputed for the given key columns, or because the table
+    /// was modified since.
+    pub fn is_stale(&self, key_columns: &[usize], modifications: u64) -> bool {
+        self.validated != Some(modifications) || self.key_columns != key_columns
+    }
+
+    pub fn row_issue(&self, row_idx: usize) -> Option<KeyIssue> {
+        self.rows.get(&row_idx).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Short summary for the status bar, e.g. "1 row with an empty key, 2 duplicate keys (5 rows)".
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.empty_rows > 0 {
+            parts.push(format!(
+                "{} {} with an empty key",
+                self.empty_rows,
+                if self.empty_rows == 1 { "row" } else { "rows" }
+            ));
+        }
+        if self.duplicate_keys > 0 {
+            parts.push(format!(
+                "{} duplicate {} ({} rows)",
+                self.duplicate_keys,
+                if self.duplicate_keys == 1 { "key" } else { "keys" },
+                self.duplicate_rows
+            ));
+        }
+        parts.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(values: &[[&str; 2]]) -> Vec<Row> {
+        values.iter()
+            .map(|v| Row { cells: v.iter().map(|c| CellValue::from(*c)).collect() })
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_empty_and_duplicate_keys() {
+        let rows = rows(&[["1", "a"], ["", "b"], ["2", "c"], ["1", "d"], ["", "e"]]);
+        let issues = KeyIssues::validate(&[0], &rows, 0);
+
+        assert_eq!(issues.row_issue(0), Some(KeyIssue::Duplicate));
+        assert_eq!(issues.row_issue(1), Some(KeyIssue::Empty));
+        assert_eq!(issues.row_issue(2), None);
+        assert_eq!(issues.row_issue(3), Some(KeyIssue::Duplicate));
+        assert_eq!(issues.summary(), "2 rows with an empty key, 1 duplicate key (2 rows)");
+    }
+
+    #[test]
+    fn test_composite_key_is_unique_as_a_whole() {
+        let rows = rows(&[["1", "a"], ["1", "b"], ["", "c"]]);
+        let issues = KeyIssues::validate(&[0, 1], &rows, 3);
+        assert!(issues.is_empty());
+        assert!(!issues.is_stale(&[0, 1], 3));
+        assert!(issues.is_stale(&[0], 3));
+        assert!(issues.is_stale(&[0, 1], 4));
+    }
+
+    #[test]
+    fn test_sheet_without_key_has_no_issues() {
+        let issues = KeyIssues::validate(&[], &rows(&[["", ""], ["", ""]]), 0);
+        assert!(issues.is_empty());
+    }
+}
@@ -10,9 +10,50 @@ pub use column_type::*;
 pub mod editors;
 pub use editors::*;
 
+pub mod row_key;
+pub use row_key::*;
+
+pub mod key_validation;
+pub use key_validation::*;
+
 pub mod column_configuration;
 pub use column_configuration::*;
 
+pub mod column_validation;
+pub use column_validation::*;
+
+pub mod cell_coercion;
+pub use cell_coercion::*;
+
+pub mod conditional_format;
+pub use conditional_format::*;
+
+pub mod find;
+pub use find::*;
+pub mod command_palette;
+pub use command_palette::*;
+pub mod key_bindings;
+pub use key_bindings::*;
+pub mod trash;
+pub use trash::*;
+pub mod portable_path;
+pub use portable_path::*;
+
+pub mod fill_series;
+pub use fill_series::*;
+
+pub mod frozen_panes;
+pub use frozen_panes::*;
+
+pub mod row_density;
+pub use row_density::*;
+
+pub mod history;
+pub use history::*;
+
+pub mod journal;
+pub use journal::*;
+
 pub mod configuration;
 pub use configuration::*;
 
@@ -58,5 +99,8 @@ pub use project::*;
 pub mod project_configuration;
 pub use project_configuration::*;
 
+pub mod project_bundle;
+pub use project_bundle::*;
+
 pub mod project_hierarchy;
 pub use project_hierarchy::*;
@@ -0,0 +1,115 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Writes `path` relative to the `base` folder, with `/` separators so it works on every
+/// platform. Paths that share no folder with `base`, like those on another drive, stay absolute.
+pub fn relative_path(base: &Path, path: &str) -> String {
+    let absolute = Path::new(path);
+    if !absolute.is_absolute() {
+        return path.to_string();
+    }
+
+    let base = base.components().collect::<Vec<_>>();
+    let components = absolute.components().collect::<Vec<_>>();
+    let common = base.iter().zip(&components).take_while(|(a, b)| a == b).count();
+    if !components[..common].iter().any(|c| matches!(c, Component::Normal(_))) {
+        return path.to_string();
+    }
+
+    let parents = std::iter::repeat_n("..".to_string(), base.len() - common);
+    let rest = components[common..].iter().map(|c| c.as_os_str().to_string_lossy().into_owned());
+    parents.chain(rest).collect::<Vec<_>>().join("/")
+}
+
+/// Resolves a stored path against the `base` folder. Earlier versions stored paths relative to
+/// the working directory, so those are still found there.
+pub fn resolve_path(base: &Path, stored: &str) -> String {
+    let path = Path::new(stored);
+    if path.is_absolute() {
+        return stored.to_string();
+    }
+
+    let resolved = normalize(&base.join(path));
+    if !resolved.exists() && path.exists()
+        && let Ok(absolute) = std::path::absolute(path) {
+        return normalize(&absolute).to_string_lossy().into_owned();
+    }
+    resolved.to_string_lossy().into_owned()
+}
+
+/// Where `path` is now, if it moved along with the folder that `old` was moved out of to
+/// become `new`. The folders that both end in are taken to be moved together.
+pub fn relocate(path: &str, old: &str, new: &str) -> Option<String> {
+    let old = Path::new(old).components().collect::<Vec<_>>();
+    let new = Path::new(new).components().collect::<Vec<_>>();
+    if old.is_empty() || new.is_empty() {
+        return None;
+    }
+
+    // The file itself may have been renamed, so only its folders are compared.
+    let moved = old.iter().rev().zip(new.iter().rev())
+        .skip(1)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_prefix = old[..old.len() - 1 - moved].iter().collect::<PathBuf>();
+    let new_prefix = new[..new.len() - 1 - moved].iter().collect::<PathBuf>();
+
+    let rest = Path::new(path).strip_prefix(&old_prefix).ok()?;
+    Some(new_prefix.join(rest).to_string_lossy().into_owned())
+}
+
+/// The absolute folder that `path` is in, which the paths stored in it are relative to.
+pub fn parent_dir(path: &Path) -> PathBuf {
+    std::path::absolute(path).ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+}
+
+/// Removes `.` and `..` from a path without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_are_stored_relative_to_the_base() {
+        let root = std::env::temp_dir().join("correlate-paths");
+        let base = root.join("config");
+        let inside = root.join("config").join("data").join("a.csv").to_string_lossy().into_owned();
+        let beside = root.join("shared").join("b.xlsx").to_string_lossy().into_owned();
+
+        assert_eq!(relative_path(&base, &inside), "data/a.csv");
+        assert_eq!(relative_path(&base, &beside), "../shared/b.xlsx");
+        assert_eq!(relative_path(&base, "already/relative.csv"), "already/relative.csv");
+
+        assert_eq!(resolve_path(&base, "data/a.csv"), inside);
+        assert_eq!(resolve_path(&base, "../shared/b.xlsx"), beside);
+        assert_eq!(resolve_path(&base, &beside), beside);
+    }
+
+    #[test]
+    fn test_files_move_along_with_their_folder() {
+        let old_root = std::env::temp_dir().join("old").join("project");
+        let new_root = std::env::temp_dir().join("new").join("moved").join("project");
+        let path = |root: &Path, file: &str| root.join("data").join(file).to_string_lossy().into_owned();
+
+        let located = relocate(&path(&old_root, "b.csv"), &path(&old_root, "a.csv"), &path(&new_root, "a.csv"));
+        assert_eq!(located, Some(path(&new_root, "b.csv")));
+
+        // A file outside of the moved folder can't be found this way.
+        let elsewhere = std::env::temp_dir().join("other").join("c.csv").to_string_lossy().into_owned();
+        assert_eq!(relocate(&elsewhere, &path(&old_root, "a.csv"), &path(&new_root, "a.csv")), None);
+    }
+}
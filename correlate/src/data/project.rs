@@ -5,11 +5,12 @@ pub struct Project {
 }
 
 impl Project {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, path: std::path::PathBuf) -> Self {
         Self {
             configuration: ProjectConfiguration {
+                path,
                 name,
-                data_sources: vec![],
+                ..Default::default()
             },
         }
     }
@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::data::*;
+
+/// Extension of the archives that projects are exported to.
+pub const BUNDLE_EXTENSION: &str = "correlatebundle";
+
+/// Name of the project definition inside a bundle.
+const BUNDLE_PROJECT: &str = "project.correlateproj";
+
+/// A file of a bundle that can't be imported as is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BundleConflict {
+    /// A data source or project that is open would be replaced.
+    Open(PathBuf),
+    /// A file that isn't open would be overwritten.
+    Exists(PathBuf),
+}
+
+/// A project archived together with its data sources and their `.correlate` files, to be handed
+/// to someone else. Data sources are stored in a `data` folder, next to the project definition.
+pub struct ProjectBundle {
+    pub path: PathBuf,
+}
+
+impl ProjectBundle {
+    /// Where each data source of the project is stored in the bundle. Files with the same name
+    /// from different folders are kept apart in numbered folders.
+    fn entry_names(project: &ProjectConfiguration) -> BTreeMap<String, String> {
+        let mut names = BTreeMap::new();
+        for source in &project.data_sources {
+            let file_name = Path::new(source).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            let name = (1..)
+                .map(|n| match n {
+                    1 => format!("data/{}", file_name),
+                    n => format!("data/{}/{}", n, file_name),
+                })
+                .find(|name| !names.values().any(|used| used == name))
+                .unwrap_or_default();
+            names.insert(source.clone(), name);
+        }
+        names
+    }
+
+    /// Writes the project, its data sources and their `.correlate` files to the archive.
+    pub fn export(&self, project: &ProjectConfiguration) -> Result<(), String> {
+        let names = Self::entry_names(project);
+        let mut definition = project.clone();
+        definition.map_paths(|source| names.get(source).cloned().unwrap_or_else(|| source.to_string()));
+
+        let file = fs::File::create(&self.path).map_err(|e| format!("Failed to create {}: {}", self.path.display(), e))?;
+        let mut archive = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        let mut add = |name: &str, content: &[u8]| -> Result<(), String> {
+            archive.start_file(name, options).map_err(|e| e.to_string())?;
+            archive.write_all(content).map_err(|e| e.to_string())
+        };
+
+        let content = serde_json::to_string_pretty(&definition).map_err(|e| e.to_string())?;
+        add(BUNDLE_PROJECT, content.as_bytes())?;
+        for (source, name) in &names {
+            let content = fs::read(source).map_err(|e| format!("Failed to read {}: {}", source, e))?;
+            add(name, &content)?;
+
+            let companion = DataSourceConfiguration::calculate_path(source);
+            if companion.exists() {
+                let content = fs::read(&companion).map_err(|e| format!("Failed to read {}: {}", companion.display(), e))?;
+                add(&DataSourceConfiguration::calculate_path(name).to_string_lossy(), &content)?;
+            }
+        }
+        archive.finish().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// The project file that importing into `folder` creates, named after the bundle.
+    pub fn project_path(&self, folder: &Path) -> PathBuf {
+        let name = self.path.file_stem().unwrap_or(self.path.as_os_str());
+        folder.join(format!("{}.{}", name.to_string_lossy(), PROJECT_FILE_EXTENSION))
+    }
+
+    /// Where the files of the bundle go when it is imported into `folder`, by their name in
+    /// the bundle.
+    fn targets(&self, folder: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+        let file = fs::File::open(&self.path).map_err(|e| format!("Failed to open {}: {}", self.path.display(), e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("{} is no project bundle: {}", self.path.display(), e))?;
+        let mut targets = Vec::new();
+        for index in 0..archive.len() {
+            let entry = archive.by_index(index).map_err(|e| e.to_string())?;
+            if entry.is_dir() {
+                continue;
+            }
+            let name = entry.name().to_string();
+            let target = match name.as_str() {
+                BUNDLE_PROJECT => self.project_path(folder),
+                _ => folder.join(entry.enclosed_name().ok_or_else(|| format!("{} points outside of the bundle", name))?),
+            };
+            targets.push((name, target));
+        }
+        if !targets.iter().any(|(name, _)| name == BUNDLE_PROJECT) {
+            return Err(format!("{} contains no project", self.path.display()));
+        }
+        Ok(targets)
+    }
+
+    /// The files that importing into `folder` would replace. `open` holds the paths of the data
+    /// sources and projects that are open.
+    pub fn conflicts(&self, folder: &Path, open: &[PathBuf]) -> Result<Vec<BundleConflict>, String> {
+        let folder = std::path::absolute(folder).map_err(|e| e.to_string())?;
+        Ok(self.targets(&folder)?.into_iter()
+            .filter_map(|(_, target)| {
+                if open.contains(&target) {
+                    Some(BundleConflict::Open(target))
+                } else if target.exists() {
+                    Some(BundleConflict::Exists(target))
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Extracts the bundle into `folder`, overwriting files that exist, and returns the project
+    /// file. Its paths are relative, so they point to the extracted data sources.
+    pub fn import(&self, folder: &Path) -> Result<PathBuf, String> {
+        let folder = std::path::absolute(folder).map_err(|e| e.to_string())?;
+        let targets = self.targets(&folder)?;
+        let file = fs::File::open(&self.path).map_err(|e| e.to_string())?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+        for (name, target) in targets {
+            let mut content = Vec::new();
+            archive.by_name(&name).map_err(|e| e.to_string())?
+                .read_to_end(&mut content).map_err(|e| e.to_string())?;
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(&target, content).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        }
+        Ok(self.project_path(&folder))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let dir = std::env::temp_dir().join(format!("correlate-bundle-{}", std::process::id()));
+        let source = |folder: &str| dir.join(folder).join("people.csv").to_string_lossy().into_owned();
+        for folder in ["a", "b"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+            fs::write(source(folder), format!("Name\n{}\n", folder)).unwrap();
+        }
+        fs::write(DataSourceConfiguration::calculate_path(source("a")), "{}").unwrap();
+        let project = ProjectConfiguration {
+            path: dir.join("Team.correlateproj"),
+            name: "Team".to_string(),
+            data_sources: vec![source("a"), source("b")],
+            ..Default::default()
+        };
+
+        let bundle = ProjectBundle { path: dir.join("Team.correlatebundle") };
+        bundle.export(&project).unwrap();
+
+        let imported = dir.join("imported");
+        assert_eq!(bundle.conflicts(&imported, &[]).unwrap(), vec![]);
+        let project_path = bundle.import(&imported).unwrap();
+        let loaded = ProjectConfiguration::load(&project_path).unwrap();
+        let extracted = |name: &str| imported.join("data").join(name).to_string_lossy().into_owned();
+        assert_eq!(loaded.data_sources, vec![extracted("people.csv"), extracted("2/people.csv")]);
+        assert_eq!(fs::read_to_string(extracted("2/people.csv")).unwrap(), "Name\nb\n");
+        assert!(DataSourceConfiguration::calculate_path(extracted("people.csv")).exists());
+
+        let open = vec![PathBuf::from(extracted("people.csv"))];
+        let conflicts = bundle.conflicts(&imported, &open).unwrap();
+        assert!(conflicts.contains(&BundleConflict::Open(open[0].clone())));
+        assert!(conflicts.contains(&BundleConflict::Exists(project_path)));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,6 +1,157 @@
 ﻿use serde::{Deserialize, Serialize};
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::data::*;
+
+/// Extension of the files that projects are stored in.
+pub const PROJECT_FILE_EXTENSION: &str = "correlateproj";
+
+/// The sheet that is shown of a data source.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectView {
+    pub data_source: String,
+    pub sheet: String,
+}
+
+/// A column that relates to another sheet. Kept in the project so the relation is restored
+/// for colleagues that only have the data sources, without their `.correlate` files.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProjectRelation {
+    pub data_source: String,
+    pub sheet: String,
+    pub column: String,
+    pub related_source: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ProjectConfiguration {
+    /// The project file, set when the project is created or loaded.
+    #[serde(skip)]
+    pub path: PathBuf,
+
     pub name: String,
     pub data_sources: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<ProjectView>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<ProjectRelation>,
+}
+
+impl ProjectConfiguration {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read project {}: {}", path.display(), e))?;
+        let mut project: Self = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse project {}: {}", path.display(), e))?;
+        project.path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+        let base = parent_dir(&project.path);
+        project.map_paths(|source| resolve_path(&base, source));
+        Ok(project)
+    }
+
+    /// Writes the project file, with the data sources relative to it so the project can be
+    /// shared together with them.
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let mut stored = self.clone();
+        let base = parent_dir(&self.path);
+        stored.map_paths(|source| relative_path(&base, source));
+        let content = serde_json::to_string_pretty(&stored).map_err(|e| e.to_string())?;
+        fs::write(&self.path, content)
+            .map_err(|e| format!("Failed to save project {}: {}", self.path.display(), e))
+    }
+
+    /// Takes the shown sheets and relations from the loaded data sources of the project. Those of
+    /// data sources that could not be loaded are kept. `current` holds the column configurations
+    /// of the sheet that is being edited, as its source and sheet index.
+    pub fn update_metadata(&mut self, data_sources: &[DataSource], current: Option<(usize, usize, &[ColumnConfiguration])>) {
+        let loaded = |path: &String| data_sources.iter().any(|ds| &ds.path == path);
+        let mut views = self.views.iter().filter(|v| !loaded(&v.data_source)).cloned().collect::<Vec<_>>();
+        let mut relations = self.relations.iter().filter(|r| !loaded(&r.data_source)).cloned().collect::<Vec<_>>();
+
+        for (source_idx, ds) in data_sources.iter().enumerate().filter(|(_, ds)| self.data_sources.contains(&ds.path)) {
+            if let Some(sheet) = ds.sheets.get(ds.selected_sheet_index) {
+                views.push(ProjectView { data_source: ds.path.clone(), sheet: sheet.name.clone() });
+            }
+            for (sheet_idx, sheet) in ds.sheets.iter().enumerate() {
+                let column_configs = match current {
+                    Some((source, sheet, configs)) if (source, sheet) == (source_idx, sheet_idx) => configs,
+                    _ => &sheet.column_configs,
+                };
+                relations.extend(column_configs.iter().filter_map(|column| Some(ProjectRelation {
+                    data_source: ds.path.clone(),
+                    sheet: sheet.name.clone(),
+                    column: column.name.clone(),
+                    related_source: column.related_source.clone()?,
+                })));
+            }
+        }
+
+        self.views = views;
+        self.relations = relations;
+    }
+
+    /// Shows the sheet of the project and restores relations that the data source lost, for
+    /// example because it was shared without its `.correlate` file.
+    pub fn apply_metadata(&self, ds: &mut DataSource) {
+        if let Some(view) = self.views.iter().find(|v| v.data_source == ds.path)
+            && let Some(index) = ds.sheets.iter().position(|s| s.name == view.sheet) {
+            ds.selected_sheet_index = index;
+        }
+        for relation in self.relations.iter().filter(|r| r.data_source == ds.path) {
+            if let Some(sheet) = ds.sheets.iter_mut().find(|s| s.name == relation.sheet)
+                && let Some(column) = sheet.column_configs.iter_mut().find(|c| c.name == relation.column)
+                && column.related_source.is_none() {
+                column.related_source = Some(relation.related_source.clone());
+            }
+        }
+    }
+
+    pub fn map_paths(&mut self, map: impl Fn(&str) -> String) {
+        for source in &mut self.data_sources {
+            *source = map(source);
+        }
+        for view in &mut self.views {
+            view.data_source = map(&view.data_source);
+        }
+        for relation in &mut self.relations {
+            relation.data_source = map(&relation.data_source);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("correlate-project-{}", std::process::id()));
+        let source = dir.join("data").join("orders.csv").to_string_lossy().into_owned();
+        let project = ProjectConfiguration {
+            path: dir.join("Sales.correlateproj"),
+            name: "Sales".to_string(),
+            data_sources: vec![source.clone()],
+            views: vec![ProjectView { data_source: source.clone(), sheet: "Orders".to_string() }],
+            relations: vec![ProjectRelation {
+                data_source: source.clone(),
+                sheet: "Orders".to_string(),
+                column: "Customer".to_string(),
+                related_source: "customers.csv > Customers".to_string(),
+            }],
+        };
+        project.save().unwrap();
+
+        let content = fs::read_to_string(&project.path).unwrap();
+        assert!(content.contains(r#""data/orders.csv""#));
+        assert_eq!(ProjectConfiguration::load(&project.path).unwrap(), project);
+
+        fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -1,6 +1,7 @@
 ﻿use egui::*;
 use crate::data::*;
 use crate::view::*;
+use crate::application_command::*;
 
 impl Project {
     pub fn ui(
@@ -14,7 +15,10 @@ impl Project {
     ) {
         let renaming_this_project = renaming_target_opt.map_or(false, |t| t == Rename::Project(project_idx));
 
-        let mut header = CollapsingHeader::new(format!("{} {}", egui_material_icons::icons::ICON_ASSIGNMENT, self.configuration.name))
+        // Changes that aren't saved to the project file yet are marked like in an editor.
+        let modified = if view_model.config.is_project_modified(&self.configuration) { " •" } else { "" };
+        let mut header = CollapsingHeader::new(format!("{} {}{}", egui_material_icons::icons::ICON_ASSIGNMENT, self.configuration.name, modified))
+            .id_salt(("project", project_idx))
             .default_open(true);
 
         if renaming_this_project {
@@ -32,6 +36,8 @@ impl Project {
                         newly_selected_index,
                         newly_selected_sheet_index,
                     );
+                } else {
+                    Self::ui_missing_data_source(ui, ds_path);
                 }
             }
         });
@@ -39,7 +45,7 @@ impl Project {
         if renaming_this_project {
             let mut rect = header_res.header_response.rect;
             rect.min.x += 20.0; // Offset for icon
-            Self::show_context_menu(header_res, project_idx, view_model);
+            Self::show_context_menu(header_res, project_idx, &self.configuration);
 
             ui.scope_builder(UiBuilder::new().max_rect(rect), |ui| {
                 Rename::ui_item_as_editable(
@@ -52,17 +58,69 @@ impl Project {
                 );
             });
         } else {
-            Self::show_context_menu(header_res, project_idx, view_model);
+            Self::show_context_menu(header_res, project_idx, &self.configuration);
         }
     }
 
-    pub fn show_context_menu(header_res: CollapsingResponse<()>, project_idx: usize, view_model: &mut RootViewModel)
+    /// A data source that could not be loaded, which can be pointed to where it is now.
+    fn ui_missing_data_source(ui: &mut Ui, path: &str) {
+        let file_name = std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string());
+        let problem = if std::path::Path::new(path).exists() { "could not be loaded" } else { "could not be found" };
+
+        let text = RichText::new(format!("{} {}", egui_material_icons::icons::ICON_WARNING, file_name)).color(ui.visuals().warn_fg_color);
+        let response = ui.add(Label::new(text).sense(Sense::click()))
+            .on_hover_text(format!("{} {}. Click to locate it.", path, problem));
+        let mut locate = response.clicked();
+        response.context_menu(|ui| {
+            if ui.button("Locate…").clicked() {
+                locate = true;
+                ui.close();
+            }
+        });
+
+        if locate {
+            let extension = std::path::Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or_default();
+            if let Some(located) = rfd::FileDialog::new()
+                .add_filter("Data source", &[extension])
+                .set_file_name(&file_name)
+                .pick_file()
+            {
+                enqueue_ui_command(ui, Box::new(LocateDataSource { missing: path.to_string(), path: located }));
+            }
+        }
+    }
+
+    pub fn show_context_menu(header_res: CollapsingResponse<()>, project_idx: usize, configuration: &ProjectConfiguration)
     {
         header_res.header_response.context_menu(|ui| {
             Rename::ui_item_context_menu(ui, Rename::Project(project_idx));
             ui.separator();
+            if ui.button("Save").on_hover_text(configuration.path.display().to_string()).clicked() {
+                enqueue_ui_command(ui, Box::new(SaveProject { project: project_idx, path: None }));
+                ui.close();
+            }
+            if ui.button("Save as…").clicked() {
+                if let Some(command) = SaveProject::pick(project_idx, configuration) {
+                    enqueue_ui_command(ui, Box::new(command));
+                }
+                ui.close();
+            }
+            if ui.button("Close project").clicked() {
+                UnsavedProjectsView::request_close(ui.ctx(), project_idx);
+                ui.close();
+            }
+            if ui.button("Export bundle…").on_hover_text("Archives the project with its data sources, to hand it to someone else").clicked() {
+                if let Some(command) = ExportProjectBundle::pick(project_idx, configuration) {
+                    enqueue_ui_command(ui, Box::new(command));
+                }
+                ui.close();
+            }
+            ui.separator();
             if let Some(path) = HierarchyPanel::ui_hierarchy_panel_context_menu(ui) {
-                view_model.handle_pending_file_add(path, project_idx);
+                enqueue_ui_command(ui, Box::new(AddExistingDataSource { path, project: project_idx }));
             }
         });
     }
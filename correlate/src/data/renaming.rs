@@ -2,7 +2,6 @@ use crate::data::*;
 use crate::view::*;
 use egui::*;
 use crate::egui_data_table::*;
-use crate::enqueue_ui_command;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Rename {
@@ -156,14 +155,13 @@ impl Rename
         match target {
             Rename::Project(index) => {
                 if ui.button("Remove").clicked() {
-
-                    enqueue_ui_command(ui, Box::new(TrashProject { project: index, ctx: ui.ctx().clone() }));
+                    TrashConfirmation::Project(index).request(ui.ctx());
                     ui.close();
                 }
             }
             Rename::DataSource(index) => {
                 if ui.button("Remove").clicked() {
-                    enqueue_ui_command(ui, Box::new(TrashDataSource { data_source: index, ctx: ui.ctx().clone() }));
+                    TrashConfirmation::DataSource(index).request(ui.ctx());
                     ui.close();
                 }
             }
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// How much room the rows of a sheet get. Rows still grow beyond their height to fit their
+/// content, e.g. wrapped text.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RowDensity {
+    Compact,
+    #[default]
+    Normal,
+    Tall,
+}
+
+impl RowDensity {
+    pub const ALL: [RowDensity; 3] = [RowDensity::Compact, RowDensity::Normal, RowDensity::Tall];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RowDensity::Compact => "Compact",
+            RowDensity::Normal => "Normal",
+            RowDensity::Tall => "Tall",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            RowDensity::Compact => egui_material_icons::icons::ICON_DENSITY_SMALL,
+            RowDensity::Normal => egui_material_icons::icons::ICON_DENSITY_MEDIUM,
+            RowDensity::Tall => egui_material_icons::icons::ICON_DENSITY_LARGE,
+        }
+    }
+
+    /// The minimum height of a row.
+    pub fn row_height(&self) -> f32 {
+        match self {
+            RowDensity::Compact => 16.0,
+            RowDensity::Normal => 20.0,
+            RowDensity::Tall => 32.0,
+        }
+    }
+
+    pub fn is_normal(&self) -> bool {
+        *self == RowDensity::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_density() {
+        assert_eq!(serde_json::to_string(&RowDensity::Tall).unwrap(), "\"tall\"");
+        assert_eq!(serde_json::from_str::<RowDensity>("\"compact\"").unwrap(), RowDensity::Compact);
+        assert!(RowDensity::ALL.windows(2).all(|w| w[0].row_height() < w[1].row_height()));
+    }
+}
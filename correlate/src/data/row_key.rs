@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::data::*;
+
+/// Separator between the parts of a composite key when it is stored as a single string.
+pub const KEY_SEPARATOR: char = '\u{1f}';
+
+/// Prefix of keys derived from row content, used for sheets without key columns.
+pub const CONTENT_KEY_PREFIX: char = '#';
+
+/// The identity of a row: the values of the key columns, in key order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RowKey(pub Vec<String>);
+
+impl RowKey {
+    /// Builds the key of a row from the given (ordered) key column indices.
+    /// Returns `None` if the sheet has no key columns.
+    pub fn from_row(key_columns: &[usize], row: &Row) -> Option<Self> {
+        if key_columns.is_empty() {
+            return None;
+        }
+        let parts = key_columns.iter()
+            .map(|&idx| row.cells.get(idx).map(|c| c.0.clone()).unwrap_or_default())
+            .collect();
+        Some(Self(parts))
+    }
+
+    /// Derives a fallback identity for every row of a sheet without key columns, based on
+    /// a hash of the physical cell values. Identical rows are told apart by their occurrence.
+    /// These keys only survive as long as the physical content of a row is unchanged.
+    pub fn from_content<'a, I>(rows: I) -> Vec<Self>
+    where
+        I: IntoIterator,
+        I::Item: IntoIterator<Item = &'a str>,
+    {
+        let mut occurrences: HashMap<u64, usize> = HashMap::new();
+        rows.into_iter()
+            .map(|values| {
+                let hash = content_hash(values);
+                let occurrence = occurrences.entry(hash).or_default();
+                let key = match *occurrence {
+                    0 => format!("{}{:016x}", CONTENT_KEY_PREFIX, hash),
+                    n => format!("{}{:016x}-{}", CONTENT_KEY_PREFIX, hash, n),
+                };
+                *occurrence += 1;
+                Self(vec![key])
+            })
+            .collect()
+    }
+
+    /// Parses the stored form of a key as written by [`RowKey::to_string`].
+    pub fn parse(stored: &str) -> Self {
+        Self(stored.split(KEY_SEPARATOR).map(|s| s.to_string()).collect())
+    }
+
+    /// A key is empty when none of its parts carries a value.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|p| p.is_empty())
+    }
+
+    /// Human-readable form, used in messages and tooltips.
+    pub fn display(&self) -> String {
+        self.0.join(" | ")
+    }
+}
+
+/// FNV-1a, chosen because it is stable between runs and platforms, unlike the std hasher.
+fn content_hash<'a>(values: impl IntoIterator<Item = &'a str>) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut hash = OFFSET;
+    for (i, value) in values.into_iter().enumerate() {
+        if i > 0 {
+            hash = (hash ^ KEY_SEPARATOR as u64).wrapping_mul(PRIME);
+        }
+        for byte in value.bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
+
+impl fmt::Display for RowKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, part) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{}", KEY_SEPARATOR)?;
+            }
+            write!(f, "{}", part)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(values: &[&str]) -> Row {
+        Row { cells: values.iter().map(|v| CellValue::from(*v)).collect() }
+    }
+
+    #[test]
+    fn test_single_key_is_stored_verbatim() {
+        let key = RowKey::from_row(&[1], &row(&["a", "b", "c"])).unwrap();
+        assert_eq!(key.to_string(), "b");
+        assert_eq!(RowKey::parse("b"), key);
+    }
+
+    #[test]
+    fn test_composite_key_roundtrip() {
+        let key = RowKey::from_row(&[2, 0], &row(&["a", "b", "c"])).unwrap();
+        assert_eq!(key.0, vec!["c".to_string(), "a".to_string()]);
+        assert_eq!(RowKey::parse(&key.to_string()), key);
+        assert_eq!(key.display(), "c | a");
+    }
+
+    #[test]
+    fn test_empty_key() {
+        assert!(RowKey::from_row(&[], &row(&["a"])).is_none());
+        assert!(RowKey::from_row(&[0, 1], &row(&["", ""])).unwrap().is_empty());
+        assert!(!RowKey::from_row(&[0, 1], &row(&["", "x"])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_content_keys() {
+        let rows = [vec!["a", "b"], vec!["c", "d"], vec!["a", "b"]];
+        let keys = RowKey::from_content(rows.iter().map(|r| r.iter().copied()));
+        assert_eq!(keys.len(), 3);
+        assert_ne!(keys[0], keys[1]);
+        assert_ne!(keys[0], keys[2]);
+        assert_eq!(keys[2].0[0], format!("{}-1", keys[0].0[0]));
+
+        // Stable across calls, and cell boundaries matter.
+        let again = RowKey::from_content([["a", "b"]]);
+        assert_eq!(again[0], keys[0]);
+        let shifted = RowKey::from_content([["ab", ""]]);
+        assert_ne!(shifted[0], keys[0]);
+    }
+}
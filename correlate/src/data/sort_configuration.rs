@@ -1,7 +1,7 @@
 ﻿use serde::{Deserialize, Serialize};
 use crate::data::*;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SortConfiguration {
     pub column_name: String,
     pub is_ascending: bool,
@@ -0,0 +1,212 @@
+use std::fs;
+use serde::{Deserialize, Serialize};
+use crate::data::*;
+
+/// How many days trashed items are kept, unless configured otherwise.
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Something that was removed from the workspace and can be restored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind")]
+pub enum TrashedItem {
+    /// A project and its position among the projects. Its project file stays where it is.
+    Project {
+        index: usize,
+        project: ProjectConfiguration,
+        #[serde(default)]
+        path: std::path::PathBuf,
+    },
+    /// A data source and the projects it belonged to. Its `.correlate` file and journal stay
+    /// next to it until the item is deleted from the trash.
+    DataSource { path: String, name: Option<String>, projects: Vec<usize> },
+    /// A virtual column of a sheet, with its conditional formats and its values keyed by row.
+    Column {
+        source: String,
+        sheet: usize,
+        column: Box<ColumnConfiguration>,
+        conditional_formats: Vec<ConditionalFormat>,
+        cell_values: Vec<CellValueConfiguration>,
+    },
+}
+
+impl TrashedItem {
+    pub fn description(&self) -> String {
+        match self {
+            TrashedItem::Project { project, .. } => format!("Project {}", project.name),
+            TrashedItem::DataSource { path, name, .. } => format!("Data source {}", name.as_ref().unwrap_or(path)),
+            TrashedItem::Column { source, column, .. } => {
+                let file_name = std::path::Path::new(source).file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                format!("Column {} of {}", column.display_name.as_ref().unwrap_or(&column.name), file_name)
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    /// Seconds since the Unix epoch.
+    pub trashed_at: u64,
+    pub item: TrashedItem,
+}
+
+impl TrashEntry {
+    /// Whole days until the entry is deleted, rounded up.
+    pub fn days_left(&self, now: u64, retention_days: u32) -> u64 {
+        let expires_at = self.trashed_at + retention_days as u64 * SECONDS_PER_DAY;
+        expires_at.saturating_sub(now).div_ceil(SECONDS_PER_DAY)
+    }
+
+    /// Deletes what is left on disk of the entry: the `.correlate` file and journal of a data
+    /// source that is no longer used by an open, recent or trashed project. The source file
+    /// itself is never deleted.
+    pub fn delete(&self, config: &Configuration) -> Result<(), String> {
+        let TrashedItem::DataSource { path, .. } = &self.item else {
+            return Ok(());
+        };
+        if Self::in_use(path, config) {
+            return Ok(());
+        }
+
+        let companion = DataSourceConfiguration::calculate_path(path);
+        if companion.exists() {
+            fs::remove_file(&companion).map_err(|e| format!("Failed to delete {:?}: {}", companion, e))?;
+        }
+        Journal::remove(path)
+    }
+
+    /// Whether a project that is open, closed or trashed still uses the data source.
+    fn in_use(path: &String, config: &Configuration) -> bool {
+        let open = config.projects.iter().flatten().any(|p| p.data_sources.contains(path));
+        let trashed = config.trash.entries().iter().any(|e| match &e.item {
+            TrashedItem::DataSource { path: p, .. } => p == path,
+            TrashedItem::Project { project, .. } => project.data_sources.contains(path),
+            TrashedItem::Column { .. } => false,
+        });
+        // A closed project that can't be read may still use it, so it is kept then as well.
+        let recent = config.recent_projects().iter().filter(|r| !r.open).any(|r| {
+            ProjectConfiguration::load(&r.path).ok().is_none_or(|p| p.data_sources.contains(path))
+        });
+        open || trashed || recent
+    }
+}
+
+/// The trashed items, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct Trash(Vec<TrashEntry>);
+
+impl Trash {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn entries(&self) -> &[TrashEntry] {
+        &self.0
+    }
+
+    pub fn push(&mut self, item: TrashedItem) {
+        self.0.push(TrashEntry { trashed_at: unix_now(), item });
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<TrashEntry> {
+        (index < self.0.len()).then(|| self.0.remove(index))
+    }
+
+    /// Removes the entry, if it is still in the trash.
+    pub fn remove_entry(&mut self, entry: &TrashEntry) {
+        self.0.retain(|e| e != entry);
+    }
+
+    /// Puts an entry back in the trash, in the order it was trashed in.
+    pub fn insert_entry(&mut self, entry: TrashEntry) {
+        if !self.0.contains(&entry) {
+            let index = self.0.partition_point(|e| e.trashed_at <= entry.trashed_at);
+            self.0.insert(index, entry);
+        }
+    }
+
+    pub fn take_all(&mut self) -> Vec<TrashEntry> {
+        std::mem::take(&mut self.0)
+    }
+
+    /// Removes the entries that were kept for longer than the retention period.
+    pub fn take_expired(&mut self, now: u64, retention_days: u32) -> Vec<TrashEntry> {
+        let (expired, kept) = self.take_all().into_iter()
+            .partition(|entry| entry.days_left(now, retention_days) == 0);
+        self.0 = kept;
+        expired
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str, trashed_at: u64) -> TrashEntry {
+        let project = ProjectConfiguration { name: name.to_string(), ..Default::default() };
+        TrashEntry { trashed_at, item: TrashedItem::Project { index: 0, project, path: Default::default() } }
+    }
+
+    #[test]
+    fn test_expired_entries_are_taken() {
+        let now = 100 * SECONDS_PER_DAY;
+        let mut trash = Trash(vec![project("Old", now - 30 * SECONDS_PER_DAY), project("New", now - SECONDS_PER_DAY / 2)]);
+        assert_eq!(trash.entries()[0].days_left(now, 30), 0);
+        assert_eq!(trash.entries()[1].days_left(now, 30), 30);
+
+        let expired = trash.take_expired(now, 30);
+        assert_eq!(expired.iter().map(|e| e.item.description()).collect::<Vec<_>>(), vec!["Project Old"]);
+        assert_eq!(trash.entries().len(), 1);
+        assert!(trash.take_expired(now, 1).is_empty());
+        assert_eq!(trash.take_expired(now, 0).len(), 1);
+        assert!(trash.is_empty());
+    }
+
+    #[test]
+    fn test_trash_is_stored_by_kind() {
+        let mut trash = Trash::default();
+        trash.push(TrashedItem::DataSource { path: "people.csv".to_string(), name: None, projects: vec![1] });
+        let json = serde_json::to_string(&trash).unwrap();
+        assert!(json.contains(r#""kind":"DataSource""#));
+        assert_eq!(serde_json::from_str::<Trash>(&json).unwrap(), trash);
+    }
+
+    #[test]
+    fn test_sources_of_closed_projects_are_kept() {
+        let dir = std::env::temp_dir().join(format!("correlate-trash-{}", std::process::id()));
+        let source = dir.join("people.csv").to_string_lossy().into_owned();
+        let companion = DataSourceConfiguration::calculate_path(&source);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&companion, "{}").unwrap();
+
+        let project = ProjectConfiguration {
+            path: dir.join("Closed.correlateproj"),
+            name: "Closed".to_string(),
+            data_sources: vec![source.clone()],
+            ..Default::default()
+        };
+        project.save().unwrap();
+        let mut config = Configuration::new(dir.join("config.json"));
+        config.remember_project(&project.path);
+
+        let entry = TrashEntry { trashed_at: 0, item: TrashedItem::DataSource { path: source, name: None, projects: Vec::new() } };
+        entry.delete(&config).unwrap();
+        assert!(companion.exists());
+
+        config.recent_projects.clear();
+        entry.delete(&config).unwrap();
+        assert!(!companion.exists());
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -4,8 +4,10 @@ use egui::scroll_area::ScrollBarVisibility;
 
 pub(crate) mod state;
 mod tsv;
+mod html;
+pub(crate) mod system_clipboard;
 pub(crate) mod ui_state;
-mod clipboard;
+pub(crate) mod clipboard;
 pub(crate) mod command;
 mod persist_data;
 mod cursor_state;
@@ -37,6 +39,10 @@ pub struct Style {
     /// If specify this as [`None`], the heterogeneous row height will be used.
     pub table_row_height: Option<f32>,
 
+    /// Minimum height of heterogeneous rows. Rows grow beyond it to fit their content, e.g.
+    /// wrapped text.
+    pub min_row_height: f32,
+
     /// When enabled, single click on a cell will start editing mode. Default is `false` where
     /// double action(click 1: select, click 2: edit) is required.
     pub single_click_edit_mode: bool,
@@ -55,6 +61,17 @@ pub struct Style {
     pub scroll_bar_visibility: ScrollBarVisibility,
 }
 
+impl Style {
+    /// Effective maximum number of undo history.
+    pub(crate) fn undo_capacity(&self) -> usize {
+        if self.max_undo_history == 0 {
+            100
+        } else {
+            self.max_undo_history
+        }
+    }
+}
+
 /* ------------------------------------------ Rendering ----------------------------------------- */
 
 /* ------------------------------------------- Translations ------------------------------------- */
@@ -1,4 +1,5 @@
 ﻿use crate::egui_data_table::*;
+use crate::egui_data_table::draw::{html, tsv};
 
 pub struct Clipboard<R> {
     pub slab: Box<[R]>,
@@ -15,4 +16,70 @@ impl<R> Clipboard<R> {
                 a_row.0.cmp(&b_row.0).then(a_col.0.cmp(&b_col.0))
             })
     }
-}
\ No newline at end of file
+}
+
+/// Copied cells as plain text (TSV) and as an HTML table, which keeps its structure when pasted
+/// into other spreadsheets.
+pub struct ClipboardDump {
+    pub text: String,
+    pub html: String,
+}
+
+/// Reads the cells pasted from the system clipboard, row by row.
+///
+/// Spreadsheets put an HTML table next to the text, which keeps cells with tabs and line breaks
+/// intact, so that table is preferred if the text starts with its first cell. Otherwise the text
+/// is read as CSV if it has several rows that are consistently separated by commas and no tabs,
+/// unless every line is a number with a decimal comma, and as TSV if not.
+pub fn parse_pasted(text: &str, html: Option<&str>) -> Vec<Vec<String>> {
+    if let Some(rows) = html.and_then(html::parse_table) {
+        let first_line = rows[0].first().and_then(|cell| cell.lines().next()).unwrap_or("");
+        if text.trim_start().trim_start_matches('"').starts_with(first_line.trim()) {
+            return rows;
+        }
+    }
+
+    let decimal_commas = text.lines().filter(|line| !line.trim().is_empty()).all(is_decimal_comma);
+    if !text.contains('\t') && text.contains(',') && !decimal_commas {
+        let rows = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes())
+            .records()
+            .map(|record| record.map(|r| r.iter().map(str::to_string).collect::<Vec<_>>()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_default();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.len() > 1 && width > 1 && rows.iter().all(|row| row.len() == width) {
+            return rows;
+        }
+    }
+
+    tsv::ParsedTsv::parse(text)
+        .iter_rows()
+        .map(|(_, cells)| cells.map(|(_, cell)| cell.to_string()).collect())
+        .collect()
+}
+
+/// Whether a value is a number with a decimal comma, like "3,50" or "-1.234,5".
+fn is_decimal_comma(value: &str) -> bool {
+    let value = value.trim().trim_start_matches(['-', '+']);
+    let Some((whole, fraction)) = value.split_once(',') else {
+        return false;
+    };
+    !whole.is_empty() && whole.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && !fraction.is_empty() && fraction.chars().all(|c| c.is_ascii_digit())
+}
+
+#[test]
+fn pasted_formats() {
+    let html = "<table><tr><td>a<br>b</td><td>c</td></tr></table>";
+    assert_eq!(parse_pasted("\"a\nb\"\tc", Some(html)), vec![vec!["a\nb", "c"]]);
+    assert_eq!(parse_pasted("other text", Some(html)), vec![vec!["other text"]]);
+
+    assert_eq!(parse_pasted("x,\"y, z\"\n1,2", None), vec![vec!["x", "y, z"], vec!["1", "2"]]);
+    assert_eq!(parse_pasted("Smith, John", None), vec![vec!["Smith, John"]]);
+    assert_eq!(parse_pasted("3,50\n4,25", None), vec![vec!["3,50"], vec!["4,25"]]);
+    assert_eq!(parse_pasted("3,50,a\n4,25,b", None), vec![vec!["3", "50", "a"], vec!["4", "25", "b"]]);
+    assert_eq!(parse_pasted("a\tb\nc\td", None), vec![vec!["a", "b"], vec!["c", "d"]]);
+}
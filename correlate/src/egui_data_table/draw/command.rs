@@ -1,4 +1,5 @@
-﻿use crate::egui_data_table::{CellWriteContext, ColumnIdx, IsAscending, RowIdx, RowSlabIndex, VisColumnPos, VisSelection};
+﻿use crate::egui_data_table::draw::clipboard::ClipboardDump;
+use crate::egui_data_table::{CellWriteContext, ColumnIdx, ColumnSnapshot, IsAscending, RowIdx, RowSlabIndex, VisColumnPos, VisSelection};
 
 /// NOTE: `Cc` prefix stands for cache command which won't be stored in the undo/redo queue, since they
 /// are not called from the `cmd_apply` method.
@@ -35,11 +36,29 @@ pub enum Command<R> {
     RenameCommitted(crate::egui_data_table::viewer::RenameTarget, String),
     RequestSave,
     RemoveRow(Vec<RowIdx>),
+    /// Reorders the rows: the row at position `i` becomes the one at index `order[i]`, after
+    /// which the `select` rows are selected.
+    ReorderRows {
+        order: Box<[RowIdx]>,
+        select: Vec<RowIdx>,
+    },
     RemoveColumn(usize),
+    ColumnsChanged(ColumnSnapshot),
+    RestoreColumns(ColumnsState<R>),
 
     CcEditStart(RowIdx, VisColumnPos, Box<R>),
     CcCancelEdit,
     CcCommitEdit,
 
-    CcUpdateSystemClipboard(String),
-}
\ No newline at end of file
+    CcUpdateSystemClipboard(ClipboardDump),
+}
+
+/// Structural state of the columns, recorded before and after a column change.
+pub struct ColumnsState<R> {
+    pub columns: ColumnSnapshot,
+    /// Column changes that move cell values also carry the rows.
+    pub rows: Option<Box<[R]>>,
+    pub num_columns: usize,
+    pub vis_cols: Vec<ColumnIdx>,
+    pub sort: Vec<(ColumnIdx, IsAscending)>,
+}
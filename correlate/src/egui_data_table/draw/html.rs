@@ -0,0 +1,177 @@
+//! A short implementation for reading and writing the HTML tables that spreadsheets put on the
+//! clipboard.
+
+pub fn write_table(rows: &[Vec<String>]) -> String {
+    let mut buf = String::from("<table>");
+
+    for row in rows {
+        buf.push_str("<tr>");
+        for cell in row {
+            buf.push_str("<td>");
+            write_content(&mut buf, cell);
+            buf.push_str("</td>");
+        }
+        buf.push_str("</tr>");
+    }
+
+    buf.push_str("</table>");
+    buf
+}
+
+fn write_content(buf: &mut String, item: &str) {
+    for char in item.chars() {
+        match char {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '"' => buf.push_str("&quot;"),
+            '\n' => buf.push_str("<br>"),
+            '\r' => {}
+            _ => buf.push(char),
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                             READER                                             */
+/* ============================================================================================== */
+
+/// Reads the cells of the first table in an HTML fragment, row by row. Line breaks within a cell
+/// are kept; any other markup is dropped.
+pub fn parse_table(html: &str) -> Option<Vec<Vec<String>>> {
+    let start = html.to_ascii_lowercase().find("<table")?;
+    let mut rest = &html[start..];
+
+    let mut rows = Vec::new();
+    let mut row: Option<Vec<String>> = None;
+    let mut cell: Option<String> = None;
+
+    let finish_cell = |row: &mut Option<Vec<String>>, cell: &mut Option<String>| {
+        if let Some(cell) = cell.take() {
+            let lines = cell.split('\n').map(str::trim).collect::<Vec<_>>();
+            row.get_or_insert_with(Vec::new).push(lines.join("\n"));
+        }
+    };
+
+    while let Some(tag_start) = rest.find('<') {
+        if let Some(cell) = &mut cell {
+            push_text(cell, &rest[..tag_start]);
+        }
+        rest = &rest[tag_start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let tag_end = rest.find('>')?;
+        let tag = rest[1..tag_end].to_ascii_lowercase();
+        rest = &rest[tag_end + 1..];
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag.as_str()),
+        };
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+
+        match (closing, name) {
+            (_, "tr") => {
+                finish_cell(&mut row, &mut cell);
+                rows.extend(row.take());
+            }
+            (false, "td" | "th") => {
+                finish_cell(&mut row, &mut cell);
+                cell = Some(String::new());
+            }
+            (true, "td" | "th") => finish_cell(&mut row, &mut cell),
+            (false, "br") | (true, "p" | "div") => {
+                if let Some(cell) = &mut cell {
+                    cell.push('\n');
+                }
+            }
+            (true, "table") => break,
+            _ => {}
+        }
+    }
+
+    finish_cell(&mut row, &mut cell);
+    rows.extend(row);
+
+    // Trailing line breaks of paragraphs are no part of the value.
+    for cell in rows.iter_mut().flatten() {
+        cell.truncate(cell.trim_end_matches('\n').len());
+    }
+
+    (!rows.is_empty()).then_some(rows)
+}
+
+/// Appends text with its whitespace collapsed and its character references decoded.
+fn push_text(buf: &mut String, text: &str) {
+    let mut rest = text;
+
+    while let Some(char) = rest.chars().next() {
+        if char.is_whitespace() {
+            if !buf.is_empty() && !buf.ends_with([' ', '\n']) {
+                buf.push(' ');
+            }
+            rest = &rest[char.len_utf8()..];
+        } else if let Some((decoded, len)) = char_reference(rest) {
+            buf.push(decoded);
+            rest = &rest[len..];
+        } else {
+            buf.push(char);
+            rest = &rest[char.len_utf8()..];
+        }
+    }
+}
+
+/// Decodes the character reference at the start of the text, e.g. `&amp;` or `&#160;`, and
+/// returns it with its length.
+fn char_reference(text: &str) -> Option<(char, usize)> {
+    let end = text.strip_prefix('&')?.find(';')? + 1;
+    let name = &text[1..end];
+
+    let decoded = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        _ => {
+            let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => name.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+
+    Some((decoded, end + 1))
+}
+
+#[test]
+fn html_table_round_trip() {
+    let rows = vec![
+        vec!["Name".to_string(), "Notes".to_string()],
+        vec!["Tom & Jerry".to_string(), "first line\nsecond <line>".to_string()],
+    ];
+
+    let html = write_table(&rows);
+    assert_eq!(parse_table(&html), Some(rows));
+}
+
+#[test]
+fn html_table_parsing() {
+    const HTML: &str = r#"<html><body><!--StartFragment--><table border=1>
+        <tr><th>Id</th><th class="x">Price</th></tr>
+        <tr><td>1</td><td>&euro;&#32;3,50</td></tr>
+        <tr><td><p>A</p><p>B</p></td><td>  spaced
+            out </td></tr>
+    </table><!--EndFragment--></body></html>"#;
+
+    let rows = parse_table(HTML).unwrap();
+    assert_eq!(rows[0], vec!["Id", "Price"]);
+    assert_eq!(rows[1], vec!["1", "&euro; 3,50"]);
+    assert_eq!(rows[2], vec!["A\nB", "spaced out"]);
+    assert!(parse_table("<p>No table</p>").is_none());
+}
@@ -0,0 +1,45 @@
+//! The HTML flavor of the system clipboard, which egui doesn't provide. It is only available
+//! natively; on the web, only the plain text that egui copies and pastes is used.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::cell::RefCell;
+
+    thread_local! {
+        /// The clipboard is kept open, as some platforms only serve copied content while it is.
+        static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = const { RefCell::new(None) };
+    }
+
+    fn with_clipboard<T>(f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Option<T> {
+        CLIPBOARD.with_borrow_mut(|clipboard| {
+            if clipboard.is_none() {
+                *clipboard = arboard::Clipboard::new()
+                    .inspect_err(|e| log::warn!("Failed to open the system clipboard: {}", e))
+                    .ok();
+            }
+            f(clipboard.as_mut()?).ok()
+        })
+    }
+
+    pub fn html() -> Option<String> {
+        with_clipboard(|clipboard| clipboard.get().html())
+    }
+
+    /// Puts an HTML table on the clipboard, with the given text as its plain text alternative.
+    pub fn set_html(html: &str, text: &str) -> bool {
+        with_clipboard(|clipboard| clipboard.set().html(html, Some(text))).is_some()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
+
+#[cfg(target_arch = "wasm32")]
+pub fn html() -> Option<String> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn set_html(_html: &str, _text: &str) -> bool {
+    false
+}
@@ -6,12 +6,12 @@ use egui::Modifiers;
 use tap::{Pipe, Tap};
 use itertools::Itertools;
 use crate::egui_data_table::*;
-use crate::egui_data_table::draw::clipboard::Clipboard;
-use crate::egui_data_table::draw::command::Command;
+use crate::egui_data_table::draw::clipboard::{parse_pasted, Clipboard, ClipboardDump};
+use crate::egui_data_table::draw::command::{ColumnsState, Command};
 use crate::egui_data_table::draw::cursor_state::CursorState;
 use crate::egui_data_table::draw::persist_data::PersistData;
 use crate::egui_data_table::draw::state::*;
-use crate::egui_data_table::draw::tsv;
+use crate::egui_data_table::draw::{html, tsv};
 
 /// TODO: Serialization?
 pub(crate) struct UiState<R> {
@@ -61,6 +61,9 @@ pub(crate) struct UiState<R> {
     /// Cached row id to the visual row position table for a quick lookup.
     cc_row_id_to_vis: HashMap<RowIdx, VisRowPos>,
 
+    /// Number of pinned rows, which come first in `cc_rows`.
+    cc_num_pinned_rows: usize,
+
     /// Spreadsheet is modified during the last validation.
     cc_dirty: bool,
 
@@ -96,6 +99,40 @@ pub(crate) struct UiState<R> {
 
     /// How many rows are rendered at once recently?
     pub cci_page_row_count: usize,
+
+    /// Cell the fill handle of the selection is dragged to.
+    cci_fill_target: Option<VisLinearIdx>,
+
+    /// Position the selected rows are dragged to, as the row they are moved in front of.
+    cci_row_drop: Option<VisRowPos>,
+
+    /// Number of visible columns that stay in place when scrolling horizontally.
+    pub cci_frozen_cols: usize,
+
+    /// Unpinned rows and unfrozen columns that are scrolled out of view, while the table
+    /// scrolls by cell.
+    cci_cell_scroll: (usize, usize),
+
+    /// Scroll input that did not add up to a whole row or column yet.
+    cci_scroll_remainder: egui::Vec2,
+
+    /// How many unfrozen columns fit in the table recently?
+    pub cci_page_col_count: usize,
+
+    /// Widths of the row header and the columns when they were last shown, so they keep
+    /// their width while the table scrolls by cell.
+    cci_row_header_width: Option<f32>,
+    cci_col_widths: HashMap<ColumnIdx, f32>,
+
+    /// Columns that were shown during the last frame.
+    pub cci_shown_cols: Vec<ColumnIdx>,
+
+    /// Actions requested from outside the table, applied when it is shown next.
+    pub queued_actions: Vec<UiAction>,
+
+    /// Undo capacity of the style the table was last shown with, for commands pushed from
+    /// outside the table.
+    pub cci_undo_capacity: usize,
 }
 
 impl<R> Default for UiState<R> {
@@ -119,6 +156,18 @@ impl<R> Default for UiState<R> {
             cc_desired_selection: None,
             cci_want_move_scroll: false,
             cci_page_row_count: 0,
+            cci_fill_target: None,
+            cci_row_drop: None,
+            cc_num_pinned_rows: 0,
+            cci_frozen_cols: 0,
+            cci_cell_scroll: (0, 0),
+            cci_scroll_remainder: egui::Vec2::ZERO,
+            cci_page_col_count: 0,
+            cci_row_header_width: None,
+            cci_col_widths: default(),
+            cci_shown_cols: Vec::new(),
+            queued_actions: Vec::new(),
+            cci_undo_capacity: Style::default().undo_capacity(),
             p: default(),
             #[cfg(feature = "persistency")]
             is_p_loaded: false,
@@ -247,6 +296,14 @@ impl<R> UiState<R> {
             });
         }
 
+        // Pinned rows come first, in their sorted order.
+        let (pinned, unpinned): (Vec<_>, Vec<_>) = self.cc_rows.drain(..)
+            .partition(|row| vwr.is_pinned_row(&rows[row.0]));
+        self.cc_num_pinned_rows = pinned.len();
+        self.cc_rows.extend(pinned);
+        self.cc_rows.extend(unpinned);
+        self.set_cell_scroll(self.cci_cell_scroll.0, self.cci_cell_scroll.1);
+
         // Just refill with a neat default height.
         self.cc_row_heights.resize(self.cc_rows.len(), 20.0);
 
@@ -296,6 +353,7 @@ impl<R> UiState<R> {
         &mut self,
         vwr: &mut V,
         contents: &str,
+        html: Option<&str>,
     ) -> bool {
         /*
             NOTE: System clipboard implementation
@@ -321,9 +379,15 @@ impl<R> UiState<R> {
                   as explicit empty data; in this case, empty data wrapped with double
                   quotes("").
 
+            The copied content is also put on the clipboard as an HTML table, so that its
+            structure is kept when pasting into other spreadsheets.
+
             # Decoding
 
-            - Every format is regarded as TSV. (only \t, \n matters)
+            - An HTML table on the clipboard is preferred, then CSV and TSV text. See
+              `parse_pasted`.
+            - If the first row holds the header names of columns, the content is pasted into
+              those columns, regardless of the selection.
             - For TSV data with same column count with this table
                 - Parse as full-scale table, then put into clipboard as-is.
             - Column count is less than current table
@@ -333,12 +397,6 @@ impl<R> UiState<R> {
             - If column count is larger than this, it is invalid data; we just skip parsing
         */
 
-        let Some(mut codec) = vwr.try_create_codec(false) else {
-            // Even when there is system clipboard content, we're going to ignore it and use
-            // internal clipboard if there's no way to parse it.
-            return false;
-        };
-
         if let CursorState::Select(selections) = &self.cc_cursor {
             let Some(first) = selections.first().map(|x| x.0) else {
                 // No selection present. Do nothing
@@ -361,13 +419,37 @@ impl<R> UiState<R> {
             0
         };
 
-        let view = tsv::ParsedTsv::parse(contents);
-        let table_width = view.calc_table_width();
+        let mut rows = parse_pasted(contents, html);
+        let columns = match self.find_header_columns(vwr, &rows) {
+            Some(columns) => {
+                rows.remove(0);
+                columns
+            }
+            None => {
+                let table_width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+                if table_width > self.p.vis_cols.len() {
+                    // If the copied data has more columns than the current table, we'll just ignore it.
+                    return false;
+                }
+
+                let mut columns = Vec::with_capacity(table_width);
+                for column in 0..table_width {
+                    let Some(&col_idx) = self.p.vis_cols.get(column + selection_offset) else {
+                        // If the column is out of range, we'll just ignore it.
+                        return false;
+                    };
+                    columns.push(Some(col_idx));
+                }
+                columns
+            }
+        };
 
-        if table_width > self.p.vis_cols.len() {
-            // If the copied data has more columns than the current table, we'll just ignore it.
+        let Some(mut codec) = vwr.try_create_codec(false) else {
+            // Even when there is system clipboard content, we're going to ignore it and use
+            // internal clipboard if there's no way to parse it.
             return false;
-        }
+        };
 
         // If any cell is failed to be parsed, we'll just give up all the parsing then use internal
         // clipboard instead.
@@ -375,26 +457,24 @@ impl<R> UiState<R> {
         let mut slab = Vec::new();
         let mut pastes = Vec::new();
 
-        for (row_offset, row_data) in view.iter_rows() {
+        for (row_offset, row_data) in rows.iter().enumerate() {
             let slab_id = slab.len();
             slab.push(codec.create_empty_decoded_row());
 
             // The restoration point of the pastes stack.
             let pastes_restore = pastes.len();
 
-            for (column, data) in row_data {
-                let col_idx = column + selection_offset;
-
-                if col_idx > self.p.vis_cols.len() {
-                    // If the column is out of range, we'll just ignore it.
-                    return false;
-                }
+            for (data, col_idx) in row_data.iter().zip(&columns) {
+                let Some(col_idx) = *col_idx else {
+                    // The header of this column didn't match a column of the table.
+                    continue;
+                };
 
-                match codec.decode_column(data, col_idx, &mut slab[slab_id]) {
+                match codec.decode_column(data, col_idx.0, &mut slab[slab_id]) {
                     Ok(_) => {
                         pastes.push((
                             VisRowOffset(row_offset),
-                            ColumnIdx(col_idx),
+                            col_idx,
                             RowSlabIndex(slab_id),
                         ));
                     }
@@ -422,10 +502,36 @@ impl<R> UiState<R> {
         true
     }
 
+    /// The shown columns that the pasted columns belong to, if the first pasted row holds their
+    /// header names. Columns without a header are skipped.
+    fn find_header_columns<V: RowViewer<R>>(&self, vwr: &mut V, rows: &[Vec<String>]) -> Option<Vec<Option<ColumnIdx>>> {
+        // A single row is the content itself, even if it matches the headers.
+        let [header, _, ..] = rows else {
+            return None;
+        };
+
+        let mut columns = Vec::with_capacity(header.len());
+        for name in header {
+            let name = name.trim();
+            if name.is_empty() {
+                columns.push(None);
+                continue;
+            }
+
+            let column = ColumnIdx(vwr.column_for_header(name)?);
+            if !self.p.vis_cols.contains(&column) || columns.contains(&Some(column)) {
+                return None;
+            }
+            columns.push(Some(column));
+        }
+
+        columns.iter().any(Option::is_some).then_some(columns)
+    }
+
     fn try_dump_clipboard_content<V: RowViewer<R>>(
         clipboard: &Clipboard<R>,
         vwr: &mut V,
-    ) -> Option<String> {
+    ) -> Option<ClipboardDump> {
         // clipboard MUST be sorted before dumping; XXX: add assertion?
         #[allow(unused_mut)]
         let mut codec = vwr.try_create_codec(true)?;
@@ -449,6 +555,9 @@ impl<R> UiState<R> {
         let mut buf_tmp = String::new();
         let mut row_cursor = 0;
 
+        // The same cells as a rectangular table, for the HTML flavor.
+        let mut table = vec![vec![String::new(); width - column_offset]; height];
+
         for (row, columns, ..) in &clipboard.pastes.iter().chunk_by(|(row, ..)| *row) {
             while row_cursor < row.0 {
                 tsv::write_newline(&mut buf_out);
@@ -467,11 +576,14 @@ impl<R> UiState<R> {
                 codec.encode_column(data, column.0, &mut buf_tmp);
 
                 tsv::write_content(&mut buf_out, &buf_tmp);
-                buf_tmp.clear();
+                table[row.0][column.0 - column_offset] = take(&mut buf_tmp);
             }
         }
 
-        Some(buf_out)
+        Some(ClipboardDump {
+            text: buf_out,
+            html: html::write_table(&table),
+        })
     }
 
     fn handle_desired_selection(&mut self) -> bool {
@@ -599,6 +711,281 @@ impl<R> UiState<R> {
         self.cci_selection.is_some()
     }
 
+    /// The selection that can be extended with the fill handle: a single rectangle, while no
+    /// cell is being edited.
+    pub fn fill_source(&self) -> Option<VisSelection> {
+        match &self.cc_cursor {
+            CursorState::Select(sel) if sel.len() == 1 => Some(sel[0]),
+            _ => None,
+        }
+    }
+
+    pub fn is_filling(&self) -> bool {
+        self.cci_fill_target.is_some()
+    }
+
+    pub fn cci_fill_update(&mut self, current: VisLinearIdx) {
+        self.cci_fill_target = Some(current);
+    }
+
+    /// The cells that dragging the fill handle adds to the selection, and the direction in
+    /// which they are filled. The selection grows along the axis it is dragged furthest on.
+    pub fn fill_area(&self) -> Option<(VisSelection, MoveDirection)> {
+        let n = self.p.vis_cols.len();
+        let source = self.fill_source()?;
+        let (target_r, target_c) = self.cci_fill_target?.row_col(n);
+        let (top, left) = source.0.row_col(n);
+        let (bottom, right) = source.1.row_col(n);
+
+        let down = target_r.0.saturating_sub(bottom.0);
+        let up = top.0.saturating_sub(target_r.0);
+        let rightwards = target_c.0.saturating_sub(right.0);
+        let leftwards = left.0.saturating_sub(target_c.0);
+
+        let ((r0, c0), (r1, c1), direction) = if down.max(up) >= rightwards.max(leftwards) {
+            if down > 0 {
+                ((bottom.0 + 1, left.0), (target_r.0, right.0), MoveDirection::Down)
+            } else if up > 0 {
+                ((target_r.0, left.0), (top.0 - 1, right.0), MoveDirection::Up)
+            } else {
+                return None;
+            }
+        } else if rightwards > 0 {
+            ((top.0, right.0 + 1), (bottom.0, target_c.0), MoveDirection::Right)
+        } else {
+            ((top.0, target_c.0), (bottom.0, left.0 - 1), MoveDirection::Left)
+        };
+
+        let area = VisSelection(
+            VisRowPos(r0).linear_index(n, VisColumnPos(c0)),
+            VisRowPos(r1).linear_index(n, VisColumnPos(c1)),
+        );
+        Some((area, direction))
+    }
+
+    pub fn num_pinned_rows(&self) -> usize {
+        self.cc_num_pinned_rows
+    }
+
+    /// Whether the table scrolls by whole rows and columns instead of by points, which keeps
+    /// the frozen columns and pinned rows in place.
+    pub fn scrolls_by_cell(&self) -> bool {
+        self.cci_frozen_cols > 0 || self.cc_num_pinned_rows > 0
+    }
+
+    /// Visible columns in the order they are shown: the frozen columns, followed by the other
+    /// columns that are scrolled into view.
+    pub fn shown_cols(&self) -> Vec<VisColumnPos> {
+        let num_cols = self.p.vis_cols.len();
+        let frozen = self.cci_frozen_cols.min(num_cols);
+        (0..frozen)
+            .chain((frozen + self.cell_scroll().1).min(num_cols)..num_cols)
+            .map(VisColumnPos)
+            .collect()
+    }
+
+    /// Number of rows shown in the table body, with the visual row shown at each of them: the
+    /// pinned rows, followed by the other rows that are scrolled into view.
+    pub fn shown_rows(&self) -> (usize, impl Fn(usize) -> VisRowPos + Copy + use<R>) {
+        let pinned = self.cc_num_pinned_rows;
+        let skipped = self.cell_scroll().0;
+        let shown_row = move |index: usize| VisRowPos(if index < pinned { index } else { index + skipped });
+        (self.cc_rows.len() - skipped, shown_row)
+    }
+
+    /// Unpinned rows and unfrozen columns that are scrolled out of view.
+    pub fn cell_scroll(&self) -> (usize, usize) {
+        if self.scrolls_by_cell() { self.cci_cell_scroll } else { (0, 0) }
+    }
+
+    /// Number of unpinned rows and unfrozen columns, which can be scrolled.
+    pub fn cell_scroll_range(&self) -> (usize, usize) {
+        (
+            self.cc_rows.len() - self.cc_num_pinned_rows,
+            self.p.vis_cols.len().saturating_sub(self.cci_frozen_cols),
+        )
+    }
+
+    pub fn set_cell_scroll(&mut self, rows: usize, cols: usize) {
+        let (num_rows, num_cols) = self.cell_scroll_range();
+        self.cci_cell_scroll = (rows.min(num_rows.saturating_sub(1)), cols.min(num_cols.saturating_sub(1)));
+    }
+
+    /// Scrolls by whole rows and columns, once the scroll input adds up to one.
+    pub fn cci_scroll_by(&mut self, delta: egui::Vec2, row_height: f32, col_width: f32) {
+        self.cci_scroll_remainder += delta;
+        let rows = (self.cci_scroll_remainder.y / row_height).trunc();
+        let cols = (self.cci_scroll_remainder.x / col_width).trunc();
+        self.cci_scroll_remainder -= egui::vec2(cols * col_width, rows * row_height);
+
+        // Scrolling up or left reveals the rows and columns before.
+        let (scroll_rows, scroll_cols) = self.cci_cell_scroll;
+        self.set_cell_scroll(
+            scroll_rows.saturating_add_signed(-rows as isize),
+            scroll_cols.saturating_add_signed(-cols as isize),
+        );
+    }
+
+    /// Scrolls the interactive cell into view, while the table scrolls by cell.
+    pub fn cci_scroll_to_interactive_cell(&mut self) {
+        let (row, col) = self.interactive_cell();
+        let (mut scroll_rows, mut scroll_cols) = self.cci_cell_scroll;
+
+        // The last rendered row may be cut off, so it doesn't count as shown.
+        if let Some(row) = row.0.checked_sub(self.cc_num_pinned_rows) {
+            let page = self.cci_page_row_count.saturating_sub(self.cc_num_pinned_rows + 1).max(1);
+            scroll_rows = scroll_rows.clamp((row + 1).saturating_sub(page), row);
+        }
+        if let Some(col) = col.0.checked_sub(self.cci_frozen_cols) {
+            let page = self.cci_page_col_count.max(1);
+            scroll_cols = scroll_cols.clamp((col + 1).saturating_sub(page), col);
+        }
+
+        self.set_cell_scroll(scroll_rows, scroll_cols);
+    }
+
+    /// Initial width of the row header, or of a column, when it was shown before.
+    pub fn shown_width(&self, column: Option<ColumnIdx>) -> Option<f32> {
+        match column {
+            Some(column) => self.cci_col_widths.get(&column).copied(),
+            None => self.cci_row_header_width,
+        }
+    }
+
+    /// Remembers the widths of the row header and the shown columns, as laid out this frame.
+    pub fn remember_shown_widths(&mut self, shown_cols: &[VisColumnPos], widths: &[f32]) {
+        let Some((&row_header, widths)) = widths.split_first() else {
+            return;
+        };
+        self.cci_row_header_width = Some(row_header);
+        for (vis_col, &width) in shown_cols.iter().zip(widths) {
+            self.cci_col_widths.insert(self.p.vis_cols[vis_col.0], width);
+        }
+    }
+
+    pub fn is_dragging_rows(&self) -> bool {
+        self.cci_row_drop.is_some()
+    }
+
+    pub fn row_drop_position(&self) -> Option<VisRowPos> {
+        self.cci_row_drop
+    }
+
+    /// Starts dragging the selected rows when `row` is one of them. Rows can't be moved while
+    /// the table is sorted, since the sort decides their order.
+    pub fn cci_row_drag_start(&mut self, row: VisRowPos) -> bool {
+        if !self.p.sort.is_empty() || !self.collect_selected_rows().contains(&row) {
+            return false;
+        }
+        self.cci_row_drop = Some(row);
+        true
+    }
+
+    pub fn cci_row_drag_update(&mut self, position: VisRowPos) {
+        self.cci_row_drop = Some(VisRowPos(position.0.min(self.cc_rows.len())));
+    }
+
+    /// Finishes dragging rows: the selected rows are moved in front of the drop position, in
+    /// their visual order.
+    pub fn cci_take_row_drag(&mut self, table: &DataTable<R>) -> Vec<Command<R>> {
+        let Some(drop) = self.cci_row_drop.take() else {
+            return vec![];
+        };
+
+        let moving = self.collect_selected_rows().into_iter().map(|r| self.cc_rows[r.0]).collect::<Vec<_>>();
+        let is_moving = |row: &RowIdx| moving.contains(row);
+
+        // Rows are inserted in front of the first row below the drop position that stays, or
+        // behind the last row that stays when dropped at the end.
+        let remaining = (0..table.rows.len()).map(RowIdx).filter(|row| !is_moving(row)).collect::<Vec<_>>();
+        let anchor = self.cc_rows[drop.0.min(self.cc_rows.len())..].iter().find(|row| !is_moving(row));
+        let position = match anchor {
+            Some(anchor) => remaining.iter().position(|row| row == anchor).unwrap_or(remaining.len()),
+            None => self.cc_rows.iter().rev().find(|row| !is_moving(row))
+                .and_then(|last| remaining.iter().position(|row| row == last))
+                .map_or(remaining.len(), |i| i + 1),
+        };
+
+        let select = (position..position + moving.len()).map(RowIdx).collect();
+        let mut order = remaining;
+        order.splice(position..position, moving);
+        if order.iter().enumerate().all(|(i, row)| row.0 == i) {
+            return vec![];
+        }
+
+        vec![Command::ReorderRows { order: order.into_boxed_slice(), select }]
+    }
+
+    /// Finishes dragging the fill handle: every column (or row) of the selection continues as
+    /// a series into the fill area, as a single undoable edit.
+    pub fn cci_take_fill(&mut self, table: &DataTable<R>, vwr: &mut impl RowViewer<R>) -> Vec<Command<R>> {
+        let area = self.fill_area();
+        self.cci_fill_target = None;
+        let (Some(source), Some((area, direction))) = (self.fill_source(), area) else {
+            return vec![];
+        };
+
+        let n = self.p.vis_cols.len();
+        let (top, left) = source.0.row_col(n);
+        let (bottom, right) = source.1.row_col(n);
+        let (area_top, area_left) = area.0.row_col(n);
+        let (area_bottom, area_right) = area.1.row_col(n);
+
+        // Seed and target cells of each series, in fill order.
+        let reverse = matches!(direction, MoveDirection::Up | MoveDirection::Left);
+        let series = |seed: Vec<(usize, usize)>, mut targets: Vec<(usize, usize)>| {
+            let mut seed = seed;
+            if reverse {
+                seed.reverse();
+                targets.reverse();
+            }
+            (seed, targets)
+        };
+        let lines: Vec<_> = match direction {
+            MoveDirection::Down | MoveDirection::Up => (left.0..=right.0)
+                .map(|c| {
+                    series(
+                        (top.0..=bottom.0).map(|r| (r, c)).collect(),
+                        (area_top.0..=area_bottom.0).map(|r| (r, c)).collect(),
+                    )
+                })
+                .collect(),
+            MoveDirection::Right | MoveDirection::Left => (top.0..=bottom.0)
+                .map(|r| {
+                    series(
+                        (left.0..=right.0).map(|c| (r, c)).collect(),
+                        (area_left.0..=area_right.0).map(|c| (r, c)).collect(),
+                    )
+                })
+                .collect(),
+        };
+
+        let mut slab = Vec::new();
+        let mut values = Vec::new();
+        for (seed, targets) in lines {
+            let cell = |(r, c): (usize, usize)| (self.cc_rows[r], self.p.vis_cols[c]);
+            let seed = seed.into_iter().map(cell).map(|(row, col)| (&table.rows[row.0], col.0)).collect::<Vec<_>>();
+            let targets = targets.into_iter().map(cell).collect::<Vec<_>>();
+            let target_rows = targets.iter().map(|(row, col)| (&table.rows[row.0], col.0)).collect::<Vec<_>>();
+
+            for ((row, col), value) in targets.iter().zip(vwr.fill_series(&seed, &target_rows)) {
+                if let Some(value) = value {
+                    values.push((*row, *col, RowSlabIndex(slab.len())));
+                    slab.push(value);
+                }
+            }
+        }
+
+        vec![
+            Command::CcSetCells {
+                slab: slab.into_boxed_slice(),
+                values: values.into_boxed_slice(),
+                context: CellWriteContext::Fill,
+            },
+            Command::CcSetSelection(vec![source.union(n, area)]),
+        ]
+    }
+
     pub fn vis_sel_contains(&self, sel: VisSelection, row: VisRowPos, col: VisColumnPos) -> bool {
         sel.contains(self.p.vis_cols.len(), row, col)
     }
@@ -684,6 +1071,41 @@ impl<R> UiState<R> {
             self.push_new_command(table, vwr, Command::CcCommitEdit, capacity);
         }
 
+        // Structural column changes are undone by restoring a snapshot of the columns, taken
+        // from the viewer, together with the rows whenever cell values are added or removed.
+        if matches!(
+            command,
+            Command::AddColumn(_)
+                | Command::RemoveColumn(_)
+                | Command::ColumnsChanged(_)
+                | Command::RenameCommitted(RenameTarget::Column(_), _)
+        ) {
+            let before = match &command {
+                Command::ColumnsChanged(before) => Some(before.clone()),
+                _ => vwr.snapshot_columns(),
+            };
+
+            if let Some(before) = before {
+                let with_rows = matches!(command, Command::AddColumn(_) | Command::RemoveColumn(_));
+                let restore = self.columns_state(table, vwr, before, with_rows);
+
+                self.cmd_apply(table, vwr, &command);
+                self.sync_num_columns(vwr);
+
+                let after = vwr.snapshot_columns().unwrap_or_else(|| restore.columns.clone());
+                let apply = self.columns_state(table, vwr, after, with_rows);
+
+                self.push_undo_arg(
+                    UndoArg {
+                        apply: Command::RestoreColumns(apply),
+                        restore: vec![Command::RestoreColumns(restore)],
+                    },
+                    capacity,
+                );
+                return;
+            }
+        }
+
         // Generate redo argument from command
         let restore = match command {
             Command::MoveColumn(from, to) => {
@@ -763,6 +1185,14 @@ impl<R> UiState<R> {
                     return;
                 }
 
+                // The viewer may reject the edited values, which discards the edition.
+                let current = &table.rows[row_id.0];
+                if !(0..self.p.num_columns).all(|col| {
+                    vwr.confirm_cell_write_by_ui(current, &edition, col, CellWriteContext::Edit)
+                }) {
+                    return;
+                }
+
                 // Change the command type of self.
                 self.push_new_command(
                     table,
@@ -887,21 +1317,25 @@ impl<R> UiState<R> {
                     })
                     .collect()
             }
-            Command::AddColumn(_at) => {
-                // For now, we don't support undoing column addition easily.
-                // We'd need to store the added data for every row.
-                vec![]
+            Command::ReorderRows { ref order, ref select } => {
+                let mut inverse = vec![RowIdx(0); order.len()];
+                for (i, row) in order.iter().enumerate() {
+                    inverse[row.0] = RowIdx(i);
+                }
+                let select = select.iter().map(|row| order[row.0]).collect();
+                vec![Command::ReorderRows { order: inverse.into_boxed_slice(), select }]
             }
-            Command::RequestSave => {
-                vec![]
+            Command::RenameCommitted(RenameTarget::Row(row), _) => {
+                vec![Command::SetRowValue(RowIdx(row), vwr.clone_row(&table.rows[row]).into())]
             }
-            Command::RenameCommitted(_target, ref _new_name) => {
-                // Rename committed doesn't easily support undoing here
-                // as we don't store the old name in the command.
+            Command::AddColumn(_)
+            | Command::RemoveColumn(_)
+            | Command::ColumnsChanged(_)
+            | Command::RenameCommitted(..) => {
+                // The viewer does not provide column snapshots, so these can't be undone.
                 vec![]
             }
-            Command::RemoveColumn(_idx) => {
-                // Similarly to AddColumn, we don't support undoing column removal easily yet
+            Command::RequestSave | Command::RestoreColumns(..) => {
                 vec![]
             }
             Command::CcUpdateSystemClipboard(..) => {
@@ -910,6 +1344,19 @@ impl<R> UiState<R> {
             }
         };
 
+        // Apply the command.
+        self.cmd_apply(table, vwr, &command);
+
+        self.push_undo_arg(
+            UndoArg {
+                apply: command,
+                restore,
+            },
+            capacity,
+        );
+    }
+
+    fn push_undo_arg(&mut self, arg: UndoArg<R>, capacity: usize) {
         // Discard all redo actions in the queue after this point.
         self.undo_queue.drain(0..self.undo_cursor);
 
@@ -920,14 +1367,39 @@ impl<R> UiState<R> {
         // Now it's the foremost element of the undo queue.
         self.undo_cursor = 0;
 
-        // Apply the command.
-        self.cmd_apply(table, vwr, &command);
-
         // Push the command to the queue.
-        self.undo_queue.push_front(UndoArg {
-            apply: command,
-            restore,
-        });
+        self.undo_queue.push_front(arg);
+    }
+
+    fn columns_state<V: RowViewer<R>>(
+        &self,
+        table: &DataTable<R>,
+        vwr: &mut V,
+        columns: ColumnSnapshot,
+        with_rows: bool,
+    ) -> ColumnsState<R> {
+        ColumnsState {
+            columns,
+            rows: with_rows.then(|| table.rows.iter().map(|row| vwr.clone_row(row)).collect()),
+            num_columns: self.p.num_columns,
+            vis_cols: self.p.vis_cols.clone(),
+            sort: self.p.sort.clone(),
+        }
+    }
+
+    /// Follows a change of the number of columns made by the viewer, so the undo history
+    /// survives the next identity validation.
+    fn sync_num_columns<V: RowViewer<R>>(&mut self, vwr: &mut V) {
+        let num_columns = vwr.num_columns();
+        if self.p.num_columns == num_columns {
+            return;
+        }
+
+        self.validate_interactive_cell(num_columns);
+        self.p.num_columns = num_columns;
+        self.p.vis_cols = (0..num_columns).map(ColumnIdx).collect();
+        self.p.sort.clear();
+        self.cc_dirty = true;
     }
 
     fn cmd_apply<V: RowViewer<R>>(
@@ -942,17 +1414,17 @@ impl<R> UiState<R> {
                 self.p.vis_cols.clear();
                 self.p.vis_cols.extend(cols.iter().cloned());
                 self.cc_dirty = true;
-                table.dirty_flag = true;
+                table.mark_as_modified();
             }
             Command::SetColumnSort(new_sort) => {
                 self.p.sort.clear();
                 self.p.sort.extend(new_sort.iter().cloned());
                 self.cc_dirty = true;
-                table.dirty_flag = true;
+                table.mark_as_modified();
             }
             Command::SetRowValue(row_id, value) => {
                 self.cc_num_frame_from_last_edit = 0;
-                table.dirty_flag = true;
+                table.mark_as_modified();
                 let old_row = vwr.clone_row(&table.rows[row_id.0]);
                 table.rows[row_id.0] = vwr.clone_row(value);
 
@@ -960,7 +1432,7 @@ impl<R> UiState<R> {
             }
             Command::SetCells { slab, values } => {
                 self.cc_num_frame_from_last_edit = 0;
-                table.dirty_flag = true;
+                table.mark_as_modified();
 
                 let mut modified_rows: HashMap<RowIdx, R> = HashMap::new();
 
@@ -976,7 +1448,7 @@ impl<R> UiState<R> {
             }
             Command::InsertRows(pos, values) => {
                 self.cc_dirty = true; // It invalidates all current `RowId` occurrences.
-                table.dirty_flag = true;
+                table.mark_as_modified();
 
                 table
                     .rows
@@ -991,7 +1463,7 @@ impl<R> UiState<R> {
             Command::RemoveRow(values) => {
                 debug_assert!(values.windows(2).all(|x| x[0] < x[1]));
                 self.cc_dirty = true; // It invalidates all current `RowId` occurrences.
-                table.dirty_flag = true;
+                table.mark_as_modified();
 
                 for row_index in values.iter() {
                     vwr.on_row_removed(row_index.0, &mut table.rows[row_index.0]);
@@ -1005,23 +1477,46 @@ impl<R> UiState<R> {
 
                 self.queue_select_rows([]);
             }
+            Command::ReorderRows { order, select } => {
+                self.cc_dirty = true; // It invalidates all current `RowId` occurrences.
+                table.mark_as_modified();
+
+                let mut rows = take(&mut table.rows).into_iter().map(Some).collect::<Vec<_>>();
+                table.rows = order.iter().map(|row| rows[row.0].take().unwrap()).collect();
+
+                vwr.on_rows_reordered(&order.iter().map(|row| row.0).collect::<Vec<_>>());
+                self.queue_select_rows(select.iter().copied());
+            }
             Command::AddColumn(at) => {
-                table.dirty_flag = true;
+                table.mark_as_modified();
                 vwr.on_column_inserted(table, *at);
                 self.cc_dirty = true;
             }
             Command::MoveColumn(from, to) => {
-                table.dirty_flag = true;
+                table.mark_as_modified();
                 vwr.on_column_moved(table, *from, *to);
                 self.cc_dirty = true;
             }
             Command::RemoveColumn(idx) => {
-                table.dirty_flag = true;
+                table.mark_as_modified();
                 vwr.on_column_removed(table, *idx);
                 self.cc_dirty = true;
             }
-            Command::RequestSave => {
-                table.dirty_flag = true;
+            Command::RequestSave | Command::ColumnsChanged(_) => {
+                table.mark_as_modified();
+            }
+            Command::RestoreColumns(state) => {
+                vwr.restore_columns(&state.columns);
+                if let Some(rows) = &state.rows {
+                    table.rows = rows.iter().map(|row| vwr.clone_row(row)).collect();
+                }
+
+                self.validate_interactive_cell(state.vis_cols.len());
+                self.p.num_columns = state.num_columns;
+                self.p.vis_cols.clone_from(&state.vis_cols);
+                self.p.sort.clone_from(&state.sort);
+                self.cc_dirty = true;
+                table.mark_as_modified();
             }
             Command::RenameCommitted(target, new_name) => {
                 vwr.on_rename_committed(table, *target, new_name.clone());
@@ -1140,6 +1635,27 @@ impl<R> UiState<R> {
         self.cc_interactive_cell = row.linear_index(self.p.vis_cols.len(), col);
     }
 
+    /// Selects a single cell and scrolls it into view. Returns `false` if the cell is not
+    /// visible, e.g. because its row is filtered out or its column is hidden, or when a
+    /// cell is being edited.
+    pub fn focus_cell(&mut self, row: RowIdx, column: ColumnIdx) -> bool {
+        if self.is_editing() {
+            return false;
+        }
+        let Some(vis_row) = self.cc_row_id_to_vis.get(&row).copied() else {
+            return false;
+        };
+        let Some(vis_col) = self.p.vis_cols.iter().position(|x| *x == column) else {
+            return false;
+        };
+
+        let cell = vis_row.linear_index(self.p.vis_cols.len(), VisColumnPos(vis_col));
+        self.cc_interactive_cell = cell;
+        self.cc_cursor = CursorState::Select(vec![VisSelection(cell, cell)]);
+        self.cci_want_move_scroll = true;
+        true
+    }
+
     pub fn try_apply_ui_action(
         &mut self,
         table: &mut DataTable<R>,
@@ -1348,6 +1864,10 @@ impl<R> UiState<R> {
                     context: CellWriteContext::Clear,
                 }]
             }
+            UiAction::Find | UiAction::Replace => {
+                vwr.on_find_requested(action == UiAction::Replace);
+                vec![]
+            }
             UiAction::DeleteRow => {
                 if vwr.allow_row_deletions() {
                     let rows = self
@@ -1403,6 +1923,12 @@ impl<R> UiState<R> {
         }
     }
 
+    /// Columns that contain at least one selected cell, in visual order.
+    pub fn selected_columns(&self) -> Vec<ColumnIdx> {
+        let columns = self.collect_selection().into_iter().map(|(_, c)| c).collect::<BTreeSet<_>>();
+        columns.into_iter().map(|c| self.p.vis_cols[c.0]).collect()
+    }
+
     fn collect_selection(&self) -> BTreeSet<(VisRowPos, VisColumnPos)> {
         let mut set = BTreeSet::new();
 
@@ -32,6 +32,10 @@ pub struct DataTable<R> {
 
     dirty_flag: bool,
 
+    /// Incremented on every change of the rows, so callers can tell whether they changed since
+    /// they last looked without comparing them.
+    modifications: u64,
+
     /// Ui
     ui: Option<Box<ui_state::UiState<R>>>,
 }
@@ -50,6 +54,7 @@ impl<R> Default for DataTable<R> {
             rows: Default::default(),
             ui: Default::default(),
             dirty_flag: false,
+            modifications: 0,
         }
     }
 }
@@ -70,12 +75,14 @@ impl<R> DataTable<R> {
 
     pub fn take(&mut self) -> Vec<R> {
         self.mark_dirty();
+        self.modifications += 1;
         std::mem::take(&mut self.rows)
     }
 
     /// Replace the current data with the new one.
     pub fn replace(&mut self, new: Vec<R>) -> Vec<R> {
         self.mark_dirty();
+        self.modifications += 1;
         std::mem::replace(&mut self.rows, new)
     }
 
@@ -92,6 +99,7 @@ impl<R> DataTable<R> {
 
         if removed_any {
             self.mark_dirty();
+            self.modifications += 1;
         }
     }
 
@@ -121,6 +129,12 @@ impl<R> DataTable<R> {
 
     pub fn mark_as_modified(&mut self) {
         self.dirty_flag = true;
+        self.modifications += 1;
+    }
+
+    /// Number of changes of the rows so far, including programmatic ones.
+    pub fn modifications(&self) -> u64 {
+        self.modifications
     }
 
     /// Returns the current visual column order.
@@ -139,6 +153,109 @@ impl<R> DataTable<R> {
         }
     }
 
+    /// Selects the given cell and scrolls it into view. Returns `false` if the cell is not
+    /// visible, or the table has not been rendered yet.
+    pub fn focus_cell(&mut self, row: usize, column: usize) -> bool {
+        self.ui.as_mut().is_some_and(|ui| ui.focus_cell(RowIdx(row), ColumnIdx(column)))
+    }
+
+    /// Removes a column through the undo history of the table, so it can be restored with its
+    /// values. Falls back to [`RowViewer::on_column_removed`] if the table has not been rendered yet.
+    pub fn remove_column<V: RowViewer<R>>(&mut self, viewer: &mut V, column: usize) {
+        let Some(mut ui) = self.ui.take() else {
+            viewer.on_column_removed(self, column);
+            return;
+        };
+
+        let capacity = ui.cci_undo_capacity;
+        ui.push_new_command(self, viewer, draw::command::Command::RemoveColumn(column), capacity);
+        self.ui = Some(ui);
+    }
+
+    /// Applies an action as if its hotkey was pressed, the next time the table is shown. Returns
+    /// `false` if the table has not been rendered yet.
+    pub fn queue_action(&mut self, action: UiAction) -> bool {
+        self.ui.as_mut().map(|ui| ui.queued_actions.push(action)).is_some()
+    }
+
+    /// Hides or shows a column through the undo history of the table.
+    pub fn set_column_visible<V: RowViewer<R>>(&mut self, viewer: &mut V, column: usize, visible: bool) {
+        let Some(mut ui) = self.ui.take() else {
+            return;
+        };
+
+        let column = ColumnIdx(column);
+        let shown = ui.vis_cols().contains(&column);
+        let command = match (visible, shown) {
+            (false, true) => Some(draw::command::Command::CcHideColumn(column)),
+            (true, false) => Some(draw::command::Command::CcShowColumn { what: column, at: VisColumnPos(ui.vis_cols().len()) }),
+            _ => None,
+        };
+        if let Some(command) = command {
+            let capacity = ui.cci_undo_capacity;
+            ui.push_new_command(self, viewer, command, capacity);
+        }
+        self.ui = Some(ui);
+    }
+
+    /// Sorts the rows by a column, ascending or not, through the undo history of the table. `None`
+    /// clears the sort. Does nothing if the table has not been rendered yet.
+    pub fn set_sort<V: RowViewer<R>>(&mut self, viewer: &mut V, sort: Option<(usize, bool)>) {
+        let Some(mut ui) = self.ui.take() else {
+            return;
+        };
+
+        let sort = sort.into_iter()
+            .filter(|&(column, _)| viewer.is_sortable_column(column))
+            .map(|(column, ascending)| (ColumnIdx(column), IsAscending(ascending)))
+            .collect();
+        let capacity = ui.cci_undo_capacity;
+        ui.push_new_command(self, viewer, draw::command::Command::SetColumnSort(sort), capacity);
+        self.ui = Some(ui);
+    }
+
+    /// Returns the columns that contain selected cells, in visual order.
+    pub fn selected_columns(&self) -> Vec<usize> {
+        self.ui.as_ref().map(|ui| ui.selected_columns().into_iter().map(|c| c.0).collect()).unwrap_or_default()
+    }
+
+    /// Replaces the value of each `(row, column)` cell with the same column of the given row, as
+    /// a single step in the undo history. Each write is confirmed with
+    /// [`RowViewer::confirm_cell_write_by_ui`] first. Falls back to [`RowViewer::set_cell_value`]
+    /// if the table has not been rendered yet.
+    pub fn set_cells<V: RowViewer<R>>(&mut self, viewer: &mut V, cells: Vec<(usize, usize, R)>) {
+        if cells.is_empty() {
+            return;
+        }
+
+        let Some(mut ui) = self.ui.take() else {
+            for (row, column, value) in cells {
+                if viewer.is_editable_cell(column, row, &self.rows[row])
+                    && viewer.confirm_cell_write_by_ui(&self.rows[row], &value, column, CellWriteContext::Replace) {
+                    viewer.set_cell_value(&value, &mut self.rows[row], column);
+                }
+            }
+            self.mark_as_modified();
+            return;
+        };
+
+        let mut slab = Vec::with_capacity(cells.len());
+        let mut values = Vec::with_capacity(cells.len());
+        for (row, column, value) in cells {
+            values.push((RowIdx(row), ColumnIdx(column), RowSlabIndex(slab.len())));
+            slab.push(value);
+        }
+
+        let command = draw::command::Command::CcSetCells {
+            slab: slab.into_boxed_slice(),
+            values: values.into_boxed_slice(),
+            context: CellWriteContext::Replace,
+        };
+        let capacity = ui.cci_undo_capacity;
+        ui.push_new_command(self, viewer, command, capacity);
+        self.ui = Some(ui);
+    }
+
     /// Sets the visual column order.
     pub fn set_visual_column_order(&mut self, order: Vec<usize>) {
         if let Some(ui) = self.ui.as_mut() {
@@ -154,6 +271,7 @@ impl<R> Extend<R> for DataTable<R> {
     fn extend<T: IntoIterator<Item = R>>(&mut self, iter: T) {
         // Invalidate the cache
         self.ui = None;
+        self.modifications += 1;
         self.rows.extend(iter);
     }
 }
@@ -184,6 +302,7 @@ impl<R: Clone> Clone for DataTable<R> {
             // UI field is treated as cache.
             ui: None,
             dirty_flag: self.dirty_flag,
+            modifications: self.modifications,
         }
     }
 }
@@ -7,6 +7,7 @@ use eframe::emath::{Align, Rect};
 use tap::{Pipe, Tap};
 use crate::egui_data_table::*;
 use crate::egui_data_table::draw::command::*;
+use crate::egui_data_table::draw::system_clipboard;
 use crate::egui_data_table::ui_state::*;
 
 pub struct Renderer<'a, R, V: RowViewer<R>> {
@@ -89,7 +90,16 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
         self
     }
 
-    pub fn show(self, ui: &mut egui::Ui) -> Response {
+    pub fn show(mut self, ui: &mut egui::Ui) -> Response {
+        let s = self.state.as_mut().unwrap();
+        s.cci_frozen_cols = self.viewer.frozen_columns();
+        s.cci_undo_capacity = self.style.undo_capacity();
+
+        // The table scrolls by cell by itself, which keeps frozen columns in place.
+        if s.scrolls_by_cell() {
+            return self.impl_show(ui);
+        }
+
         egui::ScrollArea::horizontal()
             .show(ui, |ui| self.impl_show(ui))
             .inner
@@ -99,7 +109,6 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
         let ctx = &ui.ctx().clone();
         let ui_id = ui.id();
         let style = ui.style().clone();
-        let painter = ui.painter().clone();
         let visual = &style.visuals;
         let viewer = &mut *self.viewer;
         let s = self.state.as_mut().unwrap();
@@ -117,25 +126,56 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
             Color32::GREEN
         };
 
-        let mut builder = egui_extras::TableBuilder::new(ui).column(Column::auto());
+        let scrolls_by_cell = s.scrolls_by_cell();
+        let mut scroll_to_row = None;
+        if replace(&mut s.cci_want_move_scroll, false) {
+            if scrolls_by_cell {
+                s.cci_scroll_to_interactive_cell();
+            } else {
+                scroll_to_row = Some(s.interactive_cell().0);
+            }
+        }
+
+        let shown_cols = s.shown_cols();
+        let frozen_cols = s.cci_frozen_cols.min(shown_cols.len());
+        let clip_rect = ui.clip_rect();
+        let separator = Stroke { width: 2., color: visual.widgets.noninteractive.bg_stroke.color };
 
-        let iter_vis_cols_with_flag = s
-            .vis_cols()
-            .iter()
-            .enumerate()
-            .map(|(index, column)| (column, index + 1 == s.vis_cols().len()));
+        // While scrolling by cell, the shown columns change. Columns keep the width they had,
+        // instead of the width of the column that was shown at their position before.
+        let row_header = match s.shown_width(None) {
+            Some(width) if scrolls_by_cell => Column::initial(width),
+            _ => Column::auto(),
+        };
+        let mut builder = egui_extras::TableBuilder::new(ui).column(row_header);
+
+        for (index, vis_col) in shown_cols.iter().enumerate() {
+            let column = s.vis_cols()[vis_col.0];
+            let is_last = index + 1 == shown_cols.len();
+            builder = builder.column(match s.shown_width(Some(column)) {
+                Some(width) if scrolls_by_cell && !is_last => Column::initial(width).resizable(true),
+                _ => viewer.column_render_config(column.0, is_last),
+            });
+        }
 
-        for (column, flag) in iter_vis_cols_with_flag {
-            builder = builder.column(viewer.column_render_config(column.0, flag));
+        if let Some(row) = scroll_to_row {
+            builder = builder.scroll_to_row(row.0, None);
         }
 
-        if replace(&mut s.cci_want_move_scroll, false) {
-            let interact_row = s.interactive_cell().0;
-            builder = builder.scroll_to_row(interact_row.0, None);
+        if scrolls_by_cell {
+            let shown_col_ids = shown_cols.iter().map(|c| s.vis_cols()[c.0]).collect::<Vec<_>>();
+            if s.cci_shown_cols != shown_col_ids {
+                builder.reset();
+                s.cci_shown_cols = shown_col_ids;
+            }
         }
 
+        let num_hidden_cols = if scrolls_by_cell { 0 } else { s.num_columns() - s.vis_cols().len() };
+        let mut num_page_cols = 0;
+
         builder
-            .columns(Column::auto(), s.num_columns() - s.vis_cols().len())
+            .columns(Column::auto(), num_hidden_cols)
+            .vscroll(!scrolls_by_cell)
             .drag_to_scroll(false) // Drag is used for selection;
             .striped(true)
             .cell_layout(Layout::default().with_cross_align(self.style.cell_align))
@@ -150,8 +190,8 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
 
                 let _has_any_hidden_col = s.vis_cols().len() != s.num_columns();
 
-                for (vis_col, &col) in s.vis_cols().iter().enumerate() {
-                    let vis_col = VisColumnPos(vis_col);
+                for &vis_col in &shown_cols {
+                    let col = s.vis_cols()[vis_col.0];
                     let mut painter = None;
                     let (col_rect, resp) = h.col(|ui| {
                         egui::Sides::new().show(ui, |ui| {
@@ -177,6 +217,14 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                         painter = Some(ui.painter().clone());
                     });
 
+                    if vis_col.0 >= frozen_cols && col_rect.right() <= clip_rect.right() {
+                        num_page_cols += 1;
+                    }
+                    if vis_col.0 + 1 == frozen_cols
+                        && let Some(p) = &painter {
+                        p.vline(col_rect.right(), col_rect.y_range(), separator);
+                    }
+
                     // Set drag payload for column reordering.
                     resp.dnd_set_drag_payload(vis_col);
 
@@ -249,11 +297,14 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                                 HeaderAction::ClearSort => commands.push(Command::SetColumnSort(Vec::new())),
                                 HeaderAction::ShowHidden(what) => commands.push(Command::CcShowColumn { what: ColumnIdx(what), at: vis_col }),
                                 HeaderAction::RemoveColumn(idx) => commands.push(Command::RemoveColumn(idx)),
+                                HeaderAction::ColumnsChanged(before) => commands.push(Command::ColumnsChanged(before)),
                             }
                         }
                     });
                 }
 
+                s.cci_page_col_count = num_page_cols;
+
                 // Account for the header response to calculate the total response.
                 resp_total = Some(h.response());
             })
@@ -262,18 +313,50 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
             })
             .body(|body: egui_extras::TableBody<'_>| {
                 resp_ret = Some(
-                    self.impl_show_body(body, painter, commands, ctx, &style, ui_id, resp_total),
+                    self.impl_show_body(body, &shown_cols, separator, commands, ctx, &style, ui_id, resp_total),
                 );
             });
 
-        resp_ret.unwrap_or_else(|| ui.label("??"))
+        let resp = resp_ret.unwrap_or_else(|| ui.label("??"));
+        if scrolls_by_cell {
+            self.show_cell_scroll(ui, &resp);
+        }
+        resp
+    }
+
+    /// Scrolls the table by whole rows and columns with the mouse wheel and the scroll bars,
+    /// which keeps the frozen columns and pinned rows in place.
+    fn show_cell_scroll(&mut self, ui: &mut egui::Ui, resp: &Response) {
+        let s = self.state.as_mut().unwrap();
+        let view_rect = resp.rect.intersect(ui.clip_rect());
+
+        if ui.rect_contains_pointer(view_rect) {
+            let delta = ui.input_mut(|i| take(&mut i.smooth_scroll_delta));
+            let row_height = self.style.table_row_height.unwrap_or(self.style.min_row_height.max(20.));
+            s.cci_scroll_by(delta, row_height, CELL_SCROLL_COLUMN_WIDTH);
+        }
+
+        let (scroll_rows, scroll_cols) = s.cell_scroll();
+        let (num_rows, num_cols) = s.cell_scroll_range();
+        let bar_width = ui.spacing().scroll.bar_width;
+
+        let vertical = view_rect.with_min_x(view_rect.right() - bar_width);
+        if let Some(rows) = cell_scroll_bar(ui, ui.id().with("cell_scroll_v"), vertical, true, scroll_rows, num_rows) {
+            s.set_cell_scroll(rows, scroll_cols);
+        }
+
+        let horizontal = view_rect.with_min_y(view_rect.bottom() - bar_width).with_max_x(vertical.left());
+        if let Some(cols) = cell_scroll_bar(ui, ui.id().with("cell_scroll_h"), horizontal, false, scroll_cols, num_cols) {
+            s.set_cell_scroll(scroll_rows, cols);
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
     fn impl_show_body(
         &mut self,
         body: egui_extras::TableBody<'_>,
-        _painter: egui::Painter,
+        shown_cols: &[VisColumnPos],
+        separator: Stroke,
         mut commands: Vec<Command<R>>,
         ctx: &egui::Context,
         style: &egui::Style,
@@ -287,15 +370,15 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
         let visible_cols = s.vis_cols().clone();
         let no_rounding = egui::CornerRadius::ZERO;
 
-        let mut actions = Vec::<UiAction>::new();
+        let mut actions = std::mem::take(&mut s.queued_actions);
         let mut edit_started = false;
         let hotkeys = viewer.hotkeys(&s.ui_action_context());
 
         // Preemptively consume all hotkeys.
         'detect_hotkey: {
-            // Detect hotkey inputs only when the table has focus. While editing, let the
-            // editor consume input.
-            if !s.cci_has_focus {
+            // Detect hotkey inputs only when the table has focus and no modal dialog is shown
+            // above it. While editing, let the editor consume input.
+            if !s.cci_has_focus || ctx.memory(|m| m.top_modal_layer()).is_some() {
                 break 'detect_hotkey;
             }
 
@@ -313,7 +396,8 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                                     // If the system clipboard is not empty, try to update the internal
                                     // clipboard with system clipboard content before applying
                                     // paste operation.
-                                    s.try_update_clipboard_from_string(viewer, clipboard);
+                                    let html = system_clipboard::html();
+                                    s.try_update_clipboard_from_string(viewer, clipboard, html.as_deref());
                                 }
 
                                 if i.modifiers.shift {
@@ -332,9 +416,22 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                 });
             }
 
+            // While editing, modifiers have to match exactly, so that e.g. Shift+Enter reaches
+            // the editor instead of committing the edit as Enter does.
+            let editing = s.is_editing();
             for (hotkey, action) in &hotkeys {
                 ctx.input_mut(|inp| {
-                    if inp.consume_shortcut(hotkey) {
+                    let consumed = if editing {
+                        let count = inp.events.len();
+                        inp.events.retain(|event| {
+                            !matches!(event, Event::Key { key, pressed: true, modifiers, .. }
+                                if *key == hotkey.logical_key && modifiers.matches_exact(hotkey.modifiers))
+                        });
+                        inp.events.len() != count
+                    } else {
+                        inp.consume_shortcut(hotkey)
+                    };
+                    if consumed {
                         actions.push(*action);
                     }
                 })
@@ -362,12 +459,22 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
 
         let body_max_rect = body.max_rect();
         let has_any_sort = !s.sort().is_empty();
+        s.remember_shown_widths(shown_cols, body.widths());
 
         let pointer_interact_pos = ctx.input(|i| i.pointer.latest_pos().unwrap_or_default());
         let pointer_primary_down = ctx.input(|i| i.pointer.button_down(PointerButton::Primary));
 
         s.cci_page_row_count = 0;
 
+        // The fill handle is drawn at the bottom-right cell of the selection.
+        let fill_handle_cell = s.fill_source().map(|sel| sel.1);
+        let fill_area = s.fill_area();
+        let row_drop = s.row_drop_position();
+        let num_vis_rows = s.cc_rows.len();
+        let num_pinned_rows = s.num_pinned_rows();
+        let frozen_cols = s.cci_frozen_cols;
+        let (num_shown_rows, shown_row) = s.shown_rows();
+
         /* ----------------------------- Primary Rendering Function ----------------------------- */
         // - Extracted as a closure to differentiate behavior based on row height
         //   configuration. (heterogeneous or homogeneous row heights)
@@ -375,7 +482,7 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
         let render_fn = |mut row: egui_extras::TableRow| {
             s.cci_page_row_count += 1;
 
-            let vis_row = VisRowPos(row.index());
+            let vis_row = shown_row(row.index());
             let row_id = s.cc_rows[vis_row.0];
             let prev_row_height = cc_row_heights[vis_row.0];
 
@@ -428,8 +535,32 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                 if head_resp.double_clicked() {
                     viewer.row_header_double_clicked(ui.ctx(), row_id.0, &table.rows[row_id.0]);
                 }
+                head_resp.context_menu(|ui| {
+                    viewer.row_header_context_menu(ui, row_id.0, &table.rows[row_id.0]);
+                });
+
+                if vis_row.0 + 1 == num_pinned_rows {
+                    let rect = ui.max_rect();
+                    ui.painter().hline(rect.x_range(), rect.bottom(), separator);
+                }
+
+                // Selected rows are moved by dragging their headers.
+                if head_resp.is_pointer_button_down_on()
+                    && !s.is_dragging_rows()
+                    && !s.has_cci_selection()
+                    && !s.is_filling()
+                {
+                    s.cci_row_drag_start(vis_row);
+                }
 
-                if check_mouse_dragging_selection(&ui.max_rect(), &head_resp) {
+                if s.is_dragging_rows() {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                    let rect = ui.max_rect();
+                    if pointer_primary_down && rect.y_range().contains(pointer_interact_pos.y) {
+                        let below = pointer_interact_pos.y > rect.center().y;
+                        s.cci_row_drag_update(VisRowPos(vis_row.0 + below as usize));
+                    }
+                } else if !s.is_filling() && check_mouse_dragging_selection(&ui.max_rect(), &head_resp) {
                     s.cci_sel_update_row(vis_row);
                 }
             });
@@ -441,17 +572,19 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
             /* -------------------------------- Columns Rendering ------------------------------- */
 
             // Overridable maximum height
-            let mut new_maximum_height = 0.;
+            let mut new_maximum_height = self.style.min_row_height;
 
             // Render cell contents regardless of the edition state.
-            for (vis_col, col) in visible_cols.iter().enumerate() {
-                let vis_col = VisColumnPos(vis_col);
+            for &vis_col in shown_cols {
+                let col = &visible_cols[vis_col.0];
                 let linear_index = vis_row.linear_index(visible_cols.len(), vis_col);
                 let selected = s.is_selected(vis_row, vis_col);
-                let cci_selected = s.is_selected_cci(vis_row, vis_col);
+                let cci_selected = s.is_selected_cci(vis_row, vis_col)
+                    || fill_area.is_some_and(|(area, _)| area.contains(visible_cols.len(), vis_row, vis_col));
                 let is_editing = edit_state.is_some();
                 let is_interactive_cell = interactive_row.is_some_and(|x| x == vis_col);
                 let mut response_consumed = s.is_editing();
+                let mut fill_handle_pressed = false;
 
                 let (rect, resp) = row.col(|ui| {
                     let ui_max_rect = ui.max_rect();
@@ -511,7 +644,7 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                     // the previous table behavior.
                     ui.add_enabled_ui(false, |ui| {
                         if !(is_editing && is_interactive_cell) {
-                            viewer.show_cell_view(ui, &table.rows[row_id.0], col.0);
+                            viewer.show_cell_view(ui, row_id.0, &table.rows[row_id.0], col.0);
                         }
                     });
 
@@ -545,12 +678,54 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                     if edit_state.is_some_and(|(_, vis)| vis == vis_col) {
                         editing_cell_rect = ui_max_rect;
                     }
+
+                    // Mark where the dragged rows are dropped.
+                    let drop_stroke = Stroke { width: 2., color: visual.selection.stroke.color };
+                    if row_drop == Some(vis_row) {
+                        ui.painter().hline(ui_max_rect.x_range(), ui_max_rect.top(), drop_stroke);
+                    } else if row_drop == Some(VisRowPos(num_vis_rows)) && vis_row.0 + 1 == num_vis_rows {
+                        ui.painter().hline(ui_max_rect.x_range(), ui_max_rect.bottom(), drop_stroke);
+                    }
+
+                    // Mark where the frozen columns and pinned rows end.
+                    if vis_col.0 + 1 == frozen_cols {
+                        ui.painter().vline(ui_max_rect.right(), ui_max_rect.y_range(), separator);
+                    }
+                    if vis_row.0 + 1 == num_pinned_rows {
+                        ui.painter().hline(ui_max_rect.x_range(), ui_max_rect.bottom(), separator);
+                    }
+
+                    if fill_handle_cell == Some(linear_index) {
+                        let handle_rect = Rect::from_min_max(
+                            ui_max_rect.right_bottom() - egui::vec2(6., 6.),
+                            ui_max_rect.right_bottom(),
+                        );
+                        ui.painter().rect_filled(handle_rect, no_rounding, visual.selection.stroke.color);
+
+                        let handle = ui.interact(handle_rect, ui.id().with("fill_handle"), Sense::drag());
+                        if handle.hovered() || handle.dragged() {
+                            ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+                        }
+                        fill_handle_pressed = handle.is_pointer_button_down_on();
+                    }
                 });
 
                 new_maximum_height = rect.height().max(new_maximum_height);
 
                 // -- Mouse Actions --
-                if check_mouse_dragging_selection(&rect, &resp) {
+                if fill_handle_pressed && !s.is_filling() {
+                    s.cci_fill_update(linear_index);
+                }
+
+                if s.is_dragging_rows() {
+                    response_consumed = true;
+                } else if s.is_filling() {
+                    // Extend the fill area to the hovered cell.
+                    response_consumed = true;
+                    if pointer_primary_down && rect.with_max_x(resp.rect.right()).contains(pointer_interact_pos) {
+                        s.cci_fill_update(linear_index);
+                    }
+                } else if check_mouse_dragging_selection(&rect, &resp) {
                     // Expand cci selection
                     response_consumed = true;
                     s.cci_sel_update(linear_index);
@@ -559,6 +734,8 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                 let editable = viewer.is_editable_cell(vis_col.0, vis_row.0, &table.rows[row_id.0]);
 
                 if editable
+                    && !s.is_filling()
+                    && !s.is_dragging_rows()
                     && (resp.clicked_by(PointerButton::Primary)
                         && (self.style.single_click_edit_mode || is_interactive_cell))
                 {
@@ -754,16 +931,21 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
 
         // Actual rendering
         if let Some(height) = self.style.table_row_height {
-            body.rows(height, cc_row_heights.len(), render_fn);
+            body.rows(height, num_shown_rows, render_fn);
         } else {
-            body.heterogeneous_rows(cc_row_heights.iter().cloned(), render_fn);
+            let heights = (0..num_shown_rows).map(|index| cc_row_heights[shown_row(index).0]);
+            body.heterogeneous_rows(heights, render_fn);
         }
 
         /* ----------------------------------- Event Handling ----------------------------------- */
 
         if ctx.input(|i| i.pointer.button_released(PointerButton::Primary)) {
             let mods = ctx.input(|i| i.modifiers);
-            if let Some(sel) = s.cci_take_selection(mods).filter(|_| !edit_started) {
+            if s.is_filling() {
+                commands.extend(s.cci_take_fill(table, viewer));
+            } else if s.is_dragging_rows() {
+                commands.extend(s.cci_take_row_drag(table));
+            } else if let Some(sel) = s.cci_take_selection(mods).filter(|_| !edit_started) {
                 commands.push(Command::CcSetSelection(sel));
             }
         }
@@ -807,7 +989,10 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
         for cmd in commands {
             match cmd {
                 Command::CcUpdateSystemClipboard(new_content) => {
-                    ctx.copy_text(new_content);
+                    // egui only copies plain text, so the HTML flavor is set directly if possible.
+                    if !system_clipboard::set_html(&new_content.html, &new_content.text) {
+                        ctx.copy_text(new_content.text);
+                    }
                 }
                 cmd => {
                     if matches!(cmd, Command::CcCommitEdit) {
@@ -823,11 +1008,7 @@ impl<'a, R, V: RowViewer<R>> Renderer<'a, R, V> {
                         table,
                         viewer,
                         cmd,
-                        if self.style.max_undo_history == 0 {
-                            100
-                        } else {
-                            self.style.max_undo_history
-                        },
+                        self.style.undo_capacity(),
                     );
                 }
             }
@@ -842,4 +1023,49 @@ impl<R, V: RowViewer<R>> Drop for Renderer<'_, R, V> {
     fn drop(&mut self) {
         self.table.ui = self.state.take();
     }
-}
\ No newline at end of file
+}
+/// Scroll input, in points, that scrolls the table by one column while it scrolls by cell.
+const CELL_SCROLL_COLUMN_WIDTH: f32 = 80.;
+
+/// Shows a scroll bar over `rect` for scrolling through `count` rows or columns by cell.
+/// Returns the new position when the bar was clicked or dragged.
+fn cell_scroll_bar(
+    ui: &egui::Ui,
+    id: egui::Id,
+    rect: Rect,
+    vertical: bool,
+    position: usize,
+    count: usize,
+) -> Option<usize> {
+    if count < 2 || !rect.is_positive() {
+        return None;
+    }
+
+    let resp = ui.interact(rect, id, Sense::click_and_drag());
+    let track = if vertical { rect.y_range() } else { rect.x_range() };
+    let thumb_len = (track.span() / count as f32).max(16.).min(track.span());
+    let travel = track.span() - thumb_len;
+    let thumb_start = track.min + travel * position as f32 / (count - 1) as f32;
+    let thumb = if vertical {
+        Rect::from_x_y_ranges(rect.x_range(), thumb_start..=thumb_start + thumb_len)
+    } else {
+        Rect::from_x_y_ranges(thumb_start..=thumb_start + thumb_len, rect.y_range())
+    };
+
+    let visuals = ui.visuals();
+    let thumb_fill = if resp.hovered() || resp.dragged() {
+        visuals.widgets.hovered.bg_fill
+    } else {
+        visuals.widgets.inactive.bg_fill
+    };
+    ui.painter().rect_filled(rect, egui::CornerRadius::ZERO, visuals.extreme_bg_color.gamma_multiply(0.5));
+    ui.painter().rect_filled(thumb, rect.width().min(rect.height()) / 2., thumb_fill);
+
+    if !(resp.clicked() || resp.dragged()) {
+        return None;
+    }
+    let pointer = resp.interact_pointer_pos()?;
+    let along = if vertical { pointer.y } else { pointer.x };
+    let fraction = ((along - track.min - thumb_len / 2.) / travel.max(1.)).clamp(0., 1.);
+    Some((fraction * (count - 1) as f32).round() as usize)
+}
@@ -8,7 +8,6 @@ use tap::prelude::Pipe;
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DecodeErrorBehavior {
     /// Skip the cell and continue decoding.
-    #[allow(dead_code)] // TODO: Validate
     SkipCell,
 
     /// Skip the whole row
@@ -95,6 +94,14 @@ pub trait RowViewer<R>: 'static {
         None::<()>
     }
 
+    /// Returns the column whose header is named `header`. If the first row of pasted content
+    /// consists of such names, it is taken as a header row and the content is pasted into the
+    /// named columns, instead of into the columns at the selection.
+    fn column_for_header(&mut self, header: &str) -> Option<usize> {
+        let _ = header;
+        None
+    }
+
     /// Returns the rendering configuration for the column.
     fn column_render_config(
         &mut self,
@@ -153,13 +160,32 @@ pub trait RowViewer<R>: 'static {
         true
     }
 
+    /// Returns if the row is pinned. Pinned rows are shown above the other rows and stay in
+    /// place when the table is scrolled. Like the filter, changes are only picked up when the
+    /// [`RowViewer::row_filter_hash`] changes.
+    fn is_pinned_row(&mut self, row: &R) -> bool {
+        let _ = row;
+        false
+    }
+
+    /// Number of visible columns, counted from the left, that stay in place together with
+    /// the row header when the table is scrolled horizontally.
+    ///
+    /// While any column is frozen or row is pinned, the table scrolls by whole rows and
+    /// columns, like a spreadsheet.
+    fn frozen_columns(&mut self) -> usize {
+        0
+    }
+
     /// Display values of the cell. Any input will be consumed before the table renderer.
     /// Therefore, any widget rendered inside here is read-only.
     ///
     /// To deal with input, use the `cell_edit` method. If you need to deal with drag/drop,
     /// see [`RowViewer::on_cell_view_response`] which delivers the resulting response of the
     /// containing cell.
-    fn show_cell_view(&mut self, ui: &mut egui::Ui, row: &R, column: usize);
+    ///
+    /// `row_idx` is the index of the row in the table, regardless of sorting and filtering.
+    fn show_cell_view(&mut self, ui: &mut egui::Ui, row_idx: usize, row: &R, column: usize);
 
     /// Use this to check if given cell is going to take any dropped payload / use as drag
     /// source.
@@ -184,8 +210,9 @@ pub trait RowViewer<R>: 'static {
     /// Set the value of a column in a row.
     fn set_cell_value(&mut self, src: &R, dst: &mut R, column: usize);
 
-    /// In the write context that happens outside of `show_cell_editor`, this method is
-    /// called on every cell value editions.
+    /// Called on every cell value edition, before it is applied. When an edition made with
+    /// `show_cell_editor` is committed, this is called for every column of the row with
+    /// [`CellWriteContext::Edit`].
     fn confirm_cell_write_by_ui(
         &mut self,
         current: &R,
@@ -235,6 +262,22 @@ pub trait RowViewer<R>: 'static {
         self.clone_row(row)
     }
 
+    /// Generates the values of the cells filled by dragging the fill handle of a selection.
+    /// `seed` holds the selected cells of one column (or row) in fill order as `(row, column)`,
+    /// and `targets` the cells to fill next. Returns a row holding the new value of each target
+    /// in its column, or `None` to leave it unchanged. By default, the seed values are repeated
+    /// within the same column.
+    fn fill_series(&mut self, seed: &[(&R, usize)], targets: &[(&R, usize)]) -> Vec<Option<R>> {
+        targets
+            .iter()
+            .enumerate()
+            .map(|(i, (_, column))| {
+                let (row, seed_column) = seed[i % seed.len()];
+                (seed_column == *column).then(|| self.clone_row_as_copied_base(row))
+            })
+            .collect()
+    }
+
     /// Called when a cell is selected/highlighted.
     fn on_highlight_cell(&mut self, row: &R, column: usize) {
         let _ = (row, column);
@@ -260,6 +303,12 @@ pub trait RowViewer<R>: 'static {
         let (_, _) = (row_index, row);
     }
 
+    /// Called when rows have been reordered by dragging their headers, including when
+    /// undoing/redoing. The row at index `i` was at index `order[i]` before.
+    fn on_rows_reordered(&mut self, order: &[usize]) {
+        let _ = order;
+    }
+
     /// Called when a rename has been committed
     fn on_rename_committed(&mut self, table: &mut DataTable<R>, target: RenameTarget, new_name: String) {
         let _ = (table, target, new_name);
@@ -280,9 +329,26 @@ pub trait RowViewer<R>: 'static {
     /// Called when a column has been removed
     fn on_column_removed(&mut self, _table: &mut DataTable<R>, _column_index: usize) {}
 
+    /// Captures the viewer-side state of the columns, e.g. their configuration. Structural
+    /// column changes are only recorded in the undo history if this returns a snapshot.
+    fn snapshot_columns(&mut self) -> Option<ColumnSnapshot> {
+        None
+    }
+
+    /// Restores a snapshot previously returned by [`RowViewer::snapshot_columns`]. Called on
+    /// undo/redo of structural column changes; the rows are restored by the table itself.
+    fn restore_columns(&mut self, _snapshot: &ColumnSnapshot) {}
+
+    /// Called when the user asks to search the table, e.g. with `Ctrl+F`. `replace` is set when
+    /// replacing was requested instead.
+    fn on_find_requested(&mut self, _replace: bool) {}
+
     /// Add custom items to the column header context menu.
     fn column_header_context_menu(&mut self, _ui: &mut egui::Ui, _column: usize) -> HeaderResult { None }
 
+    /// Add custom items to the row header context menu.
+    fn row_header_context_menu(&mut self, _ui: &mut egui::Ui, _row: usize, _row_data: &R) {}
+
     /// Called when a row header has been double-clicked.
     fn row_header_double_clicked(&mut self, ctx: &egui::Context, _row_idx: usize, _row: &R) {
         let _ = ctx;
@@ -364,8 +430,39 @@ pub enum HeaderAction {
     ShowHidden(usize),
     #[allow(dead_code)] // TODO: Validate
     RemoveColumn(usize),
+    /// The viewer changed its columns by itself, e.g. the type of a column. Holds the state
+    /// from before the change, so it can be undone.
+    ColumnsChanged(ColumnSnapshot),
+}
+
+/// Opaque viewer state of the columns, see [`RowViewer::snapshot_columns`].
+#[derive(Clone)]
+pub struct ColumnSnapshot(std::sync::Arc<dyn std::any::Any + Send + Sync>);
+
+impl ColumnSnapshot {
+    pub fn new<T: std::any::Any + Send + Sync>(value: T) -> Self {
+        Self(std::sync::Arc::new(value))
+    }
+
+    pub fn downcast_ref<T: std::any::Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl std::fmt::Debug for ColumnSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColumnSnapshot").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ColumnSnapshot {
+    fn eq(&self, other: &Self) -> bool {
+        std::sync::Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
+impl Eq for ColumnSnapshot {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum RenameTarget {
@@ -383,6 +480,15 @@ pub enum CellWriteContext {
 
     /// Value is being cleared by cut/delete operation.
     Clear,
+
+    /// Value is being committed from the cell editor.
+    Edit,
+
+    /// Value is generated by dragging the fill handle of a selection.
+    Fill,
+
+    /// Value is replaced from outside the table, e.g. by find and replace.
+    Replace,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -466,6 +572,9 @@ pub enum UiAction {
 
     SelectionDuplicateValues,
     SelectAll,
+
+    Find,
+    Replace,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -499,6 +608,7 @@ pub fn default_hotkeys(context: &UiActionContext) -> Vec<(KeyboardShortcut, UiAc
             (none, Key::Escape, UiAction::CommitEdition),
             (ctrl, Key::Escape, UiAction::CancelEdition),
             (shift, Key::Enter, CommitEditionAndMove(MD::Up)),
+            (none, Key::Enter, CommitEditionAndMove(MD::Down)),
             (ctrl, Key::Enter, CommitEditionAndMove(MD::Down)),
             (shift, Key::Tab, CommitEditionAndMove(MD::Left)),
             (none, Key::Tab, CommitEditionAndMove(MD::Right)),
@@ -521,6 +631,8 @@ pub fn default_hotkeys(context: &UiActionContext) -> Vec<(KeyboardShortcut, UiAc
             (ctrl | shift, Key::D, UiAction::DuplicateRow),
             (ctrl, Key::D, UiAction::SelectionDuplicateValues),
             (ctrl, Key::A, UiAction::SelectAll),
+            (ctrl, Key::F, UiAction::Find),
+            (ctrl, Key::H, UiAction::Replace),
             (ctrl, Key::Delete, UiAction::DeleteRow),
             (none, Key::Delete, UiAction::DeleteSelection),
             (none, Key::Backspace, UiAction::DeleteSelection),
@@ -170,7 +170,7 @@ impl RowViewer<Row> for Viewer {
         }
     }
 
-    fn show_cell_view(&mut self, ui: &mut Ui, row: &Row, column: usize) {
+    fn show_cell_view(&mut self, ui: &mut Ui, _row_idx: usize, row: &Row, column: usize) {
         match column {
             0 => ui.label(&row.0),
             1 => ui.label(row.1.to_string()),
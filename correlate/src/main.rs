@@ -1,5 +1,6 @@
-use view::RootView;
+use view::StartupView;
 
+mod arguments;
 mod data;
 mod view;
 mod codec;
@@ -12,6 +13,18 @@ fn main() {
     use eframe::App;
     env_logger::init();
 
+    let arguments = match arguments::Arguments::parse(std::env::args().skip(1)) {
+        Ok(arguments) if arguments.help => {
+            println!("{}", arguments::USAGE);
+            return;
+        }
+        Ok(arguments) => arguments,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, arguments::USAGE);
+            std::process::exit(2);
+        }
+    };
+
     let options = eframe::NativeOptions {
         centered: true,
         ..Default::default()
@@ -20,7 +33,7 @@ fn main() {
         "Correlate",
         options,
         {
-            let mut app = RootView::default();
+            let mut app = StartupView::new(arguments);
             move |ctx, frame| {
 
                 // register the fonts.
@@ -63,7 +76,7 @@ fn main() {
                     // register the fonts.
                     egui_material_icons::initialize(&_cc);
 
-                    Ok(Box::new(RootView::default()))
+                    Ok(Box::new(StartupView::new(arguments::Arguments::default())))
                 }),
             )
             .await;
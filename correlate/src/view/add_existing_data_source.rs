@@ -1,23 +1,30 @@
 ﻿use std::any::Any;
-use egui::{Context, Id};
 use crate::application_command::*;
 use crate::view::RootViewModel;
 
 pub struct AddExistingDataSource {
-    pub ctx: Context,
     pub path: std::path::PathBuf,
+    /// The project the data source is added to.
+    pub project: usize,
 }
 impl ApplicationCommand for AddExistingDataSource {
     fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        let name = self.path.file_name().unwrap_or(self.path.as_os_str());
+        format!("Add data source {}", name.to_string_lossy())
+    }
 }
 
 pub struct AddExistingDataSourceHandler;
 impl ApplicationCommandHandler for AddExistingDataSourceHandler {
-    fn handle(&self, cmd: &dyn Any) {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
         if let Some(command) = cmd.downcast_ref::<AddExistingDataSource>() {
-            let view_model_ptr = command.ctx.data(|d| d.get_temp::<usize>(Id::new("root_view_model"))).unwrap();
-            let view_model = unsafe { &mut *(view_model_ptr as *mut RootViewModel) };
-            view_model.handle_pending_file_add(command.path.clone(), 0);
+            // Recorded in the history, so it is undone from there.
+            return view_model.try_workspace_change(command.description(), |view_model| {
+                view_model.handle_pending_file_add(command.path.clone(), command.project)
+            });
         }
+        Ok(())
     }
 }
\ No newline at end of file
@@ -7,7 +7,54 @@ pub struct BottomPanel {}
 impl BottomPanel {
     pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
         egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
-            egui::Sides::new().show(ui, |_ui| {
+            egui::Sides::new().show(ui, |ui| {
+                let key_issues = &view_model.viewer.key_issues;
+                if !key_issues.is_empty() {
+                    ui.label(egui::RichText::new(format!("{} {}", egui_material_icons::icons::ICON_WARNING, key_issues.summary()))
+                        .color(ui.visuals().warn_fg_color))
+                        .on_hover_text("Rows with key issues are highlighted in the row header.");
+                }
+
+                let paste_issues = &view_model.viewer.paste_issues;
+                if !paste_issues.is_empty() {
+                    let column_configs = &view_model.viewer.column_configs;
+                    ui.label(egui::RichText::new(format!("{} {} pasted values could not be converted", egui_material_icons::icons::ICON_CONTENT_PASTE_OFF, paste_issues.len()))
+                        .color(ui.visuals().error_fg_color))
+                        .on_hover_ui(|ui| {
+                            ui.label("These cells were left unchanged:");
+                            for issue in paste_issues {
+                                let name = column_configs.get(issue.column)
+                                    .map(|c| c.display_name.as_ref().unwrap_or(&c.name).as_str())
+                                    .unwrap_or_default();
+                                ui.label(format!("Row {}, {}: '{}' {}", issue.row + 1, name, issue.value, issue.error));
+                            }
+                        });
+                    if ui.small_button(egui_material_icons::icons::ICON_CLOSE).clicked() {
+                        view_model.viewer.paste_issues.clear();
+                    }
+                }
+
+                if let Some(error) = view_model.command_errors.last() {
+                    let count = view_model.command_errors.len();
+                    let text = if count > 1 { format!("{} (and {} more)", error, count - 1) } else { error.to_string() };
+                    ui.label(egui::RichText::new(format!("{} {}", egui_material_icons::icons::ICON_ERROR, text))
+                        .color(ui.visuals().error_fg_color))
+                        .on_hover_ui(|ui| {
+                            for error in &view_model.command_errors {
+                                ui.label(error.to_string());
+                            }
+                        });
+                    if ui.small_button(egui_material_icons::icons::ICON_CLOSE).clicked() {
+                        view_model.command_errors.clear();
+                    }
+                }
+
+                if let Some(message) = view_model.viewer.validation_message.clone() {
+                    ui.label(egui::RichText::new(message).color(ui.visuals().error_fg_color));
+                    if ui.small_button(egui_material_icons::icons::ICON_CLOSE).clicked() {
+                        view_model.viewer.validation_message = None;
+                    }
+                }
             }, |ui|{
                 let mut has_modifications = view_model.table.has_user_modification();
                 ui.add_enabled(false, egui::Checkbox::new(&mut has_modifications, "Has modifications"));
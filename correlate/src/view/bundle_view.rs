@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+use egui::Id;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::*;
+
+/// A project bundle to import, waiting to be checked for conflicts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleImport {
+    pub bundle: PathBuf,
+    pub folder: PathBuf,
+}
+
+impl BundleImport {
+    fn id() -> Id {
+        Id::new("bundle_import")
+    }
+
+    /// Asks for the bundle and the folder to import it into.
+    pub fn pick() -> Option<Self> {
+        let bundle = rfd::FileDialog::new()
+            .add_filter("Correlate project bundle", &[BUNDLE_EXTENSION])
+            .pick_file()?;
+        let folder = rfd::FileDialog::new()
+            .set_title("Import into folder")
+            .pick_folder()?;
+        Some(Self { bundle, folder })
+    }
+
+    /// Imports the bundle, after asking what to do about conflicts.
+    pub fn request(self, ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(Self::id(), Some(self)));
+    }
+
+    fn take(ctx: &egui::Context) -> Option<Self> {
+        ctx.data_mut(|d| d.remove_temp::<Option<Self>>(Self::id())).flatten()
+    }
+
+    fn command(&self, overwrite: bool) -> Box<dyn ApplicationCommand> {
+        Box::new(ImportProjectBundle { bundle: self.bundle.clone(), folder: self.folder.clone(), overwrite })
+    }
+}
+
+/// Asks what to do when importing a project bundle would replace files.
+#[derive(Default)]
+pub struct BundleView {
+    pending: Option<(BundleImport, Vec<BundleConflict>)>,
+}
+
+impl BundleView {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        if let Some(import) = BundleImport::take(ctx) {
+            let bundle = ProjectBundle { path: import.bundle.clone() };
+            match bundle.conflicts(&import.folder, &ImportProjectBundleHandler::open_paths(view_model)) {
+                Ok(conflicts) if conflicts.is_empty() => enqueue_command(ctx, import.command(false)),
+                Ok(conflicts) => self.pending = Some((import, conflicts)),
+                Err(reason) => view_model.command_errors.push(CommandError::Failed { command: "Import project bundle".to_string(), reason }),
+            }
+        }
+
+        let Some((import, conflicts)) = &self.pending else {
+            return;
+        };
+        let blocked = conflicts.iter().any(|c| matches!(c, BundleConflict::Open(_)));
+        let mut overwrite = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(Id::new("bundle_conflicts_modal")).show(ctx, |ui| {
+            ui.set_width(420.0);
+            ui.heading("Import project bundle");
+            if blocked {
+                ui.label("Some files of the bundle are open. Close them or import into another folder.");
+            } else {
+                ui.label(format!("Importing into {} overwrites these files:", import.folder.display()));
+            }
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for conflict in conflicts {
+                    let label = match conflict {
+                        BundleConflict::Open(path) => format!("{} (open)", path.display()),
+                        BundleConflict::Exists(path) => path.display().to_string(),
+                    };
+                    ui.label(label);
+                }
+            });
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                if !blocked {
+                    overwrite = ui.button("Overwrite").clicked();
+                }
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+
+        if overwrite {
+            enqueue_command(ctx, import.command(true));
+        }
+        if overwrite || cancelled || modal.should_close() {
+            self.pending = None;
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::data::*;
 use crate::view::*;
 use crate::egui_data_table::*;
 use crate::application_command::*;
@@ -17,8 +18,6 @@ impl CentralPanel {
               view_model: &mut RootViewModel,
               ctx: &egui::Context) {
 
-        ctx.data_mut(|d| d.insert_temp(egui::Id::new("root_view_model"), view_model as *mut RootViewModel as usize));
-
         egui::CentralPanel::default()
             .show(ctx, |ui| {
 
@@ -28,7 +27,35 @@ impl CentralPanel {
                     if ui.button(egui_material_icons::icons::ICON_PAGE_INFO).clicked() {}
                     if ui.button(egui_material_icons::icons::ICON_SWAP_VERT).clicked() {}
                     if ui.button(egui_material_icons::icons::ICON_FILTER_LIST).clicked() {
-                        enqueue_ui_command(ui, Box::new(ToggleScrollBarVisibility { ctx: ctx.clone() }));
+                        enqueue_ui_command(ui, Box::new(ToggleScrollBarVisibility));
+                    }
+
+                    if ui.button(egui_material_icons::icons::ICON_SEARCH).on_hover_text("Find and replace (Ctrl+F)").clicked() {
+                        view_model.viewer.find_requested = Some(false);
+                    }
+
+                    let density = view_model.viewer.row_density;
+                    ui.menu_button(density.icon(), |ui| {
+                        for option in RowDensity::ALL {
+                            if ui.radio_value(&mut view_model.viewer.row_density, option, format!("{} {}", option.icon(), option.label())).clicked() {
+                                view_model.viewer.layout_changed = true;
+                                ui.close();
+                            }
+                        }
+                    }).response.on_hover_text("Row density");
+
+                    ui.toggle_value(&mut view_model.show_history, egui_material_icons::icons::ICON_HISTORY)
+                        .on_hover_text("Show the history of changes across all sheets");
+
+                    let problem_count = view_model.viewer.problems.len();
+                    ui.toggle_value(&mut view_model.show_problems, format!("{} {}", egui_material_icons::icons::ICON_ERROR, problem_count))
+                        .on_hover_text("Show the cells that violate a validation rule");
+
+                    let column_configs = &view_model.viewer.column_configs;
+                    if column_configs.iter().any(|c| c.is_virtual) && ColumnConfiguration::key_column_indices(column_configs).is_empty() {
+                        ui.label(egui::RichText::new(egui_material_icons::icons::ICON_WARNING).color(ui.visuals().warn_fg_color))
+                            .on_hover_text("This sheet has no key column. Virtual cell values are matched by the content of their row, \
+                                and are lost when that row is changed outside of Correlate. Mark a column as key to keep them reliably.");
                     }
                 });
 
@@ -43,23 +70,24 @@ impl CentralPanel {
 
                 //let available = ui.available_size();
 
+                let style = Style { min_row_height: view_model.viewer.row_density.row_height(), ..view_model.style_override };
                 ui.add(
                     //available,
-                    Renderer::new(&mut view_model.table, &mut view_model.viewer).with_style(view_model.style_override),
+                    Renderer::new(&mut view_model.table, &mut view_model.viewer).with_style(style),
                 );
 
                 if view_model.table.has_user_modification() {
-                    enqueue_ui_command(ui, Box::new(ClearUserModificationFlag { ctx: ctx.clone() }));
+                    enqueue_ui_command(ui, Box::new(ClearUserModificationFlag));
                 }
             });
         });
     }
 
     pub fn ui_row_context_menu(viewer: &mut RowView, ui: &mut egui::Ui, column: usize) {
-        if let Some(config) = viewer.column_configs.get_mut(column) {
+        if let Some(config) = viewer.column_configs.get(column) {
             let mut is_key = config.is_key;
-            if ui.checkbox(&mut is_key, "Is key").clicked() {
-                config.is_key = is_key;
+            if ui.add_enabled(!config.is_virtual, egui::Checkbox::new(&mut is_key, "Is key")).clicked() {
+                ColumnConfiguration::set_key(&mut viewer.column_configs, column, is_key);
                 // Reset the table to force redrawing with new header names
                 ui.ctx().memory_mut(|_mem| {
                     // This is a hacky way to force a full redrawing of the table
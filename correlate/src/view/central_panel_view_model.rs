@@ -18,6 +18,8 @@ impl CentralPanelViewModel {
         // Handle column reordering from the data table
         Self::handle_column_reordering(view_model);
 
+        Self::validate(view_model);
+
         let column_count_changed = view_model.table.is_empty() || RowViewer::num_columns(&mut view_model.viewer) != view_model.table[0].cells.len();
         if column_count_changed {
             // Update all rows in the table if needed (e.g., loading from the file with virtual columns)
@@ -33,6 +35,36 @@ impl CentralPanelViewModel {
             // Save state back to DataSource
             view_model.save_datasource_configuration();
         }
+
+        if std::mem::take(&mut view_model.viewer.config_changed) {
+            view_model.save_datasource_configuration();
+        }
+
+        if std::mem::take(&mut view_model.viewer.layout_changed) {
+            view_model.save_sheet_layout();
+        }
+
+        if std::mem::take(&mut view_model.viewer.rows_reordered) {
+            view_model.keep_manual_row_order();
+        }
+
+        view_model.sync_trashed_columns();
+        view_model.record_history();
+    }
+
+    fn validate(view_model: &mut RootViewModel) {
+        // Revalidate when the key columns or rules change, the data is (re)loaded or the table was modified.
+        let modifications = view_model.table.modifications();
+        let key_columns = ColumnConfiguration::key_column_indices(&view_model.viewer.column_configs);
+        if view_model.viewer.key_issues.is_stale(&key_columns, modifications) {
+            view_model.viewer.key_issues = KeyIssues::validate(&key_columns, &view_model.table, modifications);
+        }
+        if view_model.viewer.problems.is_stale(&view_model.viewer.column_configs, modifications) {
+            view_model.viewer.problems = Problems::validate(&view_model.viewer.column_configs, &view_model.table, modifications);
+        }
+        if view_model.viewer.formatting.is_stale(modifications) {
+            view_model.viewer.formatting.update_ranges(&view_model.viewer.column_configs, &view_model.table, modifications);
+        }
     }
 
     fn handle_column_reordering(view_model: &mut RootViewModel) {
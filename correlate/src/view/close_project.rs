@@ -0,0 +1,41 @@
+use std::any::Any;
+use crate::application_command::*;
+use crate::view::RootViewModel;
+
+/// Closes a project, so it is no longer opened on startup but stays among the recent projects.
+pub struct CloseProject {
+    pub project: usize,
+    /// Whether the project is saved to its project file first.
+    pub save: bool,
+}
+
+impl ApplicationCommand for CloseProject {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        "Close project".to_string()
+    }
+}
+
+pub struct CloseProjectHandler;
+
+impl ApplicationCommandHandler for CloseProjectHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<CloseProject>() {
+            let name = view_model.config.projects.iter().flatten().nth(command.project)
+                .map(|p| p.name.clone())
+                .ok_or_else(|| format!("There is no project {} to close", command.project))?;
+
+            if command.save {
+                view_model.update_project_metadata();
+                view_model.config.save_project(command.project)?;
+            }
+
+            // Recorded in the history, so it is opened again from there.
+            return view_model.try_workspace_change(format!("Close project {}", name), |view_model| {
+                view_model.close_project(command.project)
+            });
+        }
+        Ok(())
+    }
+}
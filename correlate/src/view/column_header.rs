@@ -1,7 +1,6 @@
 use crate::data::*;
 use crate::view::*;
 use crate::egui_data_table::*;
-use crate::application_command::*;
 use std::borrow::Cow;
 use egui::Key;
 
@@ -20,11 +19,16 @@ impl<'a> ColumnHeader<'a> {
     }
 
     pub fn name(&self, column: usize) -> Cow<'static, str> {
+        let key_columns = ColumnConfiguration::key_column_indices(self.column_configs);
         self.column_configs.get(column)
             .map(|c| {
                 let mut name = c.display_name.as_ref().unwrap_or(&c.name).clone();
-                if c.is_key {
-                    name = format!("{} {}", name, egui_material_icons::icons::ICON_KEY);
+                if let Some(pos) = key_columns.iter().position(|&i| i == column) {
+                    name = if key_columns.len() > 1 {
+                        format!("{} {}{}", name, egui_material_icons::icons::ICON_KEY, pos + 1)
+                    } else {
+                        format!("{} {}", name, egui_material_icons::icons::ICON_KEY)
+                    };
                 }
                 if c.is_name {
                     name = format!("{} {}", name, egui_material_icons::icons::ICON_VISIBILITY);
@@ -45,18 +49,32 @@ impl<'a> ColumnHeader<'a> {
         ui.add(egui::Label::new(self.name(column)).selectable(false));
     }
 
-    pub fn context_menu(&mut self, ui: &mut egui::Ui, column: usize, view_model: &mut RootViewModel) -> HeaderResult {
+    /// Shows the menu of a column. `config_changed` is set when the configuration of the data
+    /// source needs to be saved.
+    pub fn context_menu(
+        &mut self,
+        ui: &mut egui::Ui,
+        column: usize,
+        frozen: &mut FrozenPanes,
+        data_sources: &[DataSource],
+        formatting: &mut ConditionalFormatting,
+        config_changed: &mut bool,
+    ) -> HeaderResult {
         let mut action = None;
 
         self.show_rename_section(ui, column, &mut action);
         ui.separator();
 
-        self.show_relation_section(ui, column, &view_model.data_sources, &mut action);
-        self.show_change_type_section(ui, column, &mut action);
+        self.show_relation_section(ui, column, data_sources, &mut action);
+        self.show_change_type_section(ui, column, &formatting.formats, &mut action);
+        self.show_validation_section(ui, column, &mut action);
+        self.show_formatting_section(ui, column, formatting, &mut action);
 
         ui.separator();
 
-        self.show_filter_sort_hide_section(ui, column, &mut action, view_model);
+        self.show_filter_sort_hide_section(ui, column, &mut action, config_changed);
+        self.show_freeze_section(ui, column, frozen);
+        self.show_wrap_toggle(ui, column, &mut action);
         self.show_key_name_toggles(ui, column, &mut action);
 
         ui.separator();
@@ -120,7 +138,7 @@ impl<'a> ColumnHeader<'a> {
         }
     }
 
-    fn show_change_type_section(&mut self, ui: &mut egui::Ui, column: usize, action: &mut HeaderResult) {
+    fn show_change_type_section(&mut self, ui: &mut egui::Ui, column: usize, formats: &[ConditionalFormat], action: &mut HeaderResult) {
         ui.menu_button(format!("{} Change type", egui_material_icons::icons::ICON_EDIT_SQUARE), |ui| {
             let current_type = self.column_configs[column].column_type;
 
@@ -130,7 +148,7 @@ impl<'a> ColumnHeader<'a> {
                 ColumnType::DateTime,
             ];
 
-            self.update_virtual_columns(types, current_type, column, action, ui);
+            self.update_virtual_columns(types, current_type, column, formats, action, ui);
 
             let is_virtual = self.column_configs[column].is_virtual;
             ui.add_enabled_ui(is_virtual, |ui| {
@@ -139,19 +157,182 @@ impl<'a> ColumnHeader<'a> {
                     ColumnType::MultiSelect,
                     ColumnType::Relation,
                 ];
-                self.update_virtual_columns(virtual_types, current_type, column, action, ui);
+                self.update_virtual_columns(virtual_types, current_type, column, formats, action, ui);
             });
 
             self.show_disabled_types(ui);
         });
     }
 
-    fn update_virtual_columns(&mut self, types: Vec<ColumnType>, current_type: ColumnType, column: usize, action: &mut HeaderResult, ui: &mut egui::Ui) {
+    fn show_validation_section(&mut self, ui: &mut egui::Ui, column: usize, action: &mut HeaderResult) {
+        ui.menu_button(format!("{} Validation", egui_material_icons::icons::ICON_RULE), |ui| {
+            let config = &mut self.column_configs[column];
+            let column_type = config.column_type;
+            let validation = &mut config.validation;
+            let mut changed = false;
+
+            changed |= ui.checkbox(&mut validation.required, "Required").changed();
+            changed |= ui.checkbox(&mut validation.unique, "Unique").changed();
+            if matches!(column_type, ColumnType::Select | ColumnType::MultiSelect) {
+                changed |= ui.checkbox(&mut validation.allowed_values_only, "Allowed values only").changed();
+            }
+
+            ui.separator();
+            changed |= Self::optional_text(ui, "Pattern", &mut validation.pattern);
+            if column_type == ColumnType::Number {
+                changed |= Self::optional_number(ui, "Minimum", &mut validation.min);
+                changed |= Self::optional_number(ui, "Maximum", &mut validation.max);
+            }
+            if column_type == ColumnType::DateTime {
+                changed |= Self::optional_text(ui, "From date", &mut validation.date_from);
+                changed |= Self::optional_text(ui, "To date", &mut validation.date_to);
+            }
+
+            ui.separator();
+            changed |= ui.checkbox(&mut validation.reject_invalid, "Reject invalid values")
+                .on_hover_text("If checked, edits and pastes that violate a rule are rejected instead of marked.")
+                .changed();
+
+            if changed {
+                *action = Some(HeaderAction::RequestSave);
+            }
+        });
+    }
+
+    fn show_formatting_section(&mut self, ui: &mut egui::Ui, column: usize, formatting: &mut ConditionalFormatting, action: &mut HeaderResult) {
+        ui.menu_button(format!("{} Conditional formatting", egui_material_icons::icons::ICON_FORMAT_PAINT), |ui| {
+            let config = &self.column_configs[column];
+            let column_name = config.name.clone();
+            let column_type = config.column_type;
+            let allowed_values = config.allowed_values.clone().unwrap_or_default();
+            let mut changed = false;
+            let mut remove = None;
+
+            for (idx, format) in formatting.formats.iter_mut().enumerate().filter(|(_, f)| f.column_name == column_name) {
+                ui.push_id(idx, |ui| {
+                    ui.horizontal(|ui| {
+                        match &mut format.kind {
+                            FormatKind::Condition { condition, target, style } => {
+                                ui.label(condition.label());
+                                match condition {
+                                    FormatCondition::GreaterThan(x) | FormatCondition::LessThan(x) => {
+                                        changed |= ui.add(egui::DragValue::new(x).speed(0.1)).changed();
+                                    }
+                                    FormatCondition::Equals(text) if !allowed_values.is_empty() => {
+                                        egui::ComboBox::from_id_salt("value").selected_text(text.as_str()).show_ui(ui, |ui| {
+                                            for av in &allowed_values {
+                                                changed |= ui.selectable_value(text, av.value.clone(), &av.value).changed();
+                                            }
+                                        });
+                                    }
+                                    FormatCondition::Contains(text) | FormatCondition::Equals(text) => {
+                                        changed |= ui.add(egui::TextEdit::singleline(text).desired_width(80.0)).changed();
+                                    }
+                                    FormatCondition::DateInPast => {}
+                                }
+                                egui::ComboBox::from_id_salt("target").selected_text(format!("{:?}", target)).show_ui(ui, |ui| {
+                                    changed |= ui.selectable_value(target, FormatTarget::Cell, "Cell").changed();
+                                    changed |= ui.selectable_value(target, FormatTarget::Row, "Row").changed();
+                                });
+                                changed |= Self::optional_color(ui, "Background", &mut style.background);
+                                changed |= Self::optional_color(ui, "Text", &mut style.text_color);
+                                changed |= ui.checkbox(&mut style.bold, "Bold").changed();
+                            }
+                            FormatKind::ColorScale { min_color, max_color } => {
+                                ui.label("Color scale");
+                                changed |= ui.color_edit_button_srgb(min_color).changed();
+                                changed |= ui.color_edit_button_srgb(max_color).changed();
+                            }
+                            FormatKind::DataBar { color } => {
+                                ui.label("Data bar");
+                                changed |= ui.color_edit_button_srgb(color).changed();
+                            }
+                        }
+                        if ui.button(egui_material_icons::icons::ICON_DELETE).clicked() {
+                            remove = Some(idx);
+                        }
+                    });
+                });
+            }
+
+            if let Some(idx) = remove {
+                formatting.formats.remove(idx);
+                changed = true;
+            }
+
+            ui.menu_button(format!("{} Add rule", egui_material_icons::icons::ICON_ADD), |ui| {
+                let mut add = |kind| {
+                    formatting.formats.push(ConditionalFormat { column_name: column_name.clone(), kind });
+                    changed = true;
+                };
+                for condition in FormatCondition::available_for(column_type) {
+                    if ui.button(condition.label()).clicked() {
+                        let style = CellStyle { background: Some([255, 235, 156]), ..Default::default() };
+                        add(FormatKind::Condition { condition, target: FormatTarget::Cell, style });
+                    }
+                }
+                if column_type == ColumnType::Number {
+                    ui.separator();
+                    if ui.button("Color scale").clicked() {
+                        add(FormatKind::ColorScale { min_color: [248, 105, 107], max_color: [99, 190, 123] });
+                    }
+                    if ui.button("Data bar").clicked() {
+                        add(FormatKind::DataBar { color: [99, 142, 198] });
+                    }
+                }
+            });
+
+            if changed {
+                *action = Some(HeaderAction::RequestSave);
+            }
+        });
+    }
+
+    fn optional_color(ui: &mut egui::Ui, label: &str, color: &mut Option<[u8; 3]>) -> bool {
+        let mut enabled = color.is_some();
+        let mut changed = ui.checkbox(&mut enabled, label).changed();
+        if changed {
+            *color = enabled.then_some([255, 235, 156]);
+        }
+        if let Some(color) = color {
+            changed |= ui.color_edit_button_srgb(color).changed();
+        }
+        changed
+    }
+
+    fn optional_text(ui: &mut egui::Ui, label: &str, value: &mut Option<String>) -> bool {
+        ui.horizontal(|ui| {
+            ui.label(label);
+            let mut text = value.clone().unwrap_or_default();
+            let changed = ui.text_edit_singleline(&mut text).changed();
+            if changed {
+                *value = Some(text).filter(|t| !t.is_empty());
+            }
+            changed
+        }).inner
+    }
+
+    fn optional_number(ui: &mut egui::Ui, label: &str, value: &mut Option<f64>) -> bool {
+        ui.horizontal(|ui| {
+            let mut enabled = value.is_some();
+            let mut changed = ui.checkbox(&mut enabled, label).changed();
+            if changed {
+                *value = enabled.then_some(0.0);
+            }
+            if let Some(number) = value {
+                changed |= ui.add(egui::DragValue::new(number).speed(0.1)).changed();
+            }
+            changed
+        }).inner
+    }
+
+    fn update_virtual_columns(&mut self, types: Vec<ColumnType>, current_type: ColumnType, column: usize, formats: &[ConditionalFormat], action: &mut HeaderResult, ui: &mut egui::Ui) {
         for t in types {
             let mut is_selected = current_type == t;
             if ui.checkbox(&mut is_selected, format!("{} {:?}", t.icon(), t)).clicked() {
+                let before = RowView::column_snapshot(self.column_configs, formats);
                 self.column_configs[column].column_type = t;
-                *action = Some(HeaderAction::RequestSave);
+                *action = Some(HeaderAction::ColumnsChanged(before));
             }
         }
     }
@@ -179,7 +360,7 @@ impl<'a> ColumnHeader<'a> {
         });
     }
 
-    fn show_filter_sort_hide_section(&mut self, ui: &mut egui::Ui, column: usize, action: &mut HeaderResult, view_model: &mut RootViewModel) {
+    fn show_filter_sort_hide_section(&mut self, ui: &mut egui::Ui, column: usize, action: &mut HeaderResult, config_changed: &mut bool) {
         if ui.button(format!("{} Filter", egui_material_icons::icons::ICON_FILTER_LIST)).clicked() {
             ui.close();
         }
@@ -199,7 +380,7 @@ impl<'a> ColumnHeader<'a> {
 
         if ui.button(format!("{} Hide", egui_material_icons::icons::ICON_VISIBILITY_OFF)).clicked() {
             self.column_configs[column].is_visible = false;
-            view_model.save_datasource_configuration();
+            *config_changed = true;
             *action = Some(HeaderAction::HideColumn(column));
             ui.close();
         }
@@ -214,7 +395,7 @@ impl<'a> ColumnHeader<'a> {
                     for idx in hidden {
                         if ui.button(self.name(idx)).clicked() {
                             self.column_configs[idx].is_visible = true;
-                            view_model.save_datasource_configuration();
+                            *config_changed = true;
                             *action = Some(HeaderAction::ShowHidden(idx));
                             ui.close();
                         }
@@ -225,17 +406,57 @@ impl<'a> ColumnHeader<'a> {
         ui.separator();
     }
 
+    fn show_freeze_section(&self, ui: &mut egui::Ui, column: usize, frozen: &mut FrozenPanes) {
+        let position = self.visible_columns.as_ref().and_then(|vis| vis.iter().position(|&c| c == column));
+        if let Some(position) = position
+            && ui.button(format!("{} Freeze up to this column", egui_material_icons::icons::ICON_SPLITSCREEN_LEFT)).clicked() {
+            frozen.columns = position + 1;
+            ui.close();
+        }
+        if frozen.columns > 0 && ui.button("Unfreeze columns").clicked() {
+            frozen.columns = 0;
+            ui.close();
+        }
+        ui.separator();
+    }
+
+    fn show_wrap_toggle(&mut self, ui: &mut egui::Ui, column: usize, action: &mut HeaderResult) {
+        let config = &mut self.column_configs[column];
+        if config.column_type != ColumnType::Bool
+            && ui.checkbox(&mut config.wrap_text, format!("{} Wrap text", egui_material_icons::icons::ICON_WRAP_TEXT)).clicked() {
+            *action = Some(HeaderAction::RequestSave);
+            ui.close();
+        }
+    }
+
     fn show_key_name_toggles(&mut self, ui: &mut egui::Ui, column: usize, action: &mut HeaderResult) {
         let is_name_active = self.column_configs[column].is_name;
         let is_key_active = self.column_configs[column].is_key;
 
+        let is_virtual = self.column_configs[column].is_virtual;
         let mut is_key = is_key_active;
-        if ui.checkbox(&mut is_key, "Use as key").clicked() {
-            self.column_configs[column].is_key = is_key;
+        if ui.add_enabled(!is_virtual, egui::Checkbox::new(&mut is_key, "Use as key")).clicked() {
+            ColumnConfiguration::set_key(self.column_configs, column, is_key);
             *action = Some(HeaderAction::RequestSave);
             ui.close();
         }
 
+        // With a composite key, allow changing the position of this column within the key.
+        let key_columns = ColumnConfiguration::key_column_indices(self.column_configs);
+        if let Some(pos) = key_columns.iter().position(|&i| i == column).filter(|_| key_columns.len() > 1) {
+            ui.horizontal(|ui| {
+                ui.label(format!("Key part {} of {}", pos + 1, key_columns.len()));
+                if ui.add_enabled(pos > 0, egui::Button::new(egui_material_icons::icons::ICON_ARROW_BACK)).clicked() {
+                    ColumnConfiguration::move_key(self.column_configs, column, -1);
+                    *action = Some(HeaderAction::RequestSave);
+                }
+                if ui.add_enabled(pos + 1 < key_columns.len(), egui::Button::new(egui_material_icons::icons::ICON_ARROW_FORWARD)).clicked() {
+                    ColumnConfiguration::move_key(self.column_configs, column, 1);
+                    *action = Some(HeaderAction::RequestSave);
+                }
+            });
+        }
+
         let mut is_name = is_name_active;
         if ui.checkbox(&mut is_name, "Use as name").clicked() {
             for c in self.column_configs.iter_mut() {
@@ -281,7 +502,7 @@ impl<'a> ColumnHeader<'a> {
         let is_virtual = self.column_configs[column].is_virtual;
         ui.add_enabled_ui(is_virtual, |ui| {
             if ui.button(format!("{} Trash", egui_material_icons::icons::ICON_DELETE)).clicked() {
-                enqueue_ui_command(ui, Box::new(TrashColumn { column, ctx: ui.ctx().clone() }));
+                TrashConfirmation::Column(column).request(ui.ctx());
                 ui.close();
             }
         });
@@ -0,0 +1,263 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+use crate::application_command::*;
+use crate::data::*;
+use crate::egui_data_table::*;
+use crate::view::*;
+
+enum PaletteAction {
+    Table(UiAction),
+    Command(Box<dyn ApplicationCommand>),
+    /// Asks for a file first, and adds it to the first project.
+    AddExistingDataSource,
+    /// Asks for the project file first.
+    OpenProject,
+    /// Asks where to save the project first.
+    SaveProjectAs(usize),
+    /// Asks where to export the project to first.
+    ExportProjectBundle(usize),
+    /// Asks for the bundle and the folder to import it into first.
+    ImportProjectBundle,
+    /// Sorts by a column, ascending or not, or clears the sort.
+    Sort(Option<(usize, bool)>),
+    /// Asks where to export the current sheet to first.
+    ExportSheet,
+    /// Asks for confirmation first.
+    Trash(TrashConfirmation),
+    /// Asks whether to save the project first when it has unsaved changes.
+    CloseProject(usize),
+}
+
+struct PaletteEntry {
+    label: String,
+    shortcut: Option<KeyboardShortcut>,
+    action: PaletteAction,
+}
+
+impl PaletteEntry {
+    fn new(label: impl Into<String>, action: PaletteAction) -> Self {
+        Self { label: label.into(), shortcut: None, action }
+    }
+
+    fn command(label: impl Into<String>, command: impl ApplicationCommand) -> Self {
+        Self::new(label, PaletteAction::Command(Box::new(command)))
+    }
+}
+
+/// Lists every action of the table and the application, found by typing part of its name.
+#[derive(Default)]
+pub struct CommandPaletteView {
+    open: bool,
+    query: String,
+    /// Position of the highlighted entry among the matching entries.
+    selected: usize,
+    focus_query: bool,
+    recent: RecentEntries,
+}
+
+impl CommandPaletteView {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        let shortcuts = view_model.config.shortcuts.shortcuts(ShortcutTarget::CommandPalette);
+        if ctx.input_mut(|i| shortcuts.iter().any(|shortcut| i.consume_shortcut(shortcut))) {
+            self.open = !self.open;
+            self.query.clear();
+            self.selected = 0;
+            self.focus_query = true;
+        }
+        if !self.open {
+            return;
+        }
+
+        let mut entries = Self::entries(view_model);
+        let order = self.recent.filter(&self.query, entries.iter().map(|e| e.label.as_str()));
+        let (up, down) = ctx.input_mut(|i| (
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+        ));
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down {
+            self.selected += 1;
+        }
+        self.selected = self.selected.min(order.len().saturating_sub(1));
+
+        let mut chosen = None;
+        let modal = egui::Modal::new(egui::Id::new("command_palette")).show(ctx, |ui| {
+            ui.set_width(440.0);
+
+            let response = ui.add(egui::TextEdit::singleline(&mut self.query)
+                .hint_text("Type a command")
+                .desired_width(f32::INFINITY));
+            if std::mem::take(&mut self.focus_query) {
+                response.request_focus();
+            }
+            if response.changed() {
+                self.selected = 0;
+            }
+            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                chosen = order.get(self.selected).copied();
+            }
+            ui.separator();
+
+            if order.is_empty() {
+                ui.weak("No matching commands");
+            }
+            egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                for (position, &index) in order.iter().enumerate() {
+                    let entry = &entries[index];
+                    let highlighted = position == self.selected;
+                    let response = ui.horizontal(|ui| {
+                        let response = ui.selectable_label(highlighted, &entry.label);
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if let Some(shortcut) = &entry.shortcut {
+                                ui.weak(ui.ctx().format_shortcut(shortcut));
+                            } else if self.recent.rank(&entry.label).is_some() {
+                                ui.weak("recent");
+                            }
+                        });
+                        response
+                    }).inner;
+                    if highlighted && (up || down) {
+                        response.scroll_to_me(None);
+                    }
+                    if response.clicked() {
+                        chosen = Some(index);
+                    }
+                }
+            });
+        });
+
+        if let Some(index) = chosen {
+            let entry = entries.swap_remove(index);
+            self.recent.push(&entry.label);
+            self.open = false;
+            Self::run(entry.action, view_model, ctx);
+        } else if modal.should_close() {
+            self.open = false;
+        }
+    }
+
+    fn run(action: PaletteAction, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        match action {
+            PaletteAction::Table(action) => {
+                view_model.table.queue_action(action);
+            }
+            PaletteAction::Command(command) => enqueue_command(ctx, command),
+            PaletteAction::Sort(sort) => view_model.table.set_sort(&mut view_model.viewer, sort),
+            PaletteAction::ExportSheet => {
+                if let Some(command) = ExportSheet::pick(view_model) {
+                    enqueue_command(ctx, Box::new(command));
+                }
+            }
+            PaletteAction::Trash(confirmation) => confirmation.request(ctx),
+            PaletteAction::CloseProject(project) => UnsavedProjectsView::request_close(ctx, project),
+            PaletteAction::AddExistingDataSource => {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Excel Files", &["xlsx"])
+                    .add_filter("CSV Files", &["csv"])
+                    .pick_file()
+                {
+                    enqueue_command(ctx, Box::new(AddExistingDataSource { path, project: 0 }));
+                }
+            }
+            PaletteAction::OpenProject => {
+                if let Some(command) = OpenProject::pick() {
+                    enqueue_command(ctx, Box::new(command));
+                }
+            }
+            PaletteAction::SaveProjectAs(project) => {
+                if let Some(command) = view_model.config.projects.iter().flatten().nth(project)
+                    .and_then(|configuration| SaveProject::pick(project, configuration)) {
+                    enqueue_command(ctx, Box::new(command));
+                }
+            }
+            PaletteAction::ExportProjectBundle(project) => {
+                if let Some(command) = view_model.config.projects.iter().flatten().nth(project)
+                    .and_then(|configuration| ExportProjectBundle::pick(project, configuration)) {
+                    enqueue_command(ctx, Box::new(command));
+                }
+            }
+            PaletteAction::ImportProjectBundle => {
+                if let Some(import) = BundleImport::pick() {
+                    import.request(ctx);
+                }
+            }
+        }
+    }
+
+    fn entries(view_model: &RootViewModel) -> Vec<PaletteEntry> {
+        let mut entries = Vec::new();
+
+        // Sheets
+        for (index, ds) in view_model.data_sources.iter().enumerate() {
+            let source_name = ds.name.as_ref().unwrap_or(&ds.path);
+            for (sheet_idx, sheet) in ds.sheets.iter().enumerate() {
+                let label = if ds.sheets.len() == 1 {
+                    format!("Go to {}", source_name)
+                } else {
+                    format!("Go to {} > {}", source_name, sheet.display_name.as_ref().unwrap_or(&sheet.name))
+                };
+                entries.push(PaletteEntry::command(label, SwitchToSource { index, sheet_idx }));
+            }
+        }
+
+        // Table
+        let bindings = &view_model.config.shortcuts;
+        if view_model.selected_index.is_some() {
+            for action in TABLE_ACTIONS {
+                let mut entry = PaletteEntry::new(table_action_label(action), PaletteAction::Table(action));
+                entry.shortcut = bindings.shortcuts(ShortcutTarget::Table(action)).first().copied();
+                entries.push(entry);
+            }
+            entries.push(PaletteEntry::new("Clear sort", PaletteAction::Sort(None)));
+            entries.push(PaletteEntry::new("Export sheet as CSV…", PaletteAction::ExportSheet));
+        }
+
+        // Columns
+        for (column, config) in view_model.viewer.column_configs.iter().enumerate() {
+            let name = config.display_name.as_ref().unwrap_or(&config.name);
+            let label = if config.is_visible { format!("Hide column {}", name) } else { format!("Show column {}", name) };
+            entries.push(PaletteEntry::command(label, SetColumnVisibility { column, visible: !config.is_visible }));
+            entries.push(PaletteEntry::new(format!("Sort by {} ascending", name), PaletteAction::Sort(Some((column, true)))));
+            entries.push(PaletteEntry::new(format!("Sort by {} descending", name), PaletteAction::Sort(Some((column, false)))));
+            if config.is_virtual {
+                entries.push(PaletteEntry::new(format!("Trash column {}", name), PaletteAction::Trash(TrashConfirmation::Column(column))));
+            }
+        }
+
+        // Projects and data sources
+        let mut add_project = PaletteEntry::command("Add project", AddProject);
+        add_project.shortcut = bindings.shortcuts(ShortcutTarget::AddProject).first().copied();
+        entries.push(add_project);
+        entries.push(PaletteEntry::new("Add existing data source…", PaletteAction::AddExistingDataSource));
+        entries.push(PaletteEntry::new("Open project…", PaletteAction::OpenProject));
+        entries.push(PaletteEntry::new("Import project bundle…", PaletteAction::ImportProjectBundle));
+        for recent in view_model.config.recent_projects().into_iter().filter(|r| !r.open) {
+            let path = std::path::PathBuf::from(recent.path);
+            let name = path.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            entries.push(PaletteEntry::command(format!("Open recent project {}", name), OpenProject { path }));
+        }
+        for (project, config) in view_model.config.projects.iter().flatten().enumerate() {
+            entries.push(PaletteEntry::command(format!("Save project {}", config.name), SaveProject { project, path: None }));
+            entries.push(PaletteEntry::new(format!("Save project {} as…", config.name), PaletteAction::SaveProjectAs(project)));
+            entries.push(PaletteEntry::new(format!("Export project {} as bundle…", config.name), PaletteAction::ExportProjectBundle(project)));
+            entries.push(PaletteEntry::new(format!("Close project {}", config.name), PaletteAction::CloseProject(project)));
+            entries.push(PaletteEntry::new(format!("Trash project {}", config.name), PaletteAction::Trash(TrashConfirmation::Project(project))));
+        }
+        for (data_source, ds) in view_model.data_sources.iter().enumerate() {
+            let name = ds.name.as_ref().unwrap_or(&ds.path);
+            entries.push(PaletteEntry::new(format!("Trash data source {}", name), PaletteAction::Trash(TrashConfirmation::DataSource(data_source))));
+        }
+
+        // Flags and other commands with a shortcut
+        for target in ShortcutTarget::all() {
+            if let Some(command) = target.command()
+                && target != ShortcutTarget::AddProject {
+                let mut entry = PaletteEntry::new(target.label(), PaletteAction::Command(command));
+                entry.shortcut = bindings.shortcuts(target).first().copied();
+                entries.push(entry);
+            }
+        }
+        entries
+    }
+}
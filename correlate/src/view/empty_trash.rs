@@ -0,0 +1,45 @@
+use std::any::Any;
+use crate::application_command::{ApplicationCommand, ApplicationCommandHandler, CommandResult};
+use crate::view::*;
+
+/// Deletes an item from the trash for good, or all of them.
+pub struct EmptyTrash {
+    pub entry: Option<usize>,
+}
+
+impl ApplicationCommand for EmptyTrash {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn description(&self) -> String {
+        match self.entry {
+            Some(_) => "Delete from trash".to_string(),
+            None => "Empty trash".to_string(),
+        }
+    }
+}
+
+pub struct EmptyTrashHandler;
+
+impl ApplicationCommandHandler for EmptyTrashHandler {
+    fn handle(&self, command: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = command.downcast_ref::<EmptyTrash>() {
+            let entries = match command.entry {
+                Some(index) => {
+                    let entry = view_model.config.trash.remove(index)
+                        .ok_or_else(|| format!("There is no item {} in the trash", index))?;
+                    vec![entry]
+                }
+                None => view_model.config.trash.take_all(),
+            };
+
+            // Saved first, so the deleted items don't come back when deleting their files fails.
+            view_model.config.save()?;
+            for entry in &entries {
+                entry.delete(&view_model.config)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::any::Any;
+use std::path::PathBuf;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::RootViewModel;
+
+/// Archives a project together with its data sources and their `.correlate` files.
+pub struct ExportProjectBundle {
+    pub project: usize,
+    pub path: PathBuf,
+}
+
+impl ExportProjectBundle {
+    /// Asks where to export the project to.
+    pub fn pick(project: usize, configuration: &ProjectConfiguration) -> Option<Self> {
+        rfd::FileDialog::new()
+            .add_filter("Correlate project bundle", &[BUNDLE_EXTENSION])
+            .set_file_name(format!("{}.{}", configuration.name, BUNDLE_EXTENSION))
+            .save_file()
+            .map(|path| Self { project, path })
+    }
+}
+
+impl ApplicationCommand for ExportProjectBundle {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        format!("Export project to {}", self.path.display())
+    }
+}
+
+pub struct ExportProjectBundleHandler;
+
+impl ApplicationCommandHandler for ExportProjectBundleHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<ExportProjectBundle>() {
+            let project = view_model.config.projects.iter().flatten().nth(command.project).cloned()
+                .ok_or_else(|| format!("There is no project {} to export", command.project))?;
+
+            // The bundle gets the settings as they are now.
+            for index in 0..view_model.data_sources.len() {
+                if project.data_sources.contains(&view_model.data_sources[index].path) {
+                    view_model.save_source_config(index);
+                }
+            }
+            let project = view_model.config.projects.iter().flatten().nth(command.project).cloned().unwrap_or(project);
+
+            let mut path = command.path.clone();
+            if path.extension().is_none_or(|e| e != BUNDLE_EXTENSION) {
+                path.as_mut_os_string().push(format!(".{}", BUNDLE_EXTENSION));
+            }
+            ProjectBundle { path }.export(&project)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use std::any::Any;
+use std::path::PathBuf;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::RootViewModel;
+
+/// Writes the visible columns of the current sheet to a CSV file.
+pub struct ExportSheet {
+    pub path: PathBuf,
+}
+
+impl ExportSheet {
+    /// Asks where to export the current sheet to.
+    pub fn pick(view_model: &RootViewModel) -> Option<Self> {
+        let ds = view_model.data_sources.get(view_model.selected_index?)?;
+        let sheet = ds.sheets.get(ds.selected_sheet_index)?;
+        let name = sheet.display_name.as_ref().unwrap_or(&sheet.name);
+        let name = name.strip_suffix(".csv").unwrap_or(name);
+        rfd::FileDialog::new()
+            .add_filter("CSV Files", &["csv"])
+            .set_file_name(format!("{}.csv", name))
+            .save_file()
+            .map(|path| Self { path })
+    }
+}
+
+impl ApplicationCommand for ExportSheet {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        format!("Export sheet to {}", self.path.display())
+    }
+}
+
+pub struct ExportSheetHandler;
+
+impl ApplicationCommandHandler for ExportSheetHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<ExportSheet>() {
+            if view_model.selected_index.is_none() {
+                return Err("There is no sheet to export".to_string());
+            }
+            CsvSheet::export(&command.path, &view_model.viewer.column_configs, &view_model.table)?;
+        }
+        Ok(())
+    }
+}
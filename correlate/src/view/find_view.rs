@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use egui::Key;
+use crate::data::*;
+use crate::view::*;
+use crate::egui_data_table::UiAction;
+
+const NOT_VISIBLE: &str = "The cell is not visible; clear the filter or show its column first.";
+const READ_ONLY: &str = "Only unlocked cells of virtual columns can be replaced.";
+
+/// Cells to replace in a sheet: row, column and a row that holds the new value.
+type ReplacedCells = Vec<(usize, usize, Row)>;
+
+/// What the matches were searched with; they are searched again when it changes.
+#[derive(Clone, PartialEq)]
+struct SearchKey {
+    options: FindOptions,
+    columns: Vec<usize>,
+    /// The shown sheet, unless the whole project is searched.
+    sheet: Option<(usize, usize)>,
+}
+
+enum FindAction {
+    Previous,
+    Next,
+    Replace,
+    ReplaceAll,
+}
+
+#[derive(Default)]
+pub struct FindView {
+    open: bool,
+    replace: bool,
+    focus_query: bool,
+    options: FindOptions,
+    /// Columns that were selected in the table when the dialog was opened.
+    selected_columns: Vec<usize>,
+    searched: Option<SearchKey>,
+    /// Set when the data changed, so the matches are searched again.
+    stale: bool,
+    matches: Vec<FindMatch>,
+    current: Option<usize>,
+    error: Option<String>,
+    message: Option<String>,
+    /// Match of another sheet, selected once that sheet has been rendered.
+    pending_focus: Option<FindMatch>,
+}
+
+impl FindView {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        // The table handles these shortcuts itself while it has focus.
+        let bindings = &view_model.config.shortcuts;
+        let replace_shortcuts = bindings.shortcuts(ShortcutTarget::Table(UiAction::Replace));
+        let find_shortcuts = bindings.shortcuts(ShortcutTarget::Table(UiAction::Find));
+        let shortcut = ctx.input_mut(|i| {
+            if replace_shortcuts.iter().any(|s| i.consume_shortcut(s)) {
+                Some(true)
+            } else if find_shortcuts.iter().any(|s| i.consume_shortcut(s)) {
+                Some(false)
+            } else {
+                None
+            }
+        });
+        if let Some(replace) = view_model.viewer.find_requested.take().or(shortcut) {
+            self.open = true;
+            self.replace = replace;
+            self.focus_query = true;
+            self.selected_columns = view_model.table.selected_columns();
+            self.selected_columns.sort_unstable();
+        }
+        if !self.open {
+            return;
+        }
+
+        if view_model.table.has_user_modification() {
+            self.stale = true;
+        }
+        self.refresh(view_model);
+        if view_model.table.visual_column_order().is_some()
+            && let Some(found) = self.pending_focus.take() {
+            self.show_match(view_model, found);
+        }
+
+        let mut open = self.open;
+        let mut action = None;
+        egui::Window::new(format!("{} Find and replace", egui_material_icons::icons::ICON_FIND_REPLACE))
+            .open(&mut open)
+            .default_width(340.0)
+            .show(ctx, |ui| {
+                action = self.show(ui);
+            });
+        self.open = open;
+
+        let Some(action) = action else {
+            return;
+        };
+        self.refresh(view_model);
+        match action {
+            FindAction::Previous => self.step(view_model, false),
+            FindAction::Next => self.step(view_model, true),
+            FindAction::Replace => self.replace_current(view_model),
+            FindAction::ReplaceAll => self.replace_all(view_model),
+        }
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui) -> Option<FindAction> {
+        let mut action = None;
+
+        egui::Grid::new("find_fields").num_columns(2).show(ui, |ui| {
+            ui.label("Find");
+            let response = ui.text_edit_singleline(&mut self.options.query);
+            if std::mem::take(&mut self.focus_query) {
+                response.request_focus();
+            }
+            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                action = Some(if ui.input(|i| i.modifiers.shift) { FindAction::Previous } else { FindAction::Next });
+                response.request_focus();
+            }
+            ui.end_row();
+
+            if self.replace {
+                ui.label("Replace");
+                ui.text_edit_singleline(&mut self.options.replacement)
+                    .on_hover_text("With a regular expression, $1 refers to the first capture group");
+                ui.end_row();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.options.match_case, "Match case");
+            ui.checkbox(&mut self.options.whole_word, "Whole word");
+            ui.checkbox(&mut self.options.regex, "Regex");
+        });
+
+        let scope_label = |scope: FindScope| match scope {
+            FindScope::SelectedColumns => format!("{} ({})", scope.label(), self.selected_columns.len()),
+            _ => scope.label().to_string(),
+        };
+        egui::ComboBox::from_label("Search in")
+            .selected_text(scope_label(self.options.scope))
+            .show_ui(ui, |ui| {
+                for scope in FindScope::ALL {
+                    ui.selectable_value(&mut self.options.scope, scope, scope_label(scope));
+                }
+            });
+        ui.separator();
+
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if !self.options.query.is_empty() {
+            match (self.current, self.matches.len()) {
+                (_, 0) => ui.label("No matches"),
+                (Some(i), count) => ui.label(format!("{} of {} matches", i + 1, count)),
+                (None, count) => ui.label(format!("{} matches", count)),
+            };
+        }
+        if let Some(message) = &self.message {
+            ui.label(message);
+        }
+
+        ui.horizontal(|ui| {
+            let has_matches = !self.matches.is_empty();
+            if ui.add_enabled(has_matches, egui::Button::new(egui_material_icons::icons::ICON_ARROW_UPWARD)).on_hover_text("Previous match (Shift+Enter)").clicked() {
+                action = Some(FindAction::Previous);
+            }
+            if ui.add_enabled(has_matches, egui::Button::new(egui_material_icons::icons::ICON_ARROW_DOWNWARD)).on_hover_text("Next match (Enter)").clicked() {
+                action = Some(FindAction::Next);
+            }
+            ui.toggle_value(&mut self.replace, "Replace…");
+
+            if self.replace {
+                if ui.add_enabled(has_matches, egui::Button::new("Replace")).clicked() {
+                    action = Some(FindAction::Replace);
+                }
+                if ui.add_enabled(has_matches, egui::Button::new("Replace all")).clicked() {
+                    action = Some(FindAction::ReplaceAll);
+                }
+            }
+        });
+
+        action
+    }
+
+    /// Searches the matches again when the options, the shown sheet or the data changed.
+    fn refresh(&mut self, view_model: &RootViewModel) {
+        let current = view_model.current_sheet_index();
+        let key = SearchKey {
+            options: FindOptions { replacement: String::new(), ..self.options.clone() },
+            columns: if self.options.scope == FindScope::SelectedColumns { self.selected_columns.clone() } else { Vec::new() },
+            sheet: if self.options.scope == FindScope::Project { None } else { current },
+        };
+        let changed = self.searched.as_ref() != Some(&key);
+        if !changed && !self.stale {
+            return;
+        }
+        if changed {
+            self.current = None;
+            self.message = None;
+        }
+        self.stale = false;
+        self.searched = Some(key);
+
+        self.error = None;
+        let matcher = match self.options.matcher() {
+            Ok(Some(matcher)) => matcher,
+            Ok(None) => {
+                self.matches.clear();
+                return;
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self.matches.clear();
+                return;
+            }
+        };
+
+        let all_columns = |count: usize| (0..count).collect::<Vec<_>>();
+        let find = |(source, sheet): (usize, usize), rows: &[Row], columns: &[usize]| {
+            matcher.find_in_rows(rows, columns).into_iter()
+                .map(move |(row, column)| FindMatch { source, sheet, row, column })
+        };
+
+        self.matches = match (self.options.scope, current) {
+            (FindScope::SelectedColumns, Some(current)) => {
+                find(current, &view_model.table, &self.selected_columns).collect()
+            }
+            (FindScope::Sheet, Some(current)) => {
+                find(current, &view_model.table, &all_columns(view_model.viewer.column_configs.len())).collect()
+            }
+            (FindScope::Project, _) => {
+                let mut matches = Vec::new();
+                for (source, ds) in view_model.data_sources.iter().enumerate() {
+                    for (sheet, data_sheet) in ds.sheets.iter().enumerate() {
+                        // The shown sheet holds the latest data of its table.
+                        if current == Some((source, sheet)) {
+                            matches.extend(find((source, sheet), &view_model.table, &all_columns(view_model.viewer.column_configs.len())));
+                        } else {
+                            matches.extend(find((source, sheet), &data_sheet.table, &all_columns(data_sheet.column_configs.len())));
+                        }
+                    }
+                }
+                matches
+            }
+            _ => Vec::new(),
+        };
+        self.current = self.current.filter(|&i| i < self.matches.len());
+    }
+
+    fn step(&mut self, view_model: &mut RootViewModel, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+
+        let next = match (self.current, forward) {
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+            (None, true) => 0,
+            (None, false) => count - 1,
+        };
+        self.current = Some(next);
+        self.show_match(view_model, self.matches[next]);
+    }
+
+    /// Selects the cell of a match, switching to its sheet first if needed.
+    fn show_match(&mut self, view_model: &mut RootViewModel, found: FindMatch) {
+        self.message = None;
+        if view_model.current_sheet_index() != Some((found.source, found.sheet)) {
+            view_model.switch_to_source(found.source, found.sheet);
+            // The table of the sheet can only be focused once it has been rendered.
+            self.pending_focus = Some(found);
+            return;
+        }
+
+        if !view_model.table.focus_cell(found.row, found.column) {
+            self.message = Some(NOT_VISIBLE.to_string());
+        }
+    }
+
+    fn replace_current(&mut self, view_model: &mut RootViewModel) {
+        let Some(found) = self.current.and_then(|i| self.matches.get(i)).copied() else {
+            self.step(view_model, true);
+            return;
+        };
+        let Ok(Some(matcher)) = self.options.matcher() else {
+            return;
+        };
+        let Some(row) = replaced_row(view_model, &matcher, &self.options.replacement, found) else {
+            self.message = Some(READ_ONLY.to_string());
+            return;
+        };
+        view_model.replace_cells(found.source, found.sheet, vec![(found.row, found.column, row)]);
+
+        // Continue with the first match after the replaced cell.
+        self.stale = true;
+        self.refresh(view_model);
+        self.current = None;
+        if let Some(next) = self.matches.iter().position(|m| *m > found).or((!self.matches.is_empty()).then_some(0)) {
+            self.current = Some(next);
+            self.show_match(view_model, self.matches[next]);
+        }
+    }
+
+    /// Replaces every match, as one step per sheet.
+    fn replace_all(&mut self, view_model: &mut RootViewModel) {
+        let Ok(Some(matcher)) = self.options.matcher() else {
+            return;
+        };
+
+        let mut cells: BTreeMap<(usize, usize), ReplacedCells> = BTreeMap::new();
+        let mut skipped = 0;
+        for found in &self.matches {
+            match replaced_row(view_model, &matcher, &self.options.replacement, *found) {
+                Some(row) => cells.entry((found.source, found.sheet)).or_default().push((found.row, found.column, row)),
+                None => skipped += 1,
+            }
+        }
+
+        let replaced = self.matches.len() - skipped;
+        for ((source, sheet), cells) in cells {
+            view_model.replace_cells(source, sheet, cells);
+        }
+
+        self.stale = true;
+        self.current = None;
+        self.message = Some(match skipped {
+            0 => format!("Replaced {} cells.", replaced),
+            _ => format!("Replaced {} cells; {} read-only cells were skipped.", replaced, skipped),
+        });
+    }
+}
+
+/// The row of a match with the matched text of its cell replaced, or `None` if the cell is
+/// read-only.
+fn replaced_row(view_model: &RootViewModel, matcher: &Matcher, replacement: &str, found: FindMatch) -> Option<Row> {
+    let (column_configs, rows) = if view_model.current_sheet_index() == Some((found.source, found.sheet)) {
+        (&view_model.viewer.column_configs, &*view_model.table)
+    } else {
+        let data_sheet = view_model.data_sources.get(found.source)?.sheets.get(found.sheet)?;
+        (&data_sheet.column_configs, &*data_sheet.table)
+    };
+
+    let row = rows.get(found.row)?;
+    if !RowView::is_editable(column_configs, found.column, row) {
+        return None;
+    }
+    let mut row = row.clone();
+    let value = matcher.replace(&row.cells[found.column].0, replacement);
+    row.cells[found.column] = CellValue::from(value);
+    Some(row)
+}
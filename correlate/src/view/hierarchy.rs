@@ -36,7 +36,7 @@ impl HierarchyPanel {
 
                                     if let Some(index) = newly_selected_index {
                                         let sheet_idx = newly_selected_sheet_index.unwrap_or(0);
-                                        enqueue_ui_command(ui, Box::new(SwitchToSource { ctx: ctx.clone(), index, sheet_idx }));
+                                        enqueue_ui_command(ui, Box::new(SwitchToSource { index, sheet_idx }));
                                     }
                                 }
                             }
@@ -44,12 +44,25 @@ impl HierarchyPanel {
 
                     header_res.header_response.context_menu(|ui| {
                         if ui.button("Add project").clicked() {
-                            enqueue_ui_command(ui, Box::new(AddProject { ctx: ctx.clone() }));
+                            enqueue_ui_command(ui, Box::new(AddProject));
+                            ui.close();
+                        }
+                        if ui.button("Open project…").clicked() {
+                            if let Some(command) = OpenProject::pick() {
+                                enqueue_ui_command(ui, Box::new(command));
+                            }
+                            ui.close();
+                        }
+                        Self::ui_recent_projects(ui, &view_model.config);
+                        if ui.button("Import project bundle…").clicked() {
+                            if let Some(import) = BundleImport::pick() {
+                                import.request(ui.ctx());
+                            }
                             ui.close();
                         }
                         ui.separator();
                         if let Some(path) = Self::ui_hierarchy_panel_context_menu(ui) {
-                            enqueue_ui_command(ui, Box::new(AddExistingDataSource { ctx: ctx.clone(), path }));
+                            enqueue_ui_command(ui, Box::new(AddExistingDataSource { path, project: 0 }));
                         }
                     });
 
@@ -88,6 +101,23 @@ impl HierarchyPanel {
 
     }
 
+    /// Lists the projects that were opened before and are closed now.
+    fn ui_recent_projects(ui: &mut Ui, config: &Configuration) {
+        let recent = config.recent_projects().into_iter().filter(|r| !r.open).collect::<Vec<_>>();
+        ui.add_enabled_ui(!recent.is_empty(), |ui| {
+            ui.menu_button("Open recent", |ui| {
+                for project in recent {
+                    let path = std::path::PathBuf::from(&project.path);
+                    let name = path.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+                    if ui.button(name).on_hover_text(&project.path).clicked() {
+                        enqueue_ui_command(ui, Box::new(OpenProject { path }));
+                        ui.close();
+                    }
+                }
+            });
+        });
+    }
+
     pub fn ui_hierarchy_panel_context_menu(ui: &mut Ui) -> Option<std::path::PathBuf> {
         let mut result = None;
         if ui.button("Add existing data source").clicked() {
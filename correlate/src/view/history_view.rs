@@ -0,0 +1,63 @@
+use crate::view::*;
+use crate::application_command::ApplicationCommandDispatcher;
+
+#[derive(Default)]
+pub struct HistoryView {}
+
+impl HistoryView {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, dispatcher: &ApplicationCommandDispatcher, ctx: &egui::Context) {
+        if !view_model.show_history {
+            return;
+        }
+
+        let mut jump_to = None;
+        egui::SidePanel::right("history_panel")
+            .resizable(true)
+            .default_width(260.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(format!("{} History", egui_material_icons::icons::ICON_HISTORY));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button(egui_material_icons::icons::ICON_CLOSE).clicked() {
+                            view_model.show_history = false;
+                        }
+                    });
+                });
+                ui.separator();
+
+                let history = &view_model.history;
+                let position = history.position();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(history.can_undo(), egui::Button::new(format!("{} Undo", egui_material_icons::icons::ICON_UNDO))).clicked() {
+                        jump_to = Some(position - 1);
+                    }
+                    if ui.add_enabled(history.can_redo(), egui::Button::new(format!("{} Redo", egui_material_icons::icons::ICON_REDO))).clicked() {
+                        jump_to = Some(position + 1);
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if ui.selectable_label(position == 0, "Opened").on_hover_text("Undo all steps").clicked() {
+                        jump_to = Some(0);
+                    }
+
+                    for (i, entry) in history.entries().iter().enumerate() {
+                        // Steps after the current position have been undone and can be redone.
+                        let mut text = egui::RichText::new(&entry.description);
+                        if i >= position {
+                            text = text.weak();
+                        }
+                        if ui.selectable_label(i + 1 == position, text).on_hover_text(&entry.location).clicked() {
+                            jump_to = Some(i + 1);
+                        }
+                    }
+                });
+            });
+
+        if let Some(position) = jump_to
+            && let Err(error) = dispatcher.jump_in_history(view_model, position) {
+            view_model.command_errors.push(error);
+        }
+    }
+}
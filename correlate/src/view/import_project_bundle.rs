@@ -0,0 +1,57 @@
+use std::any::Any;
+use std::path::PathBuf;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::RootViewModel;
+
+/// Extracts a project bundle into a folder and opens its project.
+pub struct ImportProjectBundle {
+    pub bundle: PathBuf,
+    pub folder: PathBuf,
+    /// Whether files that exist but aren't open may be overwritten.
+    pub overwrite: bool,
+}
+
+impl ApplicationCommand for ImportProjectBundle {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        let name = self.bundle.file_stem().unwrap_or(self.bundle.as_os_str());
+        format!("Import project {}", name.to_string_lossy())
+    }
+}
+
+pub struct ImportProjectBundleHandler;
+
+impl ImportProjectBundleHandler {
+    /// The data sources and projects that are open, which an import may never replace.
+    pub fn open_paths(view_model: &RootViewModel) -> Vec<PathBuf> {
+        view_model.data_sources.iter().map(|ds| PathBuf::from(&ds.path))
+            .chain(view_model.config.projects.iter().flatten().map(|p| p.path.clone()))
+            .collect()
+    }
+}
+
+impl ApplicationCommandHandler for ImportProjectBundleHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<ImportProjectBundle>() {
+            let bundle = ProjectBundle { path: command.bundle.clone() };
+            let conflicts = bundle.conflicts(&command.folder, &Self::open_paths(view_model))?;
+            if let Some(conflict) = conflicts.iter()
+                .find(|c| !command.overwrite || matches!(c, BundleConflict::Open(_))) {
+                return Err(match conflict {
+                    BundleConflict::Open(path) => format!("{} is open and can't be replaced", path.display()),
+                    BundleConflict::Exists(path) => format!("{} already exists", path.display()),
+                });
+            }
+
+            // Recorded in the history, so the project is closed again from there. The
+            // extracted files stay.
+            return view_model.try_workspace_change(command.description(), |view_model| {
+                let project = bundle.import(&command.folder)?;
+                view_model.open_project(&project)
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::any::Any;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::RootViewModel;
+
+/// Points a data source that could not be found to where it is now.
+pub struct LocateDataSource {
+    /// The path the projects refer to.
+    pub missing: String,
+    pub path: std::path::PathBuf,
+}
+
+impl ApplicationCommand for LocateDataSource {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        let name = self.path.file_name().unwrap_or(self.path.as_os_str());
+        format!("Locate data source {}", name.to_string_lossy())
+    }
+}
+
+pub struct LocateDataSourceHandler;
+
+impl LocateDataSourceHandler {
+    /// Replaces `missing` by `path` in the projects and loads it.
+    fn replace(view_model: &mut RootViewModel, missing: &str, path: String) -> Result<(), String> {
+        if view_model.data_sources.iter().any(|ds| ds.path == path) {
+            return Err(format!("{} is already part of a project", path));
+        }
+
+        view_model.push_data_source(RootViewModel::load_data_source(&path)?);
+        for project in view_model.config.projects.iter_mut().flatten() {
+            for source in &mut project.data_sources {
+                if source == missing {
+                    source.clone_from(&path);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ApplicationCommandHandler for LocateDataSourceHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<LocateDataSource>() {
+            let path = command.path.to_string_lossy().into_owned();
+
+            // Recorded in the history, so it is undone from there.
+            return view_model.try_workspace_change(command.description(), |view_model| {
+                Self::replace(view_model, &command.missing, path.clone())?;
+
+                // Other missing data sources were likely moved along with the same folder.
+                let missing = view_model.config.projects.iter().flatten()
+                    .flat_map(|p| p.data_sources.iter())
+                    .filter(|source| !view_model.data_sources.iter().any(|ds| &ds.path == *source))
+                    .cloned()
+                    .collect::<std::collections::BTreeSet<_>>();
+                for source in missing {
+                    if let Some(moved) = relocate(&source, &command.missing, &path)
+                        && std::path::Path::new(&moved).exists()
+                        && let Err(e) = Self::replace(view_model, &source, moved) {
+                        log::error!("{}", e);
+                    }
+                }
+
+                view_model.viewer.data_sources = view_model.data_sources.clone();
+                if view_model.selected_index.is_none() {
+                    view_model.switch_to_source(0, view_model.data_sources[0].selected_sheet_index);
+                }
+                view_model.config.save()
+            });
+        }
+        Ok(())
+    }
+}
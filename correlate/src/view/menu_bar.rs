@@ -1,11 +1,12 @@
 ﻿use egui::Sense;
 use crate::view::RootViewModel;
+use crate::application_command::ApplicationCommandDispatcher;
 
 #[derive(Default)]
 pub struct MenuBar {}
 
 impl MenuBar {
-    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, dispatcher: &mut ApplicationCommandDispatcher, ctx: &egui::Context) {
         egui::TopBottomPanel::top("MenuBar").show(ctx, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
                 ui.hyperlink_to(
@@ -19,6 +20,10 @@ impl MenuBar {
 
                 ui.separator();
 
+                Self::ui_command_history(ui, view_model, dispatcher);
+
+                ui.separator();
+
                 ui.label("Name Filter");
                 ui.text_edit_singleline(&mut view_model.viewer.name_filter);
 
@@ -29,6 +34,13 @@ impl MenuBar {
                     )
                     .dnd_set_drag_payload(String::from("Hallo~"));
 
+                let trash_count = view_model.config.trash.entries().len();
+                ui.toggle_value(&mut view_model.show_trash, format!("{} Trash ({})", egui_material_icons::icons::ICON_DELETE, trash_count))
+                    .on_hover_text("Restore removed projects, data sources and columns");
+
+                ui.toggle_value(&mut view_model.show_shortcuts, format!("{} Shortcuts", egui_material_icons::icons::ICON_KEYBOARD))
+                    .on_hover_text("Show and change the keyboard shortcuts");
+
                 ui.menu_button("🎌 Flags", |ui| {
                     ui.checkbox(&mut view_model.viewer.row_protection, "Row Protection")
                         .on_hover_text(
@@ -62,4 +74,30 @@ impl MenuBar {
             })
         });
     }
+
+    /// Undo and redo of commands that aren't part of the history, like switching sheets.
+    fn ui_command_history(ui: &mut egui::Ui, view_model: &mut RootViewModel, dispatcher: &mut ApplicationCommandDispatcher) {
+        let position = view_model.history.position();
+        let entries = view_model.history.entries();
+        let undo = position.checked_sub(1).map(|i| entries[i].description.clone());
+        let redo = entries.get(position).map(|entry| entry.description.clone());
+
+        let mut jump_to = None;
+        let response = ui.add_enabled(undo.is_some(), egui::Button::new(egui_material_icons::icons::ICON_UNDO))
+            .on_hover_text(format!("Undo: {}", undo.unwrap_or_default()));
+        if response.clicked() {
+            jump_to = Some(position - 1);
+        }
+
+        let response = ui.add_enabled(redo.is_some(), egui::Button::new(egui_material_icons::icons::ICON_REDO))
+            .on_hover_text(format!("Redo: {}", redo.unwrap_or_default()));
+        if response.clicked() {
+            jump_to = Some(position + 1);
+        }
+
+        if let Some(position) = jump_to
+            && let Err(error) = dispatcher.jump_in_history(view_model, position) {
+            view_model.command_errors.push(error);
+        }
+    }
 }
@@ -12,6 +12,9 @@ pub use row_view::*;
 
 pub use root_view::*;
 
+pub mod startup_view;
+pub use startup_view::*;
+
 pub mod root_view_model;
 pub use root_view_model::*;
 
@@ -21,6 +24,25 @@ pub use menu_bar::*;
 pub mod bottom_panel;
 pub use bottom_panel::*;
 
+pub mod problems_view;
+pub use problems_view::*;
+
+pub mod history_view;
+pub use history_view::*;
+
+pub mod find_view;
+pub use find_view::*;
+pub mod command_palette_view;
+pub use command_palette_view::*;
+pub mod shortcuts_view;
+pub use shortcuts_view::*;
+pub mod trash_view;
+pub use trash_view::*;
+pub mod bundle_view;
+pub use bundle_view::*;
+pub mod unsaved_projects_view;
+pub use unsaved_projects_view::*;
+
 mod hierarchy_view_model;
 pub use hierarchy_view_model::*;
 
@@ -50,6 +72,25 @@ pub use trash_project::*;
 
 mod trash_data_source;
 pub use trash_data_source::*;
-
-
-
+mod toggle_flag;
+pub use toggle_flag::*;
+mod set_column_visibility;
+pub use set_column_visibility::*;
+mod restore_from_trash;
+pub use restore_from_trash::*;
+mod empty_trash;
+pub use empty_trash::*;
+mod locate_data_source;
+pub use locate_data_source::*;
+mod open_project;
+pub use open_project::*;
+mod save_project;
+pub use save_project::*;
+mod close_project;
+pub use close_project::*;
+mod export_project_bundle;
+pub use export_project_bundle::*;
+mod export_sheet;
+pub use export_sheet::*;
+mod import_project_bundle;
+pub use import_project_bundle::*;
@@ -0,0 +1,46 @@
+use std::any::Any;
+use std::path::PathBuf;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::RootViewModel;
+
+/// Opens a project file next to the projects that are open.
+pub struct OpenProject {
+    pub path: PathBuf,
+}
+
+impl OpenProject {
+    /// Asks for the project file to open.
+    pub fn pick() -> Option<Self> {
+        rfd::FileDialog::new()
+            .add_filter("Correlate project", &[PROJECT_FILE_EXTENSION])
+            .pick_file()
+            .map(|path| Self { path })
+    }
+}
+
+impl ApplicationCommand for OpenProject {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        let name = self.path.file_stem().unwrap_or(self.path.as_os_str());
+        format!("Open project {}", name.to_string_lossy())
+    }
+}
+
+pub struct OpenProjectHandler;
+
+impl ApplicationCommandHandler for OpenProjectHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<OpenProject>() {
+            let path = std::path::absolute(&command.path).unwrap_or_else(|_| command.path.clone());
+            if let Some(project) = view_model.config.projects.iter().flatten().find(|p| p.path == path) {
+                return Err(format!("Project {} is already open", project.name));
+            }
+
+            // Recorded in the history, so it is closed again from there.
+            return view_model.try_workspace_change(command.description(), |view_model| view_model.open_project(&path));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::data::*;
+use crate::view::*;
+
+#[derive(Default)]
+pub struct ProblemsView {}
+
+impl ProblemsView {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        let mut open = view_model.show_problems;
+        let mut jump_to = None;
+
+        egui::Window::new(format!("{} Problems", egui_material_icons::icons::ICON_ERROR))
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                let problems = &view_model.viewer.problems;
+                if problems.is_empty() {
+                    ui.label("No problems found.");
+                    return;
+                }
+
+                let name_idx = ColumnConfiguration::find_name_column_index(&view_model.viewer.column_configs);
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for problem in &problems.problems {
+                        let row_name = view_model.table.get(problem.row)
+                            .and_then(|r| r.cells.get(name_idx))
+                            .map(|c| c.0.clone())
+                            .unwrap_or_default();
+                        let column_name = view_model.viewer.column_configs.get(problem.column)
+                            .map(|c| c.display_name.as_ref().unwrap_or(&c.name).clone())
+                            .unwrap_or_default();
+
+                        let text = format!("Row {} ({}) · {}: {}", problem.row + 1, row_name, column_name, problem.error);
+                        if ui.selectable_label(false, text).on_hover_text("Go to cell").clicked() {
+                            jump_to = Some((problem.row, problem.column));
+                        }
+                    }
+                });
+            });
+
+        if let Some((row, column)) = jump_to
+            && !view_model.table.focus_cell(row, column) {
+            view_model.viewer.validation_message = Some("The cell is not visible; clear the filter or show its column first.".to_string());
+        }
+        view_model.show_problems = open;
+    }
+}
@@ -0,0 +1,155 @@
+use std::any::Any;
+use std::collections::HashMap;
+use crate::application_command::{ApplicationCommand, ApplicationCommandHandler, CommandResult};
+use crate::data::*;
+use crate::view::*;
+
+pub struct RestoreFromTrash {
+    /// Position of the entry in the trash.
+    pub entry: usize,
+}
+
+impl ApplicationCommand for RestoreFromTrash {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn description(&self) -> String {
+        "Restore from trash".to_string()
+    }
+}
+
+pub struct RestoreFromTrashHandler;
+
+impl RestoreFromTrashHandler {
+    fn restore_project(&self, view_model: &mut RootViewModel, entry: usize, index: usize, project: ProjectConfiguration) {
+        view_model.record_workspace_change(format!("Restore project {}", project.name), |view_model| {
+            view_model.config.trash.remove(entry);
+
+            // The data sources of the project are no longer loaded after a restart.
+            for source in &project.data_sources {
+                if !view_model.data_sources.iter().any(|ds| &ds.path == source) {
+                    match RootViewModel::load_data_source(source) {
+                        Ok(data_source) => view_model.push_data_source(data_source),
+                        Err(e) => log::error!("{}", e),
+                    }
+                }
+            }
+            view_model.viewer.data_sources = view_model.data_sources.clone();
+            if view_model.selected_index.is_none() && !view_model.data_sources.is_empty() {
+                view_model.switch_to_source(0, view_model.data_sources[0].selected_sheet_index);
+            }
+
+            let projects = view_model.config.projects.get_or_insert_with(Vec::new);
+            projects.insert(index.min(projects.len()), project);
+            if let Err(e) = view_model.config.save() {
+                log::error!("Failed to save config after restoring project: {}", e);
+            }
+        });
+    }
+
+    fn restore_data_source(&self, view_model: &mut RootViewModel, entry: usize, path: String, name: Option<String>, projects: Vec<usize>) -> CommandResult {
+        let name = name.unwrap_or_else(|| path.clone());
+        if view_model.data_sources.iter().any(|ds| ds.path == path) {
+            return Err(format!("Data source {} is already part of a project", name));
+        }
+        if !std::path::Path::new(&path).exists() {
+            return Err(format!("{} no longer exists", path));
+        }
+
+        // Projects that were removed since are skipped; without any left, the first one is used.
+        let project_count = view_model.config.projects.as_ref().map_or(0, Vec::len);
+        let mut projects = projects.into_iter().filter(|&p| p < project_count).collect::<Vec<_>>();
+        if projects.is_empty() {
+            if project_count == 0 {
+                return Err(format!("There is no project to restore {} into", name));
+            }
+            projects.push(0);
+        }
+
+        view_model.try_workspace_change(format!("Restore data source {}", name), |view_model| {
+            view_model.handle_pending_file_add(path.clone().into(), projects[0])?;
+            for &project in &projects[1..] {
+                if let Some(project) = view_model.config.projects.as_mut().and_then(|p| p.get_mut(project)) {
+                    project.data_sources.push(path.clone());
+                }
+            }
+            view_model.config.trash.remove(entry);
+            view_model.config.save()
+        })
+    }
+
+    fn restore_column(
+        &self,
+        view_model: &mut RootViewModel,
+        entry: usize,
+        sheet: SheetId,
+        column: Box<ColumnConfiguration>,
+        conditional_formats: Vec<ConditionalFormat>,
+        cell_values: Vec<CellValueConfiguration>,
+    ) -> CommandResult {
+        let index = view_model.data_sources.iter().position(|ds| ds.path == sheet.source)
+            .ok_or_else(|| format!("Data source {} is not part of a project", sheet.source))?;
+        if sheet.sheet >= view_model.data_sources[index].sheets.len() {
+            return Err(format!("Data source {} no longer has sheet {}", sheet.source, sheet.sheet + 1));
+        }
+        if view_model.current_sheet_index() != Some((index, sheet.sheet)) {
+            view_model.switch_to_source(index, sheet.sheet);
+        }
+        if view_model.viewer.column_configs.iter().any(|c| c.name == column.name) {
+            return Err(format!("The sheet already has a column {}", column.name));
+        }
+
+        let rows = view_model.table.take();
+        let keys = DataSheetConfiguration::row_keys(&view_model.viewer.column_configs, &rows);
+        let values = cell_values.iter()
+            .map(|v| (v.key.as_str(), v.value.as_str()))
+            .collect::<HashMap<_, _>>();
+        let rows = rows.into_iter()
+            .zip(keys)
+            .map(|(mut row, key)| {
+                let value = values.get(key.to_string().as_str())
+                    .map(|v| CellValue(v.to_string()))
+                    .unwrap_or_else(|| column.column_type.default_value());
+                row.cells.push(value);
+                row
+            })
+            .collect();
+        view_model.table.replace(rows);
+
+        view_model.viewer.column_configs.push(ColumnConfiguration { order: view_model.viewer.column_configs.len(), ..*column });
+        view_model.viewer.formatting.formats.extend(conditional_formats);
+        view_model.table.mark_as_modified();
+        view_model.record_history();
+        view_model.save_datasource_configuration();
+
+        view_model.config.trash.remove(entry);
+        view_model.config.save()
+    }
+}
+
+impl ApplicationCommandHandler for RestoreFromTrashHandler {
+    fn handle(&self, command: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = command.downcast_ref::<RestoreFromTrash>() {
+            let Some(entry) = view_model.config.trash.entries().get(command.entry).cloned() else {
+                return Err(format!("There is no item {} in the trash", command.entry));
+            };
+
+            // Restoring is recorded in the history, so it is undone from there.
+            match entry.item {
+                TrashedItem::Project { index, mut project, path } => {
+                    if !path.as_os_str().is_empty() && view_model.config.projects.iter().flatten().any(|p| p.path == path) {
+                        return Err(format!("Project {} is already open", project.name));
+                    }
+                    project.path = path;
+                    self.restore_project(view_model, command.entry, index, project)
+                }
+                TrashedItem::DataSource { path, name, projects } => self.restore_data_source(view_model, command.entry, path, name, projects)?,
+                TrashedItem::Column { source, sheet, column, conditional_formats, cell_values } => {
+                    self.restore_column(view_model, command.entry, SheetId { source, sheet }, column, conditional_formats, cell_values)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
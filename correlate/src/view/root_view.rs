@@ -1,4 +1,6 @@
-﻿use crate::view::*;
+﻿use crate::arguments::Arguments;
+use crate::data::{Configuration, PROJECT_FILE_EXTENSION};
+use crate::view::*;
 use crate::application_command::*;
 
 pub struct RootView {
@@ -9,6 +11,14 @@ pub struct RootView {
 
     pub central_panel: CentralPanel,
     pub bottom_panel: BottomPanel,
+    pub problems_view: ProblemsView,
+    pub history_view: HistoryView,
+    pub find_view: FindView,
+    pub shortcuts_view: ShortcutsView,
+    pub trash_view: TrashView,
+    pub bundle_view: BundleView,
+    pub unsaved_projects_view: UnsavedProjectsView,
+    pub command_palette_view: CommandPaletteView,
     pub menu_bar: MenuBar,
     pub hierarchy_panel: HierarchyPanel,
 
@@ -16,11 +26,13 @@ pub struct RootView {
     pub dispatcher: ApplicationCommandDispatcher,
 }
 
-impl Default for RootView {
-
-    fn default() -> Self {
-        let config_path = "config.json";
-        let config = crate::data::Configuration::load(config_path).unwrap();
+impl RootView {
+    /// Loads the configuration and opens the project and file given on the command line.
+    pub fn new(arguments: &Arguments) -> Result<Self, String> {
+        let config = match &arguments.config {
+            Some(path) => Configuration::load(path)?,
+            None => Configuration::load_default()?,
+        };
 
         let mut dispatcher = ApplicationCommandDispatcher::new();
         dispatcher.register::<ToggleScrollBarVisibility, _>(ToggleScrollBarVisibilityHandler);
@@ -31,17 +43,97 @@ impl Default for RootView {
         dispatcher.register::<TrashColumn, _>(TrashColumnHandler);
         dispatcher.register::<TrashProject, _>(TrashProjectHandler);
         dispatcher.register::<TrashDataSource, _>(TrashDataSourceHandler);
+        dispatcher.register::<RestoreFromTrash, _>(RestoreFromTrashHandler);
+        dispatcher.register::<EmptyTrash, _>(EmptyTrashHandler);
+        dispatcher.register::<LocateDataSource, _>(LocateDataSourceHandler);
+        dispatcher.register::<OpenProject, _>(OpenProjectHandler);
+        dispatcher.register::<SaveProject, _>(SaveProjectHandler);
+        dispatcher.register::<CloseProject, _>(CloseProjectHandler);
+        dispatcher.register::<ExportProjectBundle, _>(ExportProjectBundleHandler);
+        dispatcher.register::<ExportSheet, _>(ExportSheetHandler);
+        dispatcher.register::<ImportProjectBundle, _>(ImportProjectBundleHandler);
+        dispatcher.register::<ToggleFlag, _>(ToggleFlagHandler);
+        dispatcher.register::<SetColumnVisibility, _>(SetColumnVisibilityHandler);
 
-        Self {
+        let mut root_view = Self {
             hierarchy_view_model: HierarchyViewModel::default(&config),
             central_panel_view_model: CentralPanelViewModel::default(&config),
             root_view_model: RootViewModel::default(config),
             central_panel: CentralPanel::default(),
             bottom_panel: BottomPanel::default(),
+            problems_view: ProblemsView::default(),
+            history_view: HistoryView::default(),
+            find_view: FindView::default(),
+            shortcuts_view: ShortcutsView::default(),
+            trash_view: TrashView::default(),
+            bundle_view: BundleView::default(),
+            unsaved_projects_view: UnsavedProjectsView::default(),
+            command_palette_view: CommandPaletteView::default(),
             menu_bar: MenuBar::default(),
             hierarchy_panel: HierarchyPanel::default(),
             pending_commands: Vec::new(),
             dispatcher,
+        };
+        root_view.open_arguments(arguments);
+        Ok(root_view)
+    }
+
+    /// Queues the commands that open the project and file given on the command line.
+    fn open_arguments(&mut self, arguments: &Arguments) {
+        let view_model = &mut self.root_view_model;
+        for reason in std::mem::take(&mut view_model.config.project_errors) {
+            view_model.command_errors.push(CommandError::Failed { command: "Open project".to_string(), reason });
+        }
+        let projects = view_model.config.projects.clone().unwrap_or_default();
+
+        if let Some(file) = &arguments.file
+            && file.extension().is_some_and(|e| e == PROJECT_FILE_EXTENSION) {
+            self.pending_commands.push(Box::new(OpenProject { path: file.clone() }));
+            return;
+        }
+
+        let mut project = 0;
+        if let Some(name) = &arguments.project {
+            match projects.iter().position(|p| &p.name == name) {
+                Some(index) => {
+                    project = index;
+                    // The opened file is shown instead of the first data source of the project.
+                    if arguments.file.is_none()
+                        && let Some(source) = projects[index].data_sources.first()
+                        && let Some(index) = view_model.data_sources.iter().position(|ds| &ds.path == source) {
+                        let sheet_idx = view_model.data_sources[index].selected_sheet_index;
+                        self.pending_commands.push(Box::new(SwitchToSource { index, sheet_idx }));
+                    }
+                }
+                None => view_model.command_errors.push(CommandError::Failed {
+                    command: format!("Open project {}", name),
+                    reason: format!("There is no project {}", name),
+                }),
+            }
+        }
+
+        if let Some(file) = &arguments.file {
+            let path = match std::fs::canonicalize(file) {
+                Ok(path) => path,
+                Err(e) => {
+                    view_model.command_errors.push(CommandError::Failed { command: format!("Open {}", file.display()), reason: e.to_string() });
+                    return;
+                }
+            };
+            let loaded = view_model.data_sources.iter()
+                .position(|ds| std::fs::canonicalize(&ds.path).is_ok_and(|p| p == path));
+            match loaded {
+                Some(index) => {
+                    let sheet_idx = view_model.data_sources[index].selected_sheet_index;
+                    self.pending_commands.push(Box::new(SwitchToSource { index, sheet_idx }));
+                }
+                None => {
+                    if projects.is_empty() {
+                        self.pending_commands.push(Box::new(AddProject));
+                    }
+                    self.pending_commands.push(Box::new(AddExistingDataSource { path, project }));
+                }
+            }
         }
     }
 }
@@ -49,7 +141,10 @@ impl Default for RootView {
 impl eframe::App for RootView {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         
-        self.dispatcher.dispatch(&mut self.pending_commands);
+        for error in self.dispatcher.dispatch(&mut self.pending_commands, &mut self.root_view_model) {
+            log::error!("{}", error);
+            self.root_view_model.command_errors.push(error);
+        }
 
         self.central_panel.update(&mut self.root_view_model, &mut self.central_panel_view_model);
 
@@ -59,12 +154,20 @@ impl eframe::App for RootView {
         is_send(&self.root_view_model.table);
         is_sync(&self.root_view_model.table);
 
-        self.menu_bar.ui(&mut self.root_view_model, ctx);
+        self.menu_bar.ui(&mut self.root_view_model, &mut self.dispatcher, ctx);
         self.bottom_panel.ui(&mut self.root_view_model, ctx);
 
         self.pending_commands.extend(get_commands(ctx));
 
         self.hierarchy_panel.ui(&mut self.root_view_model, ctx);
+        self.history_view.ui(&mut self.root_view_model, &self.dispatcher, ctx);
         self.central_panel.ui(&mut self.root_view_model, ctx);
+        self.problems_view.ui(&mut self.root_view_model, ctx);
+        self.find_view.ui(&mut self.root_view_model, ctx);
+        self.trash_view.ui(&mut self.root_view_model, ctx);
+        self.bundle_view.ui(&mut self.root_view_model, ctx);
+        self.unsaved_projects_view.ui(&mut self.root_view_model, ctx);
+        self.shortcuts_view.ui(&mut self.root_view_model, ctx);
+        self.command_palette_view.ui(&mut self.root_view_model, ctx);
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use crate::data::*;
 use crate::view::*;
 use crate::egui_data_table::*;
+use crate::application_command::CommandError;
 
 pub struct RootViewModel {
     pub config: Configuration,
@@ -10,57 +11,56 @@ pub struct RootViewModel {
     pub selected_index: Option<usize>,
     pub style_override: Style,
     pub scroll_bar_always_visible: bool,
+    pub show_problems: bool,
+    pub history: History,
+    pub show_history: bool,
+    pub show_shortcuts: bool,
+    pub show_trash: bool,
+    /// Commands that failed since the user last dismissed them.
+    pub command_errors: Vec<CommandError>,
+    /// The sheet and the modifications of its table when it was last recorded in the history.
+    recorded_modifications: Option<(SheetId, u64)>,
+    /// Trashed columns taken out of the trash, as undoing brought them back to their sheet.
+    untrashed_columns: Vec<TrashEntry>,
 }
 
 impl RootViewModel {
     pub fn save_source_config(&mut self, index: usize) {
         if let Some(ds) = self.data_sources.get_mut(index) {
-            let (configs, table) = if Some(index) == self.selected_index {
-                (self.viewer.column_configs.clone(), self.table.clone())
+            let (configs, formats, table) = if Some(index) == self.selected_index {
+                (self.viewer.column_configs.clone(), self.viewer.formatting.formats.clone(), self.table.clone())
             } else {
                 let sheet = &ds.sheets[ds.selected_sheet_index];
-                (sheet.column_configs.clone(), sheet.table.clone())
+                (sheet.column_configs.clone(), sheet.conditional_formats.clone(), sheet.table.clone())
             };
 
-            if let Err(e) = ds.save(configs, table) {
+            if let Err(e) = ds.save(configs, formats, table) {
                 log::error!("Failed to save companion config for {}: {}", ds.path, e);
             }
         }
+        self.update_project_metadata();
     }
 
-    pub fn default(config: Configuration) -> Self {
+    /// Keeps the shown sheets and relations of the projects up to date, to be saved with them.
+    pub fn update_project_metadata(&mut self) {
+        let current = self.current_sheet_index().map(|(source, sheet)| (source, sheet, self.viewer.column_configs.as_slice()));
+        for project in self.config.projects.iter_mut().flatten() {
+            project.update_metadata(&self.data_sources, current);
+        }
+    }
+
+    pub fn default(mut config: Configuration) -> Self {
+        Self::delete_expired_trash(&mut config);
 
         let mut data_sources = Vec::new();
         for project in config.projects.as_ref().unwrap_or(&Vec::new()) {
             for source in &project.data_sources {
-                let extension = std::path::Path::new(source)
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-
-
-                let loader: Box<dyn SheetLoader> = if extension == "csv" {
-                    Box::new(CsvSheet)
-                } else {
-                    Box::new(ExcelSheet)
-                };
-
-                let loaded = loader.load(source.clone());
-
-                match loaded {
-                    Ok((loaded_sheets, source_config)) => {
-                        let custom_name = loaded_sheets.first().and_then(|s| s.custom_name.clone());
-                        data_sources.push(DataSource::new(
-                            source.to_string(),
-                            custom_name,
-                            source_config,
-                            loaded_sheets,
-                            0,
-                        ));
-                    }
-                    Err(e) => {
-                        log::error!("Failed to load {}: {}", source, e);
+                match Self::load_data_source(source) {
+                    Ok(mut data_source) => {
+                        project.apply_metadata(&mut data_source);
+                        data_sources.push(data_source);
                     }
+                    Err(e) => log::error!("{}", e),
                 }
             }
         }
@@ -68,15 +68,7 @@ impl RootViewModel {
         if data_sources.is_empty() {
             let selected_index = None;
             let table = DataTable::new();
-            let viewer = RowView {
-                name_filter: String::new(),
-                row_protection: false,
-                hotkeys: Vec::new(),
-                column_configs: Vec::new(),
-                config: config.clone(),
-                data_sources: data_sources.clone(),
-                visible_columns: None,
-            };
+            let viewer = RowView::new(Vec::new(), ConditionalFormatting::default(), config.clone(), data_sources.clone());
 
             return Self {
                 config,
@@ -86,6 +78,14 @@ impl RootViewModel {
                 selected_index,
                 style_override: Default::default(),
                 scroll_bar_always_visible: false,
+                show_problems: false,
+                history: History::default(),
+                show_history: false,
+                show_shortcuts: false,
+                show_trash: false,
+                command_errors: Vec::new(),
+                recorded_modifications: None,
+                untrashed_columns: Vec::new(),
             };
         }
 
@@ -93,15 +93,15 @@ impl RootViewModel {
         let ds = &data_sources[selected_index];
         let sheet = &ds.sheets[ds.selected_sheet_index];
         let table = sheet.table.clone();
-        let viewer = RowView {
-            name_filter: String::new(),
-            row_protection: false,
-            hotkeys: Vec::new(),
-            column_configs: sheet.column_configs.clone(),
-            config: config.clone(),
-            data_sources: data_sources.clone(),
-            visible_columns: None,
-        };
+        let viewer = RowView::new(
+            sheet.column_configs.clone(),
+            ConditionalFormatting::new(sheet.conditional_formats.clone()),
+            config.clone(),
+            data_sources.clone(),
+        );
+
+        let history = Self::load_history(&data_sources);
+        let command_errors = data_sources.iter().filter_map(Self::settings_error).collect();
 
         Self {
             config,
@@ -111,78 +111,211 @@ impl RootViewModel {
             selected_index: Some(selected_index),
             style_override: Default::default(),
             scroll_bar_always_visible: false,
+            show_problems: false,
+            history,
+            show_history: false,
+            show_shortcuts: false,
+            show_trash: false,
+            command_errors,
+            recorded_modifications: None,
+            untrashed_columns: Vec::new(),
         }
     }
 
-    pub fn handle_pending_file_add(&mut self, path: std::path::PathBuf, index: usize) {
+    /// Loads a data source with the loader that matches its extension.
+    pub fn load_data_source(source: &str) -> Result<DataSource, String> {
+        let extension = std::path::Path::new(source)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+
+        let loader: Box<dyn SheetLoader> = if extension == "csv" {
+            Box::new(CsvSheet)
+        } else {
+            Box::new(ExcelSheet)
+        };
+
+        match loader.load(source.to_string()) {
+            Ok((sheets, source_config)) => {
+                let custom_name = sheets.first().and_then(|s| s.custom_name.clone());
+                Ok(DataSource::new(source.to_string(), custom_name, source_config, sheets, 0))
+            }
+            Err(e) => Err(format!("Failed to load {}: {}", source, e)),
+        }
+    }
+
+    /// Why the `.correlate` file of a data source could not be loaded.
+    fn settings_error(ds: &DataSource) -> Option<CommandError> {
+        let reason = ds.config.load_error.clone()?;
+        let file_name = std::path::Path::new(&ds.path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Some(CommandError::Failed { command: format!("Load the settings of {}", file_name), reason })
+    }
+
+    /// Adds a loaded data source, and reports when its settings could not be read.
+    pub fn push_data_source(&mut self, data_source: DataSource) {
+        self.command_errors.extend(Self::settings_error(&data_source));
+        self.data_sources.push(data_source);
+    }
+
+    /// Loads a data source and adds it to the project at `index`.
+    pub fn handle_pending_file_add(&mut self, path: std::path::PathBuf, index: usize) -> Result<(), String> {
         let path_str = path.to_string_lossy().to_string();
 
         // If the file doesn't exist, create an empty one (with headers)
         if !path.exists() {
-            if let Err(e) = std::fs::write(&path, "Name\n") {
-                log::error!("Failed to create new file {}: {}", path_str, e);
-                return;
+            std::fs::write(&path, "Name\n").map_err(|e| format!("Failed to create new file {}: {}", path_str, e))?;
+        }
+
+        let new_index = self.data_sources.len();
+        self.push_data_source(Self::load_data_source(&path_str)?);
+        self.switch_to_source(new_index, 0);
+
+        // Persist to config
+        if let Some(projects) = self.config.projects.as_mut() {
+            if let Some(project) = projects.get_mut(index) {
+                project.data_sources.push(path_str);
             }
         }
 
-        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        self.config.selected_index = self.selected_index;
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save config: {}", e);
+        }
+        Ok(())
+    }
 
-        let loaded_result = if extension == "csv" {
-            CsvSheet.load(path_str.clone())
-        } else {
-            ExcelSheet.load(path_str.clone())
-        };
+    /// Deletes the trashed items that were kept for longer than the retention period.
+    fn delete_expired_trash(config: &mut Configuration) {
+        let expired = config.trash.take_expired(unix_now(), config.trash_retention_days);
+        if expired.is_empty() {
+            return;
+        }
+        for entry in &expired {
+            if let Err(e) = entry.delete(config) {
+                log::error!("{}", e);
+            }
+        }
+        if let Err(e) = config.save() {
+            log::error!("Failed to save config after emptying the trash: {}", e);
+        }
+    }
 
-        match loaded_result {
-            Ok((sheets, source_config)) => {
-                let custom_name = sheets.first().and_then(|s| s.custom_name.clone());
-                let new_index = self.data_sources.len();
-
-                self.data_sources.push(DataSource::new(
-                    path_str.clone(),
-                    custom_name,
-                    source_config,
-                    sheets,
-                    0,
-                ));
-
-                self.switch_to_source(new_index, 0);
-
-                // Persist to config
-                if let Some(projects) = self.config.projects.as_mut() {
-                    if let Some(project) = projects.get_mut(index) {
-                        project.data_sources.push(path_str);
+    /// Applies changed keyboard shortcuts and saves them in the configuration.
+    pub fn set_shortcuts(&mut self, shortcuts: KeyBindings) {
+        self.viewer.config.shortcuts = shortcuts.clone();
+        self.config.shortcuts = shortcuts;
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save config after changing shortcuts: {}", e);
+        }
+    }
+
+    pub fn add_project(&mut self) {
+        let name = format!("New Project {}", self.config.projects.as_ref().map_or(0, Vec::len) + 1);
+        let project = Project::new(name.clone(), self.config.new_project_path(&name));
+        let projects = self.config.projects.get_or_insert_with(Vec::new);
+        projects.push(project.configuration);
+        let index = projects.len() - 1;
+        // The project file is created right away, so the project is opened again after a restart.
+        if let Err(e) = self.config.save_project(index) {
+            log::error!("Failed to save project after adding it: {}", e);
+        }
+    }
+
+    /// Opens a project file and loads those of its data sources that aren't loaded yet.
+    pub fn open_project(&mut self, path: &std::path::Path) -> Result<(), String> {
+        let project = ProjectConfiguration::load(path)?;
+        for source in &project.data_sources {
+            if !self.data_sources.iter().any(|ds| &ds.path == source) {
+                match Self::load_data_source(source) {
+                    Ok(mut data_source) => {
+                        project.apply_metadata(&mut data_source);
+                        self.push_data_source(data_source);
                     }
+                    Err(e) => log::error!("{}", e),
                 }
+            }
+        }
+
+        self.config.saved_projects.push(project.clone());
+        self.config.projects.get_or_insert_with(Vec::new).push(project);
+        self.viewer.data_sources = self.data_sources.clone();
+        if self.selected_index.is_none() && !self.data_sources.is_empty() {
+            self.switch_to_source(0, self.data_sources[0].selected_sheet_index);
+        }
+        self.config.save()
+    }
 
-                self.config.selected_index = self.selected_index;
-                if let Err(e) = self.config.save() {
-                    log::error!("Failed to save config: {}", e);
+    /// Removes a loaded data source, after saving its `.correlate` file, and shows another one
+    /// when it was shown.
+    pub fn unload_data_source(&mut self, index: usize) -> DataSource {
+        if self.selected_index == Some(index) {
+            self.save_datasource_configuration();
+        }
+        let removed = self.data_sources.remove(index);
+
+        match self.selected_index {
+            Some(selected) if selected == index => {
+                // The removed source was already saved, so switching must not write it back.
+                self.selected_index = None;
+                if self.data_sources.is_empty() {
+                    self.table = DataTable::new();
+                    self.viewer.column_configs = Vec::new();
+                    self.viewer.data_sources = Vec::new();
+                } else {
+                    let new_idx = index.min(self.data_sources.len() - 1);
+                    self.switch_to_source(new_idx, self.data_sources[new_idx].selected_sheet_index);
                 }
             }
-            Err(e) => {
-                log::error!("Failed to load {}: {}", path_str, e);
+            Some(selected) => {
+                if selected > index {
+                    self.selected_index = Some(selected - 1);
+                }
+                self.viewer.data_sources = self.data_sources.clone();
             }
+            None => self.viewer.data_sources = self.data_sources.clone(),
         }
+        removed
     }
 
-    pub fn add_project(&mut self) {
+    /// Closes a project and unloads the data sources that no other open project uses. The
+    /// project stays among the recent projects.
+    pub fn close_project(&mut self, index: usize) -> Result<(), String> {
         let projects = self.config.projects.get_or_insert_with(Vec::new);
-        let project = Project::new(format!("New Project {}", projects.len() + 1));
-        projects.push(project.configuration);
-        if let Err(e) = self.config.save() {
-            log::error!("Failed to save config after adding project: {}", e);
+        if index >= projects.len() {
+            return Err(format!("There is no project {} to close", index));
+        }
+        let project = projects.remove(index);
+
+        for source in &project.data_sources {
+            let used = self.config.projects.iter().flatten().any(|p| p.data_sources.contains(source));
+            if !used && let Some(ds_idx) = self.data_sources.iter().position(|ds| &ds.path == source) {
+                self.unload_data_source(ds_idx);
+            }
+        }
+
+        if !project.path.as_os_str().is_empty() {
+            self.config.remember_project(&project.path);
         }
+        self.config.save()
     }
 
-    pub fn switch_to_source(&mut self, index: usize, sheet_idx: usize) {
-        // Save the current table state back to its source
+    /// Writes the current table state back to its sheet.
+    fn store_current_sheet(&mut self) {
         if let Some(old_idx) = self.selected_index {
             let old_ds = &mut self.data_sources[old_idx];
             let old_sheet = &mut old_ds.sheets[old_ds.selected_sheet_index];
             old_sheet.table = self.table.clone();
             old_sheet.column_configs = self.viewer.column_configs.clone();
+            old_sheet.conditional_formats = self.viewer.formatting.formats.clone();
+            old_sheet.frozen = self.viewer.frozen.clone();
+            old_sheet.row_density = self.viewer.row_density;
+        }
+    }
 
+    pub fn switch_to_source(&mut self, index: usize, sheet_idx: usize) {
+        // Save the current table state back to its source
+        if let Some(old_idx) = self.selected_index {
+            self.store_current_sheet();
             self.save_source_config(old_idx);
         }
 
@@ -194,7 +327,15 @@ impl RootViewModel {
         self.table = sheet.table.clone();
         self.viewer.config = self.config.clone();
         self.viewer.column_configs = sheet.column_configs.clone();
+        self.viewer.formatting = ConditionalFormatting::new(sheet.conditional_formats.clone());
+        self.viewer.frozen = sheet.frozen.clone();
+        self.viewer.row_density = sheet.row_density;
         self.viewer.data_sources = self.data_sources.clone();
+        self.viewer.key_issues = KeyIssues::default();
+        self.viewer.problems = Problems::default();
+        self.viewer.validation_message = None;
+        self.viewer.paste_issues.clear();
+        self.update_project_metadata();
     }
 
     pub fn save_datasource_configuration(&mut self) {
@@ -204,6 +345,22 @@ impl RootViewModel {
     }
 
     pub fn apply_rename(&mut self, target: Rename, new_name: String) {
+        if matches!(target, Rename::Project(_) | Rename::DataSource(_) | Rename::Sheet(..)) {
+            self.record_workspace_change(format!("Rename to {}", new_name), |view_model| {
+                view_model.apply_rename_in_sheet(target, new_name);
+            });
+        } else {
+            self.apply_rename_in_sheet(target, new_name);
+            self.table.mark_as_modified();
+            self.record_history();
+        }
+    }
+
+    fn apply_rename_in_sheet(&mut self, target: Rename, new_name: String) {
+        let old_column_name = match target {
+            Rename::Column(idx) => self.viewer.column_configs.get(idx).map(|c| c.name.clone()),
+            _ => None,
+        };
         Rename::apply(
             target,
             new_name,
@@ -212,6 +369,332 @@ impl RootViewModel {
             &mut self.table,
             &mut self.viewer.column_configs,
         );
+        if let (Rename::Column(idx), Some(old_name)) = (target, old_column_name) {
+            self.viewer.formatting.rename_column(&old_name, &self.viewer.column_configs[idx].name);
+        }
         self.save_datasource_configuration();
     }
+
+    /// Saves the frozen columns, pinned rows and row density of the shown sheet.
+    pub fn save_sheet_layout(&mut self) {
+        if let Some((source, sheet)) = self.current_sheet_index()
+            && let Some(data_sheet) = self.data_sources[source].sheets.get_mut(sheet) {
+            data_sheet.frozen = self.viewer.frozen.clone();
+            data_sheet.row_density = self.viewer.row_density;
+            self.save_source_config(source);
+        }
+    }
+
+    /// Keeps the manual row order of the shown sheet, so it is saved from now on.
+    pub fn keep_manual_row_order(&mut self) {
+        if let Some((source, sheet)) = self.current_sheet_index()
+            && let Some(data_sheet) = self.data_sources[source].sheets.get_mut(sheet) {
+            data_sheet.manual_row_order = true;
+        }
+    }
+
+    /// The indices of the current data source and its shown sheet.
+    pub fn current_sheet_index(&self) -> Option<(usize, usize)> {
+        let index = self.selected_index?;
+        Some((index, self.data_sources.get(index)?.selected_sheet_index))
+    }
+
+    fn current_sheet_id(&self) -> Option<SheetId> {
+        let ds = self.data_sources.get(self.selected_index?)?;
+        Some(SheetId { source: ds.path.clone(), sheet: ds.selected_sheet_index })
+    }
+
+    fn current_sheet_location(&self) -> String {
+        match self.selected_index.and_then(|i| self.data_sources.get(i)) {
+            Some(ds) => Self::sheet_location(ds, ds.selected_sheet_index),
+            None => String::new(),
+        }
+    }
+
+    fn sheet_location(ds: &DataSource, sheet_idx: usize) -> String {
+        let source_name = ds.name.as_ref().unwrap_or(&ds.path);
+        match ds.sheets.get(sheet_idx) {
+            Some(sheet) => format!("{} > {}", source_name, sheet.display_name.as_ref().unwrap_or(&sheet.name)),
+            None => source_name.clone(),
+        }
+    }
+
+    /// Restores the steps saved in the journals of the data sources, so they can be undone
+    /// after a restart.
+    fn load_history(data_sources: &[DataSource]) -> History {
+        // The journal of a newer version of Correlate is left alone, like its `.correlate` file.
+        let mut entries: Vec<HistoryEntry> = data_sources.iter()
+            .filter(|ds| !ds.config.read_only)
+            .flat_map(|ds| {
+                Journal::load(&ds.path, ds.fingerprint, &ds.config).into_iter()
+                    .map(|entry| HistoryEntry::from_journal(&ds.path, Self::sheet_location(ds, entry.sheet), entry))
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.timestamp);
+        History::with_entries(entries)
+    }
+
+    fn save_journal(&self, source: &str) {
+        let Some(ds) = self.data_sources.iter().find(|ds| ds.path == source && !ds.config.read_only) else {
+            return;
+        };
+        let entries = self.history.journal(source, |sheet, state| {
+            let data_sheet = &ds.sheets[sheet.sheet];
+            DataSheetConfiguration::from_sheet(
+                data_sheet.name.clone(),
+                data_sheet.display_name.clone(),
+                &state.column_configs,
+                &state.formats,
+                &state.rows,
+                data_sheet.manual_row_order,
+            )
+        });
+        if let Err(e) = Journal::save(&ds.path, ds.fingerprint, entries) {
+            log::error!("Failed to save journal for {}: {}", ds.path, e);
+        }
+    }
+
+    /// Records the changes of the current sheet as a step in the history.
+    pub fn record_history(&mut self) {
+        let Some(sheet) = self.current_sheet_id() else {
+            return;
+        };
+        // Comparing all rows is only needed after the table was modified.
+        let recorded = Some((sheet.clone(), self.table.modifications()));
+        if self.recorded_modifications == recorded
+            && !self.history.columns_changed(&sheet, &self.viewer.column_configs, &self.viewer.formatting.formats) {
+            return;
+        }
+        self.recorded_modifications = recorded;
+
+        let state = SheetState {
+            rows: self.table.iter().cloned().collect(),
+            column_configs: self.viewer.column_configs.clone(),
+            formats: self.viewer.formatting.formats.clone(),
+        };
+        let location = self.current_sheet_location();
+        if self.history.record_sheet(sheet.clone(), location, state) {
+            self.save_journal(&sheet.source);
+        }
+    }
+
+    /// Keeps the trashed columns of the current sheet in step with undo and redo: a column that
+    /// is back in its sheet is taken out of the trash, and put back once it is removed again.
+    pub fn sync_trashed_columns(&mut self) {
+        let Some((source, sheet)) = self.current_sheet_index() else {
+            return;
+        };
+        let path = &self.data_sources[source].path;
+        let column_configs = &self.viewer.column_configs;
+        // Whether the entry is a column of the current sheet, and that column is shown.
+        let shown = |entry: &TrashEntry| match &entry.item {
+            TrashedItem::Column { source, sheet: index, column, .. } if source == path && *index == sheet => {
+                Some(column_configs.iter().any(|c| c.name == column.name))
+            }
+            _ => None,
+        };
+
+        let restored = self.config.trash.entries().iter()
+            .filter(|entry| shown(entry) == Some(true))
+            .cloned()
+            .collect::<Vec<_>>();
+        let (removed, untrashed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.untrashed_columns).into_iter()
+            .partition(|entry| shown(entry) == Some(false));
+        self.untrashed_columns = untrashed;
+        if restored.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        for entry in restored {
+            self.config.trash.remove_entry(&entry);
+            self.untrashed_columns.push(entry);
+        }
+        for entry in removed {
+            self.config.trash.insert_entry(entry);
+        }
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save config after undoing the trashing of a column: {}", e);
+        }
+    }
+
+    /// Replaces the values of `(row, column)` cells of a sheet with the same column of the given
+    /// rows, as a single step in the history. The current sheet is changed through the undo
+    /// history of its table; other sheets are changed in place and saved.
+    pub fn replace_cells(&mut self, source: usize, sheet: usize, cells: Vec<(usize, usize, Row)>) {
+        if self.current_sheet_index() == Some((source, sheet)) {
+            self.table.set_cells(&mut self.viewer, cells);
+            return;
+        }
+
+        let sheet_state = |data_sheet: &DataSheet| SheetState {
+            rows: data_sheet.table.iter().cloned().collect(),
+            column_configs: data_sheet.column_configs.clone(),
+            formats: data_sheet.conditional_formats.clone(),
+        };
+        let ds = &mut self.data_sources[source];
+        let id = SheetId { source: ds.path.clone(), sheet };
+        let location = Self::sheet_location(ds, sheet);
+
+        // Start tracking the sheet first, so the replacement becomes a step of its own.
+        self.history.record_sheet(id.clone(), location.clone(), sheet_state(&ds.sheets[sheet]));
+        let data_sheet = &mut ds.sheets[sheet];
+        for (row, column, value) in cells {
+            data_sheet.table[row].cells[column] = value.cells[column].clone();
+        }
+        if self.history.record_sheet(id.clone(), location, sheet_state(data_sheet)) {
+            self.save_journal(&id.source);
+        }
+
+        self.save_source_config(source);
+        self.viewer.data_sources = self.data_sources.clone();
+    }
+
+    fn workspace_state(&mut self) -> WorkspaceState {
+        self.store_current_sheet();
+        WorkspaceState {
+            projects: self.config.projects.clone(),
+            data_sources: self.data_sources.clone(),
+            selected_index: self.selected_index,
+            trashed: Vec::new(),
+            untrashed: Vec::new(),
+        }
+    }
+
+    /// Applies a change to the projects or data sources, and records it in the history.
+    pub fn record_workspace_change(&mut self, description: String, change: impl FnOnce(&mut Self)) {
+        let before = self.workspace_state();
+        let trash = self.config.trash.entries().to_vec();
+        change(self);
+        self.record_workspace_step(description, before, trash);
+    }
+
+    /// Like [`Self::record_workspace_change`], but only records the change when it succeeded.
+    pub fn try_workspace_change(&mut self, description: String, change: impl FnOnce(&mut Self) -> Result<(), String>) -> Result<(), String> {
+        let before = self.workspace_state();
+        let trash = self.config.trash.entries().to_vec();
+        change(self)?;
+        self.record_workspace_step(description, before, trash);
+        Ok(())
+    }
+
+    /// Records a workspace change, along with the entries it moved into or out of the `trash`
+    /// as it was before the change.
+    fn record_workspace_step(&mut self, description: String, mut before: WorkspaceState, trash: Vec<TrashEntry>) {
+        let mut after = self.workspace_state();
+        let entries = self.config.trash.entries();
+        after.trashed = entries.iter().filter(|e| !trash.contains(e)).cloned().collect();
+        after.untrashed = trash.iter().filter(|e| !entries.contains(e)).cloned().collect();
+        before.trashed = after.untrashed.clone();
+        before.untrashed = after.trashed.clone();
+        self.history.record_workspace(description, before, after);
+    }
+
+    /// Undoes or redoes steps until `position` steps of the history are applied. Steps of
+    /// commands are left to [`crate::application_command::ApplicationCommandDispatcher::jump_in_history`], which handles them.
+    pub fn jump_in_history(&mut self, position: usize) {
+        let mut sources = Vec::new();
+        for state in self.history.jump_to(position) {
+            match state {
+                HistoryState::Sheet(sheet, state) => {
+                    self.restore_sheet(&sheet, &state);
+                    sources.push(sheet.source);
+                }
+                HistoryState::Saved(sheet, config) => {
+                    self.restore_saved_sheet(&sheet, &config);
+                    sources.push(sheet.source);
+                }
+                HistoryState::Workspace(state) => self.restore_workspace(&state),
+                // Commands are handled by the dispatcher before the jump.
+                HistoryState::Command(_) => {}
+            }
+        }
+
+        sources.sort();
+        sources.dedup();
+        for source in sources {
+            self.save_journal(&source);
+        }
+    }
+
+    fn restore_sheet(&mut self, sheet: &SheetId, state: &SheetState) {
+        let Some(index) = self.data_sources.iter().position(|ds| ds.path == sheet.source) else {
+            return;
+        };
+        if self.current_sheet_id().as_ref() != Some(sheet) {
+            self.switch_to_source(index, sheet.sheet);
+        }
+
+        self.table = state.rows.iter().cloned().collect();
+        self.viewer.column_configs = state.column_configs.clone();
+        self.viewer.formatting = ConditionalFormatting::new(state.formats.clone());
+        self.viewer.key_issues = KeyIssues::default();
+        self.viewer.problems = Problems::default();
+        self.save_datasource_configuration();
+    }
+
+    /// Restores a sheet from its saved configuration by reloading it from its data source.
+    fn restore_saved_sheet(&mut self, sheet: &SheetId, config: &DataSheetConfiguration) {
+        let Some(index) = self.data_sources.iter().position(|ds| ds.path == sheet.source) else {
+            return;
+        };
+        if self.current_sheet_id().as_ref() == Some(sheet) {
+            // The reloaded sheet replaces the current table, so don't write it back.
+            self.selected_index = None;
+        }
+
+        let ds = &mut self.data_sources[index];
+        if let Some(sheet_config) = ds.config.sheets.get_mut(sheet.sheet) {
+            // The layout isn't part of the history, so it is kept as it is.
+            let frozen = std::mem::take(&mut sheet_config.frozen);
+            *sheet_config = DataSheetConfiguration { frozen, row_density: sheet_config.row_density, ..config.clone() };
+        }
+        if let Err(e) = ds.config.save() {
+            log::error!("Failed to save companion config for {}: {}", ds.path, e);
+            return;
+        }
+
+        // Only this sheet is replaced, so the unsaved edits of the other sheets are kept.
+        match Self::load_data_source(&ds.path) {
+            Ok(mut loaded) if sheet.sheet < loaded.sheets.len().min(ds.sheets.len()).min(ds.config.sheets.len()) => {
+                ds.sheets[sheet.sheet] = loaded.sheets.swap_remove(sheet.sheet);
+                ds.config.sheets[sheet.sheet] = loaded.config.sheets.swap_remove(sheet.sheet);
+            }
+            Ok(_) => log::error!("Failed to reload {}: sheet {} no longer exists", ds.path, sheet.sheet),
+            Err(e) => log::error!("{}", e),
+        }
+
+        self.switch_to_source(index, sheet.sheet);
+    }
+
+    fn restore_workspace(&mut self, state: &WorkspaceState) {
+        // Only the projects and what the step trashed are part of the history; shortcuts, other
+        // trashed items and the like are kept.
+        self.config.projects = state.projects.clone();
+        self.config.selected_index = state.selected_index;
+        for entry in &state.untrashed {
+            self.config.trash.remove_entry(entry);
+        }
+        for entry in &state.trashed {
+            self.config.trash.insert_entry(entry.clone());
+        }
+        self.data_sources = state.data_sources.clone();
+
+        // The restored sources already hold the content of their sheets.
+        self.selected_index = None;
+        match state.selected_index.filter(|&i| i < self.data_sources.len()) {
+            Some(index) => self.switch_to_source(index, self.data_sources[index].selected_sheet_index),
+            None => {
+                self.table = DataTable::new();
+                self.viewer.column_configs = Vec::new();
+                self.viewer.formatting = ConditionalFormatting::default();
+                self.viewer.frozen = FrozenPanes::default();
+                self.viewer.row_density = RowDensity::default();
+                self.viewer.data_sources = Vec::new();
+            }
+        }
+
+        if let Err(e) = self.config.save() {
+            log::error!("Failed to save config after restoring history: {}", e);
+        }
+    }
 }
@@ -2,6 +2,7 @@ use crate::data::*;
 use crate::view::*;
 use crate::egui_data_table::*;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use egui::{Key, Response};
 
 pub struct RowView {
@@ -9,9 +10,95 @@ pub struct RowView {
     pub row_protection: bool,
     pub hotkeys: Vec<(egui::KeyboardShortcut, UiAction)>,
     pub column_configs: Vec<ColumnConfiguration>,
+    pub formatting: ConditionalFormatting,
     pub config: Configuration,
     pub data_sources: Vec<DataSource>,
     pub visible_columns: Option<Vec<usize>>,
+    pub key_issues: KeyIssues,
+    pub problems: Problems,
+    /// Outcome of the last edit that violated a validation rule.
+    pub validation_message: Option<String>,
+    /// Values of the last paste that couldn't be converted to the type of their column.
+    pub paste_issues: Vec<PasteIssue>,
+    /// Set when the find dialog was requested from the table; `true` to replace.
+    pub find_requested: Option<bool>,
+    /// Set when rows were moved by hand, so the order of the sheet is saved.
+    pub rows_reordered: bool,
+    pub frozen: FrozenPanes,
+    pub row_density: RowDensity,
+    /// Set when columns were frozen, rows pinned or the row density changed, so the sheet is
+    /// saved.
+    pub layout_changed: bool,
+    /// Set when a column header menu or cell editor changed the configuration, so the data source
+    /// is saved.
+    pub config_changed: bool,
+    /// What the rows are filtered and pinned by, see `row_filter_hash`.
+    pub row_filter: (String, Vec<String>),
+    /// Set while a text cell is edited, whose editor starts a new line with
+    /// [`TextEditor::NEW_LINE`] instead of the table committing the edit.
+    pub multiline_editing: bool,
+}
+
+impl RowView {
+    /// A viewer of a sheet with the given columns and formats, nothing filtered or selected yet.
+    pub fn new(
+        column_configs: Vec<ColumnConfiguration>,
+        formatting: ConditionalFormatting,
+        config: Configuration,
+        data_sources: Vec<DataSource>,
+    ) -> Self {
+        Self {
+            name_filter: String::new(),
+            row_protection: false,
+            hotkeys: Vec::new(),
+            column_configs,
+            formatting,
+            config,
+            data_sources,
+            visible_columns: None,
+            key_issues: KeyIssues::default(),
+            problems: Problems::default(),
+            validation_message: None,
+            paste_issues: Vec::new(),
+            find_requested: None,
+            rows_reordered: false,
+            frozen: FrozenPanes::default(),
+            row_density: RowDensity::default(),
+            layout_changed: false,
+            config_changed: false,
+            row_filter: Default::default(),
+            multiline_editing: false,
+        }
+    }
+
+    /// Snapshot of the column state that is restored when undoing a structural column change.
+    pub fn column_snapshot(column_configs: &[ColumnConfiguration], formats: &[ConditionalFormat]) -> ColumnSnapshot {
+        ColumnSnapshot::new((column_configs.to_vec(), formats.to_vec()))
+    }
+
+    /// Whether a cell can be edited: only virtual columns are editable, and only the
+    /// "Row locked" column of a locked row.
+    pub fn is_editable(column_configs: &[ColumnConfiguration], column: usize, row_value: &Row) -> bool {
+        // We still need a way to identify the "Row locked" column if it exists.
+        // For now, let's see if we can find it by name or type if it's special.
+        // In the original it was ROW_LOCKED = 5.
+        
+        let row_locked = column_configs.iter().enumerate().find(|(_, c)| c.name == "Row locked")
+            .and_then(|(idx, _)| {
+                row_value.cells[idx].0.parse::<bool>().ok()
+            }).unwrap_or(false);
+
+        // allow editing of the locked flag, but prevent editing other columns when locked.
+        if let Some(config) = column_configs.get(column) {
+            if !config.is_virtual {
+                return false;
+            }
+            if config.name == "Row locked" {
+                return true;
+            }
+        }
+        !row_locked
+    }
 }
 
 impl RowViewer<Row> for RowView {
@@ -23,8 +110,31 @@ impl RowViewer<Row> for RowView {
         ColumnHeader::new(&mut self.column_configs).name(column)
     }
 
-    fn try_create_codec(&mut self, _: bool) -> Option<impl RowCodec<Row>> {
-        Some(crate::codec::Codec { column_configs: self.column_configs.clone() })
+    fn try_create_codec(&mut self, is_encoding: bool) -> Option<impl RowCodec<Row>> {
+        let relation_targets = if is_encoding {
+            Vec::new()
+        } else {
+            // Decoding starts a new paste, which gets its own report.
+            self.paste_issues.clear();
+            self.column_configs.iter()
+                .map(|config| match config.column_type {
+                    ColumnType::Relation => RelationTargets::resolve(&self.data_sources, config),
+                    _ => None,
+                })
+                .collect()
+        };
+        Some(crate::codec::Codec {
+            column_configs: &self.column_configs,
+            relation_targets,
+            issues: &mut self.paste_issues,
+            decoded_rows: 0,
+        })
+    }
+
+    fn column_for_header(&mut self, header: &str) -> Option<usize> {
+        self.column_configs.iter().position(|c| {
+            c.name.eq_ignore_ascii_case(header) || c.display_name.as_ref().is_some_and(|name| name.eq_ignore_ascii_case(header))
+        })
     }
 
     fn column_render_config(
@@ -54,25 +164,7 @@ impl RowViewer<Row> for RowView {
     }
 
     fn is_editable_cell(&self, column: usize, _row: usize, row_value: &Row) -> bool {
-        // We still need a way to identify the "Row locked" column if it exists.
-        // For now, let's see if we can find it by name or type if it's special.
-        // In the original it was ROW_LOCKED = 5.
-        
-        let row_locked = self.column_configs.iter().enumerate().find(|(_, c)| c.name == "Row locked")
-            .and_then(|(idx, _)| {
-                row_value.cells[idx].0.parse::<bool>().ok()
-            }).unwrap_or(false);
-
-        // allow editing of the locked flag, but prevent editing other columns when locked.
-        if let Some(config) = self.column_configs.get(column) {
-            if !config.is_virtual {
-                return false;
-            }
-            if config.name == "Row locked" {
-                return true;
-            }
-        }
-        !row_locked
+        Self::is_editable(&self.column_configs, column, row_value)
     }
 
     fn compare_cell(&self, row_l: &Row, row_r: &Row, column: usize) -> std::cmp::Ordering {
@@ -88,7 +180,11 @@ impl RowViewer<Row> for RowView {
     }
 
     fn row_filter_hash(&mut self) -> &impl std::hash::Hash {
-        &self.name_filter
+        // Pinned rows are ordered together with the filter, so both invalidate the rows.
+        if self.row_filter.0 != self.name_filter || self.row_filter.1 != self.frozen.rows {
+            self.row_filter = (self.name_filter.clone(), self.frozen.rows.clone());
+        }
+        &self.row_filter
     }
 
     fn filter_row(&mut self, row: &Row) -> bool {
@@ -101,11 +197,45 @@ impl RowViewer<Row> for RowView {
         }
     }
 
-    fn show_cell_view(&mut self, ui: &mut egui::Ui, row: &Row, column: usize) {
+    fn is_pinned_row(&mut self, row: &Row) -> bool {
+        self.frozen.is_pinned(&ColumnConfiguration::key_column_indices(&self.column_configs), row)
+    }
+
+    fn frozen_columns(&mut self) -> usize {
+        self.frozen.columns
+    }
+
+    fn show_cell_view(&mut self, ui: &mut egui::Ui, row_idx: usize, row: &Row, column: usize) {
         if let Some(config) = self.column_configs.get_mut(column) {
             config.width = Some(ui.available_width());
         }
 
+        // Conditional formatting is painted first, so it ends up behind the cell content.
+        let format = self.formatting.resolve(&self.column_configs, row, column);
+        let rect = ui.max_rect();
+        if let Some([r, g, b]) = format.style.background {
+            ui.painter().rect_filled(rect, egui::CornerRadius::ZERO, egui::Color32::from_rgb(r, g, b));
+        }
+        if let Some((fraction, [r, g, b])) = format.data_bar {
+            let bar = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width() * fraction.clamp(0.0, 1.0), rect.height()));
+            ui.painter().rect_filled(bar.shrink2(egui::vec2(0.0, 2.0)), 2.0, egui::Color32::from_rgb(r, g, b).gamma_multiply(0.6));
+        }
+        let styled = |text: &str| {
+            let mut text = egui::RichText::new(text);
+            if let Some([r, g, b]) = format.style.text_color {
+                text = text.color(egui::Color32::from_rgb(r, g, b));
+            }
+            if format.style.bold {
+                text = text.strong();
+            }
+            text
+        };
+        let wrap_text = self.column_configs[column].wrap_text;
+        let label = |ui: &mut egui::Ui, text: &str| {
+            let label = egui::Label::new(styled(text));
+            ui.add(if wrap_text { label.wrap() } else { label })
+        };
+
         let cell = &row.cells[column];
         let resp = match self.column_configs[column].column_type {
             ColumnType::Bool => {
@@ -128,7 +258,7 @@ impl RowViewer<Row> for RowView {
                         ui.button(&cell.0)
                     }).inner
                 } else {
-                    ui.label(&cell.0)
+                    label(ui, &cell.0)
                 }
             }
             ColumnType::Relation => {
@@ -136,9 +266,9 @@ impl RowViewer<Row> for RowView {
                 if let Ok(rel) = cell.0.parse::<Relation>() {
                     display = rel.value;
                 }
-                ui.label(display)
+                label(ui, &display)
             }
-            _ => ui.label(&cell.0),
+            _ => label(ui, &cell.0),
         };
 
         if let Some(config) = self.column_configs.get(column) {
@@ -151,6 +281,20 @@ impl RowViewer<Row> for RowView {
             }
         }
 
+        if let Some(problem) = self.problems.cell_problem(row_idx, column) {
+            // Mark invalid cells with a red triangle in the top-right corner.
+            let rect = ui.max_rect();
+            let size = 6.0;
+            ui.painter().add(egui::Shape::convex_polygon(
+                vec![rect.right_top(), rect.right_top() + egui::vec2(0.0, size), rect.right_top() - egui::vec2(size, 0.0)],
+                ui.visuals().error_fg_color,
+                egui::Stroke::NONE,
+            ));
+            // Cell views are rendered disabled, hence the disabled hover text.
+            ui.interact(rect, ui.id().with(("problem", row_idx, column)), egui::Sense::hover())
+                .on_disabled_hover_text(problem.error.to_string());
+        }
+
         resp.context_menu(|ui| {
             CentralPanel::ui_row_context_menu(self, ui, column);
         });
@@ -190,24 +334,80 @@ impl RowViewer<Row> for RowView {
         let column_config = self.column_configs.get_mut(column)?;
         let column_type = column_config.column_type;
         let cell_value = &mut row.cells[column];
+        self.multiline_editing = column_type == ColumnType::Text;
 
-        let view_model_ptr = ui.ctx().data(|d| d.get_temp::<usize>(egui::Id::new("root_view_model"))).expect("RootViewModel pointer not found in egui data");
-        let view_model = unsafe { &mut *(view_model_ptr as *mut RootViewModel) };
-
-        column_type.show_editor(ui, cell_value, column_config, view_model)
+        let mut context = EditorContext {
+            data_sources: &self.data_sources,
+            save_requested: &mut self.config_changed,
+        };
+        column_type.show_editor(ui, cell_value, column_config, &mut context)
     }
 
     fn set_cell_value(&mut self, src: &Row, dst: &mut Row, column: usize) {
         dst.cells[column] = src.cells[column].clone();
     }
 
+    fn fill_series(&mut self, seed: &[(&Row, usize)], targets: &[(&Row, usize)]) -> Vec<Option<Row>> {
+        let values = seed.iter().map(|(row, column)| row.cells[*column].0.as_str()).collect::<Vec<_>>();
+        let config = seed.first().and_then(|(_, column)| self.column_configs.get(*column));
+
+        let series = match config.map(|c| c.column_type) {
+            // These values don't form a series.
+            Some(ColumnType::Bool | ColumnType::MultiSelect | ColumnType::Relation) => {
+                (0..targets.len()).map(|i| values[i % values.len()].to_string()).collect()
+            }
+            Some(ColumnType::Select) => {
+                let options = config.and_then(|c| c.allowed_values.as_ref())
+                    .map(|values| values.iter().map(|v| v.value.clone()).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                continue_series(&values, &options, targets.len())
+            }
+            _ => continue_series(&values, &[], targets.len()),
+        };
+
+        // A horizontal fill crosses columns, so each value is converted to the type of its own
+        // column; values that don't fit leave their cell unchanged.
+        let mut relation_targets = HashMap::new();
+        targets.iter().zip(series)
+            .map(|((row, column), value)| {
+                let value = match self.column_configs.get(*column) {
+                    Some(config) => {
+                        let targets = relation_targets.entry(*column)
+                            .or_insert_with(|| match config.column_type {
+                                ColumnType::Relation => RelationTargets::resolve(&self.data_sources, config),
+                                _ => None,
+                            });
+                        coerce_value(&value, config, targets.as_ref()).ok()?
+                    }
+                    None => value,
+                };
+                let mut row = (*row).clone();
+                row.cells[*column] = CellValue::from(value);
+                Some(row)
+            })
+            .collect()
+    }
+
     fn confirm_cell_write_by_ui(
         &mut self,
         current: &Row,
-        _next: &Row,
-        _column: usize,
+        next: &Row,
+        column: usize,
         _context: CellWriteContext,
     ) -> bool {
+        if let Some(config) = self.column_configs.get(column) {
+            let value = &next.cells[column];
+            if *value != current.cells[column]
+                && let Err(error) = config.validation.check(&value.0, config) {
+                let name = config.display_name.as_ref().unwrap_or(&config.name);
+                if config.validation.reject_invalid {
+                    self.validation_message = Some(format!("Rejected value '{}' for {}: {}", value.0, name, error));
+                    return false;
+                }
+                self.validation_message = Some(format!("Invalid value '{}' for {}: {}", value.0, name, error));
+            }
+        }
+
         if !self.row_protection {
             return true;
         }
@@ -276,6 +476,10 @@ impl RowViewer<Row> for RowView {
         };
 
         if let Some(renaming_target) = renaming_target {
+            let old_column_name = match target {
+                RenameTarget::Column(idx) => self.column_configs.get(idx).map(|c| c.name.clone()),
+                _ => None,
+            };
             Rename::apply(
                 renaming_target,
                 new_name,
@@ -284,6 +488,9 @@ impl RowViewer<Row> for RowView {
                 table,
                 &mut self.column_configs,
             );
+            if let (RenameTarget::Column(idx), Some(old_name)) = (target, old_column_name) {
+                self.formatting.rename_column(&old_name, &self.column_configs[idx].name);
+            }
             table.mark_as_modified();
         }
     }
@@ -311,6 +518,7 @@ impl RowViewer<Row> for RowView {
             display_name: None,
             column_type: ColumnType::Text,
             is_key: false,
+            key_order: 0,
             is_name: false,
             is_virtual: true,
             is_visible: true,
@@ -318,6 +526,8 @@ impl RowViewer<Row> for RowView {
             width: None,
             allowed_values: None,
             related_source: None,
+            validation: ColumnValidation::default(),
+            wrap_text: false,
         };
         self.column_configs.insert(at, new_column);
         // Update all rows in the table
@@ -329,11 +539,66 @@ impl RowViewer<Row> for RowView {
         table.mark_as_modified();
     }
 
+    fn on_column_removed(&mut self, table: &mut DataTable<Row>, index: usize) {
+        if index >= self.column_configs.len() {
+            return;
+        }
+
+        // Remove column config, and the formats that belong to it
+        let removed = self.column_configs.remove(index);
+        self.formatting.formats.retain(|f| f.column_name != removed.name);
+
+        // Update all rows in the table
+        let mut rows = table.take();
+        for row in &mut rows {
+            if index < row.cells.len() {
+                row.cells.remove(index);
+            }
+        }
+        table.replace(rows);
+        table.mark_as_modified();
+    }
+
+    fn snapshot_columns(&mut self) -> Option<ColumnSnapshot> {
+        Some(Self::column_snapshot(&self.column_configs, &self.formatting.formats))
+    }
+
+    fn on_find_requested(&mut self, replace: bool) {
+        self.find_requested = Some(replace);
+    }
+
+    fn on_rows_reordered(&mut self, _order: &[usize]) {
+        self.rows_reordered = true;
+    }
+
+    fn restore_columns(&mut self, snapshot: &ColumnSnapshot) {
+        if let Some((column_configs, formats)) = snapshot.downcast_ref::<(Vec<ColumnConfiguration>, Vec<ConditionalFormat>)>() {
+            self.column_configs.clone_from(column_configs);
+            self.formatting.formats.clone_from(formats);
+        }
+    }
+
     fn column_header_context_menu(&mut self, ui: &mut egui::Ui, column: usize) -> HeaderResult {
-        let view_model_ptr = ui.ctx().data(|d| d.get_temp::<usize>(egui::Id::new("root_view_model"))).expect("RootViewModel pointer not found in egui data");
-        let view_model = unsafe { &mut *(view_model_ptr as *mut RootViewModel) };
-        ColumnHeader::new_with_visibility(&mut self.column_configs, self.visible_columns.clone())
-            .context_menu(ui, column, view_model)
+        let frozen_columns = self.frozen.columns;
+        let action = ColumnHeader::new_with_visibility(&mut self.column_configs, self.visible_columns.clone())
+            .context_menu(ui, column, &mut self.frozen, &self.data_sources, &mut self.formatting, &mut self.config_changed);
+        self.layout_changed |= self.frozen.columns != frozen_columns;
+        action
+    }
+
+    fn row_header_context_menu(&mut self, ui: &mut egui::Ui, _row: usize, row_data: &Row) {
+        let key_columns = ColumnConfiguration::key_column_indices(&self.column_configs);
+        let key = RowKey::from_row(&key_columns, row_data);
+        let mut pinned = self.frozen.is_pinned(&key_columns, row_data);
+        let checkbox = egui::Checkbox::new(&mut pinned, format!("{} Pin to top", egui_material_icons::icons::ICON_KEEP));
+        let resp = ui.add_enabled(key.is_some(), checkbox)
+            .on_disabled_hover_text("Rows are pinned by their key. Mark a column as key to pin rows.");
+        if resp.clicked()
+            && let Some(key) = key {
+            self.frozen.set_pinned(key.to_string(), pinned);
+            self.layout_changed = true;
+            ui.close();
+        }
     }
 
     fn row_header_double_clicked(&mut self, ctx: &egui::Context, row_idx: usize, _row: &Row) {
@@ -377,6 +642,12 @@ impl RowViewer<Row> for RowView {
             }
             res.request_focus();
         } else {
+            if let Some(issue) = self.key_issues.row_issue(row_idx) {
+                let rect = ui.max_rect();
+                ui.painter().rect_filled(rect, egui::CornerRadius::ZERO, ui.visuals().error_fg_color.gamma_multiply(0.25));
+                ui.interact(rect, ui.id().with("key_issue"), egui::Sense::hover())
+                    .on_hover_text(issue.description());
+            }
             self.show_row_header_internal(ui, row_idx, vis_row, has_any_sort, row_id_digits, vis_row_digits);
         }
         committed
@@ -386,7 +657,11 @@ impl RowViewer<Row> for RowView {
         &mut self,
         context: &UiActionContext,
     ) -> Vec<(egui::KeyboardShortcut, UiAction)> {
-        let hotkeys = default_hotkeys(context);
+        let editing = context.cursor.is_editing();
+        let mut hotkeys = self.config.shortcuts.table_hotkeys(editing);
+        if editing && self.multiline_editing {
+            hotkeys.retain(|(shortcut, _)| *shortcut != TextEditor::NEW_LINE);
+        }
         self.hotkeys.clone_from(&hotkeys);
         hotkeys
     }
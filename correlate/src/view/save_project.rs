@@ -0,0 +1,66 @@
+use std::any::Any;
+use std::path::PathBuf;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::RootViewModel;
+
+/// Writes a project to its project file, or to `path` which becomes its project file from now on.
+pub struct SaveProject {
+    pub project: usize,
+    pub path: Option<PathBuf>,
+}
+
+impl SaveProject {
+    /// Asks where to save the project to.
+    pub fn pick(project: usize, configuration: &ProjectConfiguration) -> Option<Self> {
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter("Correlate project", &[PROJECT_FILE_EXTENSION])
+            .set_file_name(format!("{}.{}", configuration.name, PROJECT_FILE_EXTENSION));
+        if let Some(dir) = configuration.path.parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        dialog.save_file().map(|path| Self { project, path: Some(path) })
+    }
+}
+
+impl ApplicationCommand for SaveProject {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        match &self.path {
+            Some(path) => format!("Save project as {}", path.display()),
+            None => "Save project".to_string(),
+        }
+    }
+}
+
+pub struct SaveProjectHandler;
+
+impl ApplicationCommandHandler for SaveProjectHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<SaveProject>() {
+            view_model.update_project_metadata();
+
+            let config = &mut view_model.config;
+            if let Some(path) = &command.path {
+                let mut path = std::path::absolute(path).map_err(|e| e.to_string())?;
+                if path.extension().is_none_or(|e| e != PROJECT_FILE_EXTENSION) {
+                    path.as_mut_os_string().push(format!(".{}", PROJECT_FILE_EXTENSION));
+                }
+                let projects = config.projects.get_or_insert_with(Vec::new);
+                if projects.iter().enumerate().any(|(i, p)| i != command.project && p.path == path) {
+                    return Err(format!("{} belongs to another open project", path.display()));
+                }
+                let project = projects.get_mut(command.project)
+                    .ok_or_else(|| format!("There is no project {} to save", command.project))?;
+                let previous = std::mem::replace(&mut project.path, path);
+                if !previous.as_os_str().is_empty() {
+                    config.remember_project(&previous);
+                }
+            }
+
+            config.save_project(command.project)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use std::any::Any;
+use crate::application_command::*;
+use crate::view::RootViewModel;
+
+pub struct SetColumnVisibility {
+    pub column: usize,
+    pub visible: bool,
+}
+impl ApplicationCommand for SetColumnVisibility {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        if self.visible { "Show column" } else { "Hide column" }.to_string()
+    }
+}
+
+pub struct SetColumnVisibilityHandler;
+impl ApplicationCommandHandler for SetColumnVisibilityHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<SetColumnVisibility>() {
+            if command.column >= view_model.viewer.column_configs.len() {
+                return Err(format!("There is no column {}", command.column));
+            }
+            let visible_count = view_model.viewer.column_configs.iter().filter(|c| c.is_visible).count();
+            if !command.visible && visible_count == 1 {
+                return Err("The last visible column can't be hidden".to_string());
+            }
+
+            // Hidden through the table, so it is undone with Ctrl+Z like hiding from the header.
+            view_model.viewer.column_configs[command.column].is_visible = command.visible;
+            view_model.table.set_column_visible(&mut view_model.viewer, command.column, command.visible);
+            view_model.viewer.config_changed = true;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use egui::{KeyboardShortcut, Modifiers};
+use crate::application_command::enqueue_command;
+use crate::data::*;
+use crate::view::*;
+
+/// A recorded shortcut that is already bound to another target.
+struct Conflict {
+    target: ShortcutTarget,
+    shortcut: KeyboardShortcut,
+    other: ShortcutTarget,
+}
+
+#[derive(Default)]
+pub struct ShortcutsView {
+    filter: String,
+    /// The target a new shortcut is recorded for.
+    recording: Option<ShortcutTarget>,
+    conflict: Option<Conflict>,
+}
+
+impl ShortcutsView {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        Self::run_pressed_commands(view_model, ctx);
+
+        let mut bindings = view_model.config.shortcuts.clone();
+        let mut open = view_model.show_shortcuts;
+        egui::Window::new(format!("{} Keyboard shortcuts", egui_material_icons::icons::ICON_KEYBOARD))
+            .open(&mut open)
+            .default_width(480.0)
+            .default_height(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("Filter"));
+                    if ui.add_enabled(!bindings.is_empty(), egui::Button::new(format!("{} Reset all to defaults", egui_material_icons::icons::ICON_RESTART_ALT))).clicked() {
+                        bindings.reset_all();
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (scope, heading) in [
+                        (ShortcutScope::Application, "Application"),
+                        (ShortcutScope::Table, "Table"),
+                        (ShortcutScope::Editing, "While editing a cell"),
+                    ] {
+                        let targets = ShortcutTarget::all().into_iter()
+                            .filter(|target| target.scope() == scope && fuzzy_score(&self.filter, &target.label()).is_some())
+                            .collect::<Vec<_>>();
+                        if targets.is_empty() {
+                            continue;
+                        }
+
+                        ui.strong(heading);
+                        egui::Grid::new(heading).num_columns(3).striped(true).min_col_width(120.0).show(ui, |ui| {
+                            for target in targets {
+                                self.ui_target(ui, &mut bindings, target);
+                                ui.end_row();
+                            }
+                        });
+                        ui.add_space(8.0);
+                    }
+                });
+            });
+        view_model.show_shortcuts = open;
+
+        self.ui_recording(&mut bindings, ctx);
+        self.ui_conflict(&mut bindings, ctx);
+
+        if bindings != view_model.config.shortcuts {
+            view_model.set_shortcuts(bindings);
+        }
+    }
+
+    /// Runs the commands of application shortcuts. Nothing runs while typing text or while a
+    /// dialog is open, so those keep their keys.
+    fn run_pressed_commands(view_model: &RootViewModel, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() || ctx.memory(|m| m.top_modal_layer()).is_some() {
+            return;
+        }
+        if let Some(command) = ctx.input_mut(|i| view_model.config.shortcuts.consume_pressed(i))
+            .and_then(|target| target.command()) {
+            enqueue_command(ctx, command);
+        }
+    }
+
+    fn ui_target(&mut self, ui: &mut egui::Ui, bindings: &mut KeyBindings, target: ShortcutTarget) {
+        ui.label(target.label());
+
+        let shortcuts = bindings.shortcuts(target);
+        let mut removed = None;
+        ui.horizontal_wrapped(|ui| {
+            for (i, shortcut) in shortcuts.iter().enumerate() {
+                let mut text = egui::RichText::new(format!("{} {}", ui.ctx().format_shortcut(shortcut), egui_material_icons::icons::ICON_CLOSE));
+                let conflict = bindings.conflict(target, *shortcut);
+                if conflict.is_some() {
+                    text = text.color(ui.visuals().error_fg_color);
+                }
+                let mut response = ui.small_button(text);
+                response = match conflict {
+                    Some(other) => response.on_hover_text(format!("Also bound to {}. Click to remove.", other.label())),
+                    None => response.on_hover_text("Remove"),
+                };
+                if response.clicked() {
+                    removed = Some(i);
+                }
+            }
+            if ui.small_button(egui_material_icons::icons::ICON_ADD).on_hover_text("Add shortcut").clicked() {
+                self.recording = Some(target);
+            }
+        });
+        if let Some(i) = removed {
+            let mut shortcuts = shortcuts;
+            shortcuts.remove(i);
+            bindings.set(target, shortcuts);
+        }
+
+        let customized = bindings.is_customized(target);
+        if ui.add_enabled(customized, egui::Button::new(egui_material_icons::icons::ICON_RESTART_ALT).small())
+            .on_hover_text("Reset to default")
+            .clicked() {
+            bindings.reset(target);
+        }
+    }
+
+    /// Waits for the next key press and adds it as a shortcut of the recorded target.
+    fn ui_recording(&mut self, bindings: &mut KeyBindings, ctx: &egui::Context) {
+        let Some(target) = self.recording else {
+            return;
+        };
+
+        let pressed = ctx.input_mut(|i| {
+            let pressed = i.events.iter().find_map(|event| match event {
+                egui::Event::Key { key, pressed: true, repeat: false, modifiers, .. } => Some(KeyboardShortcut::new(
+                    Modifiers { alt: modifiers.alt, ctrl: modifiers.ctrl, shift: modifiers.shift, mac_cmd: modifiers.mac_cmd, command: false },
+                    *key,
+                )),
+                _ => None,
+            });
+            if let Some(shortcut) = &pressed {
+                i.consume_shortcut(shortcut);
+            }
+            pressed
+        });
+
+        let mut cancel = false;
+        egui::Modal::new(egui::Id::new("record_shortcut")).show(ctx, |ui| {
+            ui.heading(target.label());
+            ui.label("Press the keys of the new shortcut.");
+            ui.add_space(8.0);
+            cancel = ui.button("Cancel").clicked();
+        });
+
+        if cancel {
+            self.recording = None;
+        } else if let Some(shortcut) = pressed {
+            self.recording = None;
+            match bindings.conflict(target, shortcut) {
+                Some(other) => self.conflict = Some(Conflict { target, shortcut, other }),
+                None => add_shortcut(bindings, target, shortcut),
+            }
+        }
+    }
+
+    /// Asks whether a recorded shortcut that is already in use should be moved to the new target.
+    fn ui_conflict(&mut self, bindings: &mut KeyBindings, ctx: &egui::Context) {
+        let Some(conflict) = &self.conflict else {
+            return;
+        };
+
+        let mut reassign = false;
+        let mut cancel = false;
+        egui::Modal::new(egui::Id::new("shortcut_conflict")).show(ctx, |ui| {
+            ui.heading("Shortcut in use");
+            ui.label(format!(
+                "{} is already bound to {}. Reassign it to {}?",
+                ctx.format_shortcut(&conflict.shortcut),
+                conflict.other.label(),
+                conflict.target.label(),
+            ));
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                reassign = ui.button("Reassign").clicked();
+                cancel = ui.button("Cancel").clicked();
+            });
+        });
+
+        if reassign {
+            let mut other_shortcuts = bindings.shortcuts(conflict.other);
+            other_shortcuts.retain(|s| *s != conflict.shortcut);
+            bindings.set(conflict.other, other_shortcuts);
+            add_shortcut(bindings, conflict.target, conflict.shortcut);
+        }
+        if reassign || cancel {
+            self.conflict = None;
+        }
+    }
+}
+
+fn add_shortcut(bindings: &mut KeyBindings, target: ShortcutTarget, shortcut: KeyboardShortcut) {
+    let mut shortcuts = bindings.shortcuts(target);
+    if !shortcuts.contains(&shortcut) {
+        shortcuts.push(shortcut);
+        bindings.set(target, shortcuts);
+    }
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+use crate::arguments::Arguments;
+use crate::data::Configuration;
+use crate::view::*;
+
+/// The application, or why it could not be started.
+pub enum StartupView {
+    Started(Box<RootView>),
+    Failed {
+        arguments: Arguments,
+        error: String,
+    },
+}
+
+impl StartupView {
+    pub fn new(arguments: Arguments) -> Self {
+        match RootView::new(&arguments) {
+            Ok(root_view) => StartupView::Started(Box::new(root_view)),
+            Err(error) => {
+                log::error!("Failed to load the configuration: {}", error);
+                StartupView::Failed { arguments, error }
+            }
+        }
+    }
+
+    fn config_path(arguments: &Arguments) -> PathBuf {
+        arguments.config.clone().unwrap_or_else(Configuration::default_path)
+    }
+
+    /// Moves the configuration aside as `<name>.bak`, so the application starts with a new one.
+    fn back_up(path: &std::path::Path) -> Result<(), String> {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        std::fs::rename(path, &backup).map_err(|e| e.to_string())
+    }
+
+    fn ui_error(ui: &mut egui::Ui, arguments: &Arguments, error: &str) -> Option<StartupView> {
+        let path = Self::config_path(arguments);
+        let mut next = None;
+
+        ui.vertical_centered(|ui| {
+            ui.set_max_width(520.0);
+            ui.add_space(48.0);
+            ui.heading(egui::RichText::new(format!("{} The configuration could not be loaded", egui_material_icons::icons::ICON_ERROR))
+                .color(ui.visuals().error_fg_color));
+            ui.add_space(12.0);
+            ui.label(path.display().to_string());
+            ui.add_space(8.0);
+            ui.label(egui::RichText::new(error).monospace());
+            ui.add_space(16.0);
+            ui.label("Fix the file and try again, or set it aside and start with an empty configuration.");
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                if ui.button(format!("{} Try again", egui_material_icons::icons::ICON_REFRESH)).clicked() {
+                    next = Some(StartupView::new(arguments.clone()));
+                }
+                if ui.button(format!("{} Back up and start over", egui_material_icons::icons::ICON_RESTART_ALT))
+                    .on_hover_text(format!("Renames the file to {}.bak", path.display()))
+                    .clicked() {
+                    next = Some(match Self::back_up(&path) {
+                        Ok(()) => StartupView::new(arguments.clone()),
+                        Err(e) => StartupView::Failed { arguments: arguments.clone(), error: format!("Failed to back up the configuration: {}", e) },
+                    });
+                }
+                if ui.button("Quit").clicked() {
+                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+        });
+        next
+    }
+}
+
+impl eframe::App for StartupView {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let next = match self {
+            StartupView::Started(root_view) => {
+                root_view.update(ctx, frame);
+                None
+            }
+            StartupView::Failed { arguments, error } => {
+                egui::CentralPanel::default().show(ctx, |ui| Self::ui_error(ui, arguments, error)).inner
+            }
+        };
+        if let Some(next) = next {
+            *self = next;
+        }
+    }
+}
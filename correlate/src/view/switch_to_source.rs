@@ -1,25 +1,37 @@
 ﻿use std::any::Any;
-use egui::{Context, Id};
 use crate::application_command::*;
 use crate::view::RootViewModel;
 
 
 pub struct SwitchToSource {
-    pub ctx: Context,
     pub index: usize,
     pub sheet_idx: usize,
 }
 impl ApplicationCommand for SwitchToSource {
     fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        "Switch sheet".to_string()
+    }
+
+    /// Switches back to the sheet that was shown before.
+    fn inverse(&self, app: &RootViewModel) -> Option<Box<dyn ApplicationCommand>> {
+        let index = app.selected_index?;
+        let sheet_idx = app.data_sources.get(index)?.selected_sheet_index;
+        Some(Box::new(SwitchToSource { index, sheet_idx }))
+    }
 }
 
 pub struct SwitchToSourceHandler;
 impl ApplicationCommandHandler for SwitchToSourceHandler {
-    fn handle(&self, cmd: &dyn Any) {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
         if let Some(command) = cmd.downcast_ref::<SwitchToSource>() {
-            let view_model_ptr = command.ctx.data(|d| d.get_temp::<usize>(Id::new("root_view_model"))).unwrap();
-            let view_model = unsafe { &mut *(view_model_ptr as *mut RootViewModel) };
+            let sheet_count = view_model.data_sources.get(command.index).map(|ds| ds.sheets.len());
+            if sheet_count.is_none_or(|count| command.sheet_idx >= count) {
+                return Err(format!("There is no sheet {} in data source {}", command.sheet_idx, command.index));
+            }
             view_model.switch_to_source(command.index, command.sheet_idx);
         }
+        Ok(())
     }
 }
\ No newline at end of file
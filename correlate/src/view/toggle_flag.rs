@@ -0,0 +1,81 @@
+use std::any::Any;
+use crate::application_command::*;
+use crate::view::RootViewModel;
+
+/// Settings of the "Flags" menu that can be switched on and off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    RowProtection,
+    SingleClickEdit,
+    AutoShrinkX,
+    AutoShrinkY,
+    ShowProblems,
+    ShowHistory,
+    ShowShortcuts,
+    ShowTrash,
+}
+
+impl Flag {
+    pub const ALL: [Flag; 8] = [
+        Flag::RowProtection,
+        Flag::SingleClickEdit,
+        Flag::AutoShrinkX,
+        Flag::AutoShrinkY,
+        Flag::ShowProblems,
+        Flag::ShowHistory,
+        Flag::ShowShortcuts,
+        Flag::ShowTrash,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Flag::RowProtection => "Row protection",
+            Flag::SingleClickEdit => "Single click edit",
+            Flag::AutoShrinkX => "Auto-shrink X",
+            Flag::AutoShrinkY => "Auto-shrink Y",
+            Flag::ShowProblems => "Show problems",
+            Flag::ShowHistory => "Show history",
+            Flag::ShowShortcuts => "Show keyboard shortcuts",
+            Flag::ShowTrash => "Show trash",
+        }
+    }
+
+    pub fn value_mut<'a>(&self, view_model: &'a mut RootViewModel) -> &'a mut bool {
+        match self {
+            Flag::RowProtection => &mut view_model.viewer.row_protection,
+            Flag::SingleClickEdit => &mut view_model.style_override.single_click_edit_mode,
+            Flag::AutoShrinkX => &mut view_model.style_override.auto_shrink.x,
+            Flag::AutoShrinkY => &mut view_model.style_override.auto_shrink.y,
+            Flag::ShowProblems => &mut view_model.show_problems,
+            Flag::ShowHistory => &mut view_model.show_history,
+            Flag::ShowShortcuts => &mut view_model.show_shortcuts,
+            Flag::ShowTrash => &mut view_model.show_trash,
+        }
+    }
+}
+
+pub struct ToggleFlag {
+    pub flag: Flag,
+}
+impl ApplicationCommand for ToggleFlag {
+    fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        format!("Toggle {}", self.flag.label().to_lowercase())
+    }
+
+    fn inverse(&self, _app: &RootViewModel) -> Option<Box<dyn ApplicationCommand>> {
+        Some(Box::new(ToggleFlag { flag: self.flag }))
+    }
+}
+
+pub struct ToggleFlagHandler;
+impl ApplicationCommandHandler for ToggleFlagHandler {
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if let Some(command) = cmd.downcast_ref::<ToggleFlag>() {
+            let value = command.flag.value_mut(view_model);
+            *value = !*value;
+        }
+        Ok(())
+    }
+}
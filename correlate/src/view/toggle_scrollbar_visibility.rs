@@ -4,20 +4,23 @@ use crate::application_command::*;
 use crate::view::RootViewModel;
 
 
-pub struct ToggleScrollBarVisibility {
-    pub ctx: egui::Context,
-}
+pub struct ToggleScrollBarVisibility;
 impl ApplicationCommand for ToggleScrollBarVisibility {
     fn as_any(&self) -> &dyn Any { self }
+
+    fn description(&self) -> String {
+        "Toggle scroll bar visibility".to_string()
+    }
+
+    fn inverse(&self, _app: &RootViewModel) -> Option<Box<dyn ApplicationCommand>> {
+        Some(Box::new(ToggleScrollBarVisibility))
+    }
 }
 
 pub struct ToggleScrollBarVisibilityHandler;
 impl ApplicationCommandHandler for ToggleScrollBarVisibilityHandler {
-    fn handle(&self, cmd: &dyn Any) {
-        if let Some(command) = cmd.downcast_ref::<ToggleScrollBarVisibility>() {
-            let view_model_ptr = command.ctx.data(|d| d.get_temp::<usize>(egui::Id::new("root_view_model"))).unwrap();
-            let view_model = unsafe { &mut *(view_model_ptr as *mut RootViewModel) };
-
+    fn handle(&self, cmd: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
+        if cmd.is::<ToggleScrollBarVisibility>() {
             view_model.scroll_bar_always_visible = !view_model.scroll_bar_always_visible;
             if view_model.scroll_bar_always_visible {
                 view_model.style_override.scroll_bar_visibility = ScrollBarVisibility::AlwaysVisible;
@@ -25,5 +28,6 @@ impl ApplicationCommandHandler for ToggleScrollBarVisibilityHandler {
                 view_model.style_override.scroll_bar_visibility = ScrollBarVisibility::VisibleWhenNeeded;
             }
         }
+        Ok(())
     }
 }
\ No newline at end of file
@@ -1,12 +1,9 @@
-﻿use egui::{Context, Id};
-use std::any::Any;
-use crate::application_command::{ApplicationCommand, ApplicationCommandHandler};
+﻿use std::any::Any;
+use crate::application_command::{ApplicationCommand, ApplicationCommandHandler, CommandResult};
 use crate::data::*;
 use crate::view::*;
-use crate::egui_data_table::DataTable;
 
 pub struct TrashColumn {
-    pub ctx: Context,
     pub column: usize,
 }
 
@@ -14,52 +11,60 @@ impl ApplicationCommand for TrashColumn {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn description(&self) -> String {
+        "Trash column".to_string()
+    }
 }
 
 pub struct TrashColumnHandler;
 
 impl TrashColumnHandler {
-    fn on_column_removed(&self, row_view: &mut RowView, table: &mut DataTable<Row>, index: usize) {
-        if index >= row_view.column_configs.len() {
-            return;
-        }
+    /// The trash entry of a virtual column, holding its values by row key. Other columns come
+    /// from the data source, so there is nothing to restore.
+    fn trashed_column(view_model: &RootViewModel, column: usize) -> Option<TrashedItem> {
+        let config = view_model.viewer.column_configs.get(column).filter(|c| c.is_virtual)?;
+        let (source, sheet) = view_model.current_sheet_index()?;
+        let keys = DataSheetConfiguration::row_keys(&view_model.viewer.column_configs, &view_model.table);
 
-        // Remove column config
-        row_view.column_configs.remove(index);
-
-        // Update all rows in the table
-        let mut rows = table.take();
-        for row in &mut rows {
-            if index < row.cells.len() {
-                row.cells.remove(index);
-            }
-        }
-        table.replace(rows);
-        table.mark_as_modified();
+        Some(TrashedItem::Column {
+            source: view_model.data_sources[source].path.clone(),
+            sheet,
+            column: Box::new(config.clone()),
+            conditional_formats: view_model.viewer.formatting.formats.iter()
+                .filter(|f| f.column_name == config.name)
+                .cloned()
+                .collect(),
+            cell_values: view_model.table.iter()
+                .zip(keys)
+                .filter(|(row, key)| !key.is_empty() && !row.cells[column].0.is_empty())
+                .map(|(row, key)| CellValueConfiguration {
+                    key: key.to_string(),
+                    column_name: config.name.clone(),
+                    value: row.cells[column].0.clone(),
+                })
+                .collect(),
+        })
     }
 }
+
 impl ApplicationCommandHandler for TrashColumnHandler {
-    fn handle(&self, command: &dyn Any) {
+    fn handle(&self, command: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
         if let Some(command) = command.downcast_ref::<TrashColumn>() {
-            let view_model_ptr = command.ctx.data(|d| d.get_temp::<usize>(Id::new("root_view_model"))).unwrap();
-            let view_model = unsafe { &mut *(view_model_ptr as *mut RootViewModel) };
             let column_idx = command.column;
+            if column_idx >= view_model.viewer.column_configs.len() {
+                return Err(format!("There is no column {} to trash", column_idx));
+            }
 
-            // let ctx = &command.ctx;
-            // egui::Modal::new(Id::new("confirm_trash_modal")).show(ctx, |ui| {
-            //     ui.set_width(300.0);
-            //     ui.heading("Confirm Trash");
-            //     ui.label(format!("Are you sure you want to delete the column '{}'?",
-            //                      view_model.viewer.column_configs[column_idx].display_name.as_ref().unwrap_or(&view_model.viewer.column_configs[column_idx].name)));
-            //     ui.add_space(10.0);
-            //     ui.horizontal(|ui| {
-            //         if ui.button("Yes, Delete").clicked() {
-            //             self.on_column_removed(&mut view_model.viewer, &mut view_model.table, column_idx);
-            //         }
-            //     });
-            // });
-            // TODO: Re-add confirmation dialog.
-            self.on_column_removed(&mut view_model.viewer, &mut view_model.table, column_idx);
+            if let Some(item) = Self::trashed_column(view_model, column_idx) {
+                view_model.config.trash.push(item);
+                if let Err(e) = view_model.config.save() {
+                    log::error!("Failed to save config after trashing column: {}", e);
+                }
+            }
+            // Removing through the table records the column and its values for undo.
+            view_model.table.remove_column(&mut view_model.viewer, column_idx);
         }
+        Ok(())
     }
 }
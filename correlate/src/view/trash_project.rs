@@ -1,10 +1,9 @@
-﻿use egui::{Context, Id};
-use std::any::Any;
-use crate::application_command::{ApplicationCommand, ApplicationCommandHandler};
+﻿use std::any::Any;
+use crate::application_command::{ApplicationCommand, ApplicationCommandHandler, CommandResult};
+use crate::data::*;
 use crate::view::*;
 
 pub struct TrashProject {
-    pub ctx: Context,
     pub project: usize,
 }
 
@@ -12,6 +11,10 @@ impl ApplicationCommand for TrashProject {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn description(&self) -> String {
+        "Trash project".to_string()
+    }
 }
 
 pub struct TrashProjectHandler;
@@ -20,7 +23,9 @@ impl TrashProjectHandler {
     pub fn remove_project(&self, view_model: &mut RootViewModel, index: usize) {
         if let Some(projects) = view_model.config.projects.as_mut() {
             if index < projects.len() {
-                projects.remove(index);
+                let project = projects.remove(index);
+                let path = project.path.clone();
+                view_model.config.trash.push(TrashedItem::Project { index, project, path });
                 if let Err(e) = view_model.config.save() {
                     log::error!("Failed to save config after removing project: {}", e);
                 }
@@ -30,36 +35,22 @@ impl TrashProjectHandler {
 }
 
 impl ApplicationCommandHandler for TrashProjectHandler {
-    fn handle(&self, command: &dyn Any) {
+    fn handle(&self, command: &dyn Any, view_model: &mut RootViewModel) -> CommandResult {
         if let Some(command) = command.downcast_ref::<TrashProject>() {
-            let view_model_ptr = command.ctx.data(|d| d.get_temp::<usize>(Id::new("root_view_model"))).unwrap();
-            let view_model = unsafe { &mut *(view_model_ptr as *mut RootViewModel) };
             let project_idx = command.project;
+            let project_count = view_model.config.projects.as_ref().map_or(0, Vec::len);
+            if project_idx >= project_count {
+                return Err(format!("There is no project {} to trash", project_idx));
+            }
 
-            let ctx = &command.ctx;
-            egui::Modal::new(Id::new("confirm_trash_project_modal")).show(ctx, |ui| {
-                ui.set_width(300.0);
-                ui.heading("Confirm Delete Project");
-                let project_name = view_model.config.projects.as_ref()
-                    .and_then(|p| p.get(project_idx))
-                    .map(|p| p.name.clone())
-                    .unwrap_or_else(|| "Unknown Project".to_string());
-                ui.label(format!("Are you sure you want to delete the project '{}'?", project_name));
-                ui.add_space(10.0);
-                ui.horizontal(|ui| {
-                    if ui.button("Yes, Delete").clicked() {
-                        self.remove_project(view_model, project_idx);
-                        //ctx.data_mut(|d| d.insert_temp(trash_project_index_id, None::<usize>));
-                    }
-                    if ui.button("Cancel").clicked() {
-                        //ctx.data_mut(|d| d.insert_temp(trash_project_index_id, None::<usize>));
-                    }
-                });
-                // if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
-                //     ctx.data_mut(|d| d.insert_temp(trash_project_index_id, None::<usize>));
-                // }
+            let project_name = view_model.config.projects.as_ref()
+                .and_then(|p| p.get(project_idx))
+                .map(|p| p.name.clone())
+                .unwrap_or_default();
+            view_model.record_workspace_change(format!("Trash project {}", project_name), |view_model| {
+                self.remove_project(view_model, project_idx);
             });
-            self.remove_project(view_model, project_idx);
         }
+        Ok(())
     }
 }
@@ -0,0 +1,169 @@
+use egui::Id;
+use crate::application_command::*;
+use crate::data::*;
+use crate::view::*;
+
+/// A removal that waits for the user to confirm it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrashConfirmation {
+    Project(usize),
+    DataSource(usize),
+    Column(usize),
+    /// Deletes an item from the trash for good.
+    Delete(usize),
+    Empty,
+}
+
+impl TrashConfirmation {
+    fn id() -> Id {
+        Id::new("trash_confirmation")
+    }
+
+    /// Asks for confirmation before the removal is run.
+    pub fn request(self, ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(Self::id(), Some(self)));
+    }
+
+    fn clear(ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(Self::id(), None::<TrashConfirmation>));
+    }
+
+    /// The question to ask, or `None` when what would be removed no longer exists.
+    fn message(&self, view_model: &RootViewModel) -> Option<String> {
+        let days = view_model.config.trash_retention_days;
+        match *self {
+            TrashConfirmation::Project(index) => {
+                let project = view_model.config.projects.as_ref()?.get(index)?;
+                Some(format!("Move project '{}' to the trash? It can be restored for {} days.", project.name, days))
+            }
+            TrashConfirmation::DataSource(index) => {
+                let ds = view_model.data_sources.get(index)?;
+                Some(format!(
+                    "Move data source '{}' to the trash? It is removed from its projects, and can be restored with its settings for {} days.",
+                    ds.name.as_ref().unwrap_or(&ds.path), days,
+                ))
+            }
+            TrashConfirmation::Column(index) => {
+                let column = view_model.viewer.column_configs.get(index)?;
+                let name = column.display_name.as_ref().unwrap_or(&column.name);
+                if column.is_virtual {
+                    Some(format!("Move column '{}' and its values to the trash? It can be restored for {} days.", name, days))
+                } else {
+                    Some(format!("Remove column '{}'? Its values come from the data source, so it is not kept in the trash.", name))
+                }
+            }
+            TrashConfirmation::Delete(index) => {
+                let entry = view_model.config.trash.entries().get(index)?;
+                Some(format!("Permanently delete {}? This can't be undone.", entry.item.description().to_lowercase()))
+            }
+            TrashConfirmation::Empty => {
+                let count = view_model.config.trash.entries().len();
+                (count > 0).then(|| format!("Permanently delete the {} items in the trash? This can't be undone.", count))
+            }
+        }
+    }
+
+    fn command(self) -> Box<dyn ApplicationCommand> {
+        match self {
+            TrashConfirmation::Project(project) => Box::new(TrashProject { project }),
+            TrashConfirmation::DataSource(data_source) => Box::new(TrashDataSource { data_source }),
+            TrashConfirmation::Column(column) => Box::new(TrashColumn { column }),
+            TrashConfirmation::Delete(entry) => Box::new(EmptyTrash { entry: Some(entry) }),
+            TrashConfirmation::Empty => Box::new(EmptyTrash { entry: None }),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct TrashView {}
+
+impl TrashView {
+    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        self.ui_confirmation(view_model, ctx);
+
+        let mut open = view_model.show_trash;
+        egui::Window::new(format!("{} Trash", egui_material_icons::icons::ICON_DELETE))
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Keep items for");
+                    let response = ui.add(egui::DragValue::new(&mut view_model.config.trash_retention_days).range(1..=365).suffix(" days"));
+                    // Saved once dragging stops, not on every step of it.
+                    if (response.drag_stopped() || response.changed() && !response.dragged())
+                        && let Err(e) = view_model.config.save() {
+                        log::error!("Failed to save config after changing the trash retention: {}", e);
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let empty = ui.add_enabled(
+                            !view_model.config.trash.is_empty(),
+                            egui::Button::new(format!("{} Empty trash", egui_material_icons::icons::ICON_DELETE_FOREVER)),
+                        );
+                        if empty.clicked() {
+                            TrashConfirmation::Empty.request(ctx);
+                        }
+                    });
+                });
+                ui.separator();
+
+                let trash = &view_model.config.trash;
+                if trash.is_empty() {
+                    ui.label("The trash is empty.");
+                    return;
+                }
+
+                let now = unix_now();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("trash_entries").num_columns(3).striped(true).show(ui, |ui| {
+                        // The most recently trashed items first.
+                        for (index, entry) in trash.entries().iter().enumerate().rev() {
+                            ui.label(entry.item.description());
+                            ui.weak(format!("{} days left", entry.days_left(now, view_model.config.trash_retention_days)));
+                            ui.horizontal(|ui| {
+                                if ui.small_button(egui_material_icons::icons::ICON_RESTORE_FROM_TRASH).on_hover_text("Restore").clicked() {
+                                    enqueue_command(ctx, Box::new(RestoreFromTrash { entry: index }));
+                                }
+                                if ui.small_button(egui_material_icons::icons::ICON_DELETE_FOREVER).on_hover_text("Delete permanently").clicked() {
+                                    TrashConfirmation::Delete(index).request(ctx);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        view_model.show_trash = open;
+    }
+
+    fn ui_confirmation(&mut self, view_model: &RootViewModel, ctx: &egui::Context) {
+        let Some(confirmation) = ctx.data(|d| d.get_temp::<Option<TrashConfirmation>>(TrashConfirmation::id())).flatten() else {
+            return;
+        };
+        let Some(message) = confirmation.message(view_model) else {
+            TrashConfirmation::clear(ctx);
+            return;
+        };
+
+        let permanent = matches!(confirmation, TrashConfirmation::Delete(_) | TrashConfirmation::Empty);
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(Id::new("confirm_trash_modal")).show(ctx, |ui| {
+            ui.set_width(320.0);
+            ui.heading(if permanent { "Delete permanently" } else { "Move to trash" });
+            ui.label(message);
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                confirmed = ui.button(if permanent { "Delete" } else { "Move to trash" }).clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+
+        if confirmed {
+            enqueue_command(ctx, confirmation.command());
+        }
+        if confirmed || cancelled || modal.should_close() {
+            TrashConfirmation::clear(ctx);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use egui::Id;
+use crate::application_command::*;
+use crate::view::*;
+
+/// What waits for the unsaved changes of projects to be saved or discarded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnsavedProjectsAction {
+    Exit,
+    Close(usize),
+}
+
+/// Asks whether to save projects with unsaved changes before they are closed, or before the
+/// application is.
+#[derive(Default)]
+pub struct UnsavedProjectsView {
+    pending: Option<UnsavedProjectsAction>,
+    /// Set once the user agreed to exit, so the next close request goes through.
+    exiting: bool,
+}
+
+impl UnsavedProjectsView {
+    fn close_id() -> Id {
+        Id::new("close_project_request")
+    }
+
+    /// Closes a project, after asking whether to save it when it has unsaved changes.
+    pub fn request_close(ctx: &egui::Context, project: usize) {
+        ctx.data_mut(|d| d.insert_temp(Self::close_id(), Some(project)));
+    }
+
+    /// The names and indices of the open projects that differ from their project files.
+    fn modified_projects(view_model: &RootViewModel) -> Vec<(usize, String)> {
+        view_model.config.projects.iter().flatten().enumerate()
+            .filter(|(_, project)| view_model.config.is_project_modified(project))
+            .map(|(index, project)| (index, project.name.clone()))
+            .collect()
+    }
+
+    pub fn ui(&mut self, view_model: &mut RootViewModel, ctx: &egui::Context) {
+        if let Some(project) = ctx.data_mut(|d| d.remove_temp::<Option<usize>>(Self::close_id())).flatten() {
+            view_model.update_project_metadata();
+            if Self::modified_projects(view_model).iter().any(|(index, _)| *index == project) {
+                self.pending = Some(UnsavedProjectsAction::Close(project));
+            } else {
+                enqueue_command(ctx, Box::new(CloseProject { project, save: false }));
+            }
+        }
+        if ctx.input(|i| i.viewport().close_requested()) && !self.exiting {
+            view_model.update_project_metadata();
+            if !Self::modified_projects(view_model).is_empty() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                self.pending = Some(UnsavedProjectsAction::Exit);
+            }
+        }
+        let Some(action) = self.pending else {
+            return;
+        };
+
+        let modified = Self::modified_projects(view_model).into_iter()
+            .filter(|(index, _)| action == UnsavedProjectsAction::Exit || action == UnsavedProjectsAction::Close(*index))
+            .collect::<Vec<_>>();
+        if modified.is_empty() {
+            self.pending = None;
+            return;
+        }
+
+        let mut save = false;
+        let mut discard = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(Id::new("unsaved_projects_modal")).show(ctx, |ui| {
+            ui.set_width(360.0);
+            ui.heading("Unsaved projects");
+            ui.label("These projects have changes that aren't saved to their project files:");
+            for (_, name) in &modified {
+                ui.label(format!("• {}", name));
+            }
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                save = ui.button("Save").clicked();
+                discard = ui.button("Don't save").clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+
+        match action {
+            UnsavedProjectsAction::Close(project) => {
+                if save || discard {
+                    enqueue_command(ctx, Box::new(CloseProject { project, save }));
+                }
+            }
+            UnsavedProjectsAction::Exit => {
+                let mut exit = discard;
+                if save {
+                    // A project that fails to save keeps the application open, with the error shown.
+                    match modified.iter().try_for_each(|(index, _)| view_model.config.save_project(*index)) {
+                        Ok(()) => exit = true,
+                        Err(reason) => view_model.command_errors.push(CommandError::Failed { command: "Save project".to_string(), reason }),
+                    }
+                }
+                if exit {
+                    self.exiting = true;
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            }
+        }
+        if save || discard || cancelled || modal.should_close() {
+            self.pending = None;
+        }
+    }
+}